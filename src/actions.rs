@@ -0,0 +1,53 @@
+use std::{
+    env,
+    fs::OpenOptions,
+    io::{self, Write},
+};
+
+use tracing::warn;
+
+use crate::Report;
+
+/// Step outputs for later steps in a workflow to use as `steps.<id>.outputs.<name>`.
+pub fn outputs(report: &Report) -> Vec<(&'static str, String)> {
+    let top_language = report
+        .languages
+        .iter()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(lang, _)| lang.clone())
+        .unwrap_or_default();
+    vec![
+        ("top_language", top_language),
+        ("total_stars", format!("{:.0}", report.total_stars)),
+        ("total_forks", format!("{:.0}", report.total_forks)),
+        ("total_watchers", format!("{:.0}", report.total_watchers)),
+        ("repos", report.repos.len().to_string()),
+        // Compact JSON stays on one line, so it doesn't need a heredoc delimiter
+        (
+            "languages",
+            serde_json::to_string(&report.languages).unwrap_or_default(),
+        ),
+    ]
+}
+
+/// Appends the [`outputs`] to `$GITHUB_OUTPUT` and `summary` to `$GITHUB_STEP_SUMMARY`. Either
+/// is skipped with a warning when not running in GitHub Actions.
+pub fn write_action_outputs(report: &Report, summary: &str) -> io::Result<()> {
+    match env::var_os("GITHUB_OUTPUT") {
+        Some(path) => {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            for (name, value) in outputs(report) {
+                writeln!(file, "{name}={value}")?;
+            }
+        }
+        None => warn!("GITHUB_OUTPUT isn't set, not writing step outputs"),
+    }
+    match env::var_os("GITHUB_STEP_SUMMARY") {
+        Some(path) => {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{summary}")?;
+        }
+        None => warn!("GITHUB_STEP_SUMMARY isn't set, not writing a job summary"),
+    }
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+use crate::RepoInfo;
+
+/// Repos pushed to within this many days count as recently active.
+pub const RECENT_DAYS: u64 = 90;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ActivityStats {
+    /// Left out if no repo has a known creation date
+    pub oldest_repo: Option<DatedRepo>,
+    pub newest_repo: Option<DatedRepo>,
+    /// Mean age of the repos with a known creation date
+    pub average_age_days: Option<u64>,
+    /// Repos pushed to in the last [`RECENT_DAYS`] days
+    pub recently_pushed: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DatedRepo {
+    pub full_name: String,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+}
+impl DatedRepo {
+    /// The creation date without the time of day.
+    pub fn created_date(&self) -> &str {
+        self.created_at.get(..10).unwrap_or(&self.created_at)
+    }
+}
+
+/// Ages and recent pushes of the repos, as of `now`. Timestamps that don't parse are ignored.
+pub fn activity_stats(repos: &[RepoInfo], now: SystemTime) -> ActivityStats {
+    let mut created: Vec<(SystemTime, &RepoInfo)> = repos
+        .iter()
+        .filter_map(|repo| {
+            let created_at = humantime::parse_rfc3339(repo.created_at.as_deref()?).ok()?;
+            Some((created_at, repo))
+        })
+        .collect();
+    created.sort_by_key(|(created_at, _)| *created_at);
+
+    let dated = |(_, repo): &(SystemTime, &RepoInfo)| DatedRepo {
+        full_name: repo.full_name.clone(),
+        created_at: repo.created_at.clone().unwrap_or_default(),
+    };
+    let ages: Vec<u64> = created
+        .iter()
+        .map(|(created_at, _)| {
+            now.duration_since(*created_at)
+                .unwrap_or_default()
+                .as_secs()
+        })
+        .collect();
+
+    let recent_cutoff = now - Duration::from_secs(RECENT_DAYS * SECONDS_PER_DAY);
+    let recently_pushed = repos
+        .iter()
+        .filter_map(|repo| humantime::parse_rfc3339(repo.pushed_at.as_deref()?).ok())
+        .filter(|pushed_at| *pushed_at >= recent_cutoff)
+        .count() as u32;
+
+    ActivityStats {
+        oldest_repo: created.first().map(dated),
+        newest_repo: created.last().map(dated),
+        average_age_days: (!ages.is_empty())
+            .then(|| ages.iter().sum::<u64>() / ages.len() as u64 / SECONDS_PER_DAY),
+        recently_pushed,
+    }
+}
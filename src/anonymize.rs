@@ -0,0 +1,75 @@
+use sha2::{Digest, Sha256};
+
+use crate::Report;
+
+/// A stand-in for `name` that stays the same across runs, e.g. `repo-1a2b3c4d`. Names are
+/// compared case-insensitively, like GitHub does.
+pub fn pseudonym(prefix: &str, name: &str) -> String {
+    let hash = Sha256::digest(name.to_lowercase().as_bytes());
+    format!("{prefix}-{}", hex::encode(&hash[..4]))
+}
+
+/// Replaces the repo's name, and its owner unless that is one of `known_owners`.
+pub fn anonymize_repo_name(full_name: &str, known_owners: &[&str]) -> String {
+    let Some((owner, _)) = full_name.split_once('/') else {
+        return pseudonym("repo", full_name);
+    };
+    let owner = if known_owners
+        .iter()
+        .any(|known| known.eq_ignore_ascii_case(owner))
+    {
+        owner.to_string()
+    } else {
+        pseudonym("org", owner)
+    };
+    format!("{owner}/{}", pseudonym("repo", full_name))
+}
+
+/// Replaces every repo and organization name in the report, keeping the analyzed accounts'
+/// own logins since they are shown on the profile anyway.
+pub fn anonymize_report(report: &mut Report) {
+    let logins: Vec<String> = report
+        .profiles
+        .iter()
+        .map(|profile| profile.login.clone())
+        .collect();
+    let logins: Vec<&str> = logins.iter().map(String::as_str).collect();
+    for repo in &mut report.repos {
+        repo.full_name = anonymize_repo_name(&repo.full_name, &logins);
+    }
+    if let Some(activity) = &mut report.activity {
+        for repo in [&mut activity.oldest_repo, &mut activity.newest_repo]
+            .into_iter()
+            .flatten()
+        {
+            repo.full_name = anonymize_repo_name(&repo.full_name, &logins);
+        }
+    }
+    if let Some(releases) = &mut report.releases {
+        for repo in &mut releases.repos {
+            repo.full_name = anonymize_repo_name(&repo.full_name, &logins);
+        }
+    }
+    if let Some(packages) = &mut report.packages {
+        for package in &mut packages.packages {
+            package.name = pseudonym("package", &package.name);
+            package.full_name = anonymize_repo_name(&package.full_name, &logins);
+        }
+    }
+    if let Some(discussions) = &mut report.discussions {
+        for repo in &mut discussions.repos {
+            repo.full_name = anonymize_repo_name(&repo.full_name, &logins);
+        }
+    }
+    if let Some(traffic) = &mut report.traffic {
+        for repo in &mut traffic.repos {
+            repo.full_name = anonymize_repo_name(&repo.full_name, &logins);
+        }
+    }
+    if let Some(containers) = &mut report.containers {
+        for image in &mut containers.images {
+            image.image = pseudonym("image", &image.image);
+            image.full_name = anonymize_repo_name(&image.full_name, &logins);
+        }
+    }
+}
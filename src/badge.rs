@@ -0,0 +1,103 @@
+use std::{
+    error::Error,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{card::escape, colors::language_color_or_fallback, Report};
+
+/// Rough width of a character of the badges' 11px font, which is what shields.io badges use.
+const CHAR_WIDTH: f32 = 6.5;
+const PADDING: f32 = 12.0;
+
+/// A shields.io style badge, the language on the left and its share in its color on the right.
+pub fn render_badge(language: &str, percent: f32) -> String {
+    let value = format!("{percent:.1}%");
+    let label_width = (language.chars().count() as f32 * CHAR_WIDTH + PADDING).round();
+    let value_width = (value.chars().count() as f32 * CHAR_WIDTH + PADDING).round();
+    let width = label_width + value_width;
+    let color = language_color_or_fallback(language);
+    let value_text = if is_light(color) { "#333" } else { "#fff" };
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{}: {value}">"#,
+        escape(language)
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"  <mask id="round"><rect width="{width}" height="20" rx="3" fill="white"/></mask>"#
+    )
+    .unwrap();
+    writeln!(svg, r#"  <g mask="url(#round)">"#).unwrap();
+    writeln!(
+        svg,
+        r##"    <rect width="{label_width}" height="20" fill="#555"/>"##
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"    <rect x="{label_width}" width="{value_width}" height="20" fill="{color}"/>"#
+    )
+    .unwrap();
+    writeln!(svg, "  </g>").unwrap();
+    writeln!(
+        svg,
+        r##"  <g font-family="Verdana, DejaVu Sans, sans-serif" font-size="11" text-anchor="middle"><text x="{}" y="14" fill="#fff">{}</text><text x="{}" y="14" fill="{value_text}">{value}</text></g>"##,
+        label_width / 2.0,
+        escape(language),
+        label_width + value_width / 2.0
+    )
+    .unwrap();
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Whether dark text reads better than white on the `#rrggbb` color.
+fn is_light(color: &str) -> bool {
+    let channel = |range| u8::from_str_radix(color.get(range)?, 16).ok();
+    let (Some(r), Some(g), Some(b)) = (channel(1..3), channel(3..5), channel(5..7)) else {
+        return false;
+    };
+    0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32 > 160.0
+}
+
+/// File name of a language's badge, e.g. `c-sharp.svg` for C#.
+pub fn badge_file_name(language: &str) -> String {
+    let name = language
+        .to_ascii_lowercase()
+        .replace('#', "-sharp")
+        .replace('+', "-plus");
+    let mut slug = String::new();
+    for c in name.chars() {
+        match c {
+            'a'..='z' | '0'..='9' => slug.push(c),
+            _ if !slug.ends_with('-') && !slug.is_empty() => slug.push('-'),
+            _ => {}
+        }
+    }
+    format!("{}.svg", slug.trim_end_matches('-'))
+}
+
+/// Writes a badge for each of the `count` most used languages into `dir`, creating it if
+/// needed. Returns the paths written, most used language first.
+pub fn write_badges(
+    report: &Report,
+    dir: &Path,
+    count: usize,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+    let mut langs: Vec<_> = report.languages.iter().collect();
+    langs.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    let mut paths = Vec::new();
+    for (language, percent) in langs.into_iter().take(count) {
+        let path = dir.join(badge_file_name(language));
+        fs::write(&path, render_badge(language, *percent))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
@@ -0,0 +1,68 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+/// On-disk store of API responses, revalidated with `If-None-Match` on every request. Entries
+/// are kept per token, since what a response holds depends on who asked.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CacheEntry {
+    pub etag: String,
+    /// The `rel="next"` page link that came with the response, if any
+    pub next: Option<String>,
+    pub body: String,
+}
+
+impl Cache {
+    /// Uses `$XDG_CACHE_HOME/lets-git-the-metrics`, falling back to `~/.cache`.
+    pub fn new() -> Option<Self> {
+        let base = std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(Self {
+            dir: base.join("lets-git-the-metrics"),
+        })
+    }
+
+    /// `authorization` is the request's `Authorization` header, if any. Only its hash goes into
+    /// the key.
+    fn path(&self, url: &str, authorization: Option<&str>) -> PathBuf {
+        let key = match authorization {
+            Some(authorization) => {
+                let hash = Sha256::digest(authorization.as_bytes());
+                format!("{url}\n{}", hex::encode(hash))
+            }
+            None => url.to_string(),
+        };
+        self.dir.join(format!("{:016x}.json", fnv1a(&key)))
+    }
+
+    pub fn load(&self, url: &str, authorization: Option<&str>) -> Option<CacheEntry> {
+        let json = fs::read_to_string(self.path(url, authorization)).ok()?;
+        serde_json::from_str(&json).ok()
+    }
+
+    pub fn store(&self, url: &str, authorization: Option<&str>, entry: &CacheEntry) {
+        let result = fs::create_dir_all(&self.dir).and_then(|_| {
+            fs::write(
+                self.path(url, authorization),
+                serde_json::to_string(entry).expect("cache entries are always serializable"),
+            )
+        });
+        if let Err(err) = result {
+            warn!("Failed to write cache entry for {url}: {err}");
+        }
+    }
+}
+
+/// Stable across runs and Rust versions, unlike `DefaultHasher`.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
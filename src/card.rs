@@ -0,0 +1,443 @@
+use std::{error::Error, fmt::Write};
+
+use resvg::{tiny_skia, usvg};
+
+use crate::{colors::language_color_or_fallback, sponsors::SponsorStats, theme::Theme, Report};
+
+const MAX_LANGUAGES: usize = 8;
+
+/// How the card lays out the languages.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CardLayout {
+    /// A stacked bar with a two column legend
+    #[default]
+    Normal,
+    /// Only the bar and a small legend, narrow enough for two cards side by side
+    Compact,
+    /// A row per language with its lines of code
+    Detailed,
+    /// A donut chart next to the legend
+    Donut,
+}
+
+pub struct CardOptions {
+    pub title: String,
+    pub theme: Theme,
+    /// Colors used instead of the theme's when the viewer prefers a dark color scheme, so one
+    /// card suits both of GitHub's modes. Bar colors don't switch.
+    pub dark_theme: Option<Theme>,
+    /// Fill the bars, count up the stars and fade in the legend with CSS animations. Renderers
+    /// without animation support, like the PNG one, show the finished card.
+    pub animate: bool,
+    pub layout: CardLayout,
+}
+
+/// Steps the star count goes through when counting up.
+const COUNT_STEPS: u32 = 20;
+/// How long the bars take to fill and the stars to count up, in seconds.
+const ANIMATION_SECONDS: f32 = 1.0;
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn render_svg(report: &Report, options: &CardOptions) -> String {
+    let mut langs: Vec<(&str, f32)> = report
+        .languages
+        .iter()
+        .map(|(lang, percent)| (lang.as_str(), *percent))
+        .collect();
+    langs.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    langs.truncate(MAX_LANGUAGES);
+
+    // Theme bar colors go to the languages in order of use
+    let color = |lang: &str| {
+        let bars = &options.theme.bars;
+        let themed = langs
+            .iter()
+            .position(|(card_lang, _)| *card_lang == lang)
+            .filter(|_| !bars.is_empty())
+            .map(|index| &bars[index % bars.len()]);
+        match themed {
+            Some(color) => escape(color),
+            None => language_color_or_fallback(lang).to_string(),
+        }
+    };
+
+    let mut body = String::new();
+    let (width, mut height) = match options.layout {
+        CardLayout::Normal => normal_layout(&mut body, report, options, &langs, &color),
+        CardLayout::Compact => compact_layout(&mut body, options, &langs, &color),
+        CardLayout::Detailed => detailed_layout(&mut body, report, options, &langs, &color),
+        CardLayout::Donut => donut_layout(&mut body, report, options, &langs, &color),
+    };
+    if let Some(sponsorship) = &report.sponsorship {
+        height += sponsors_line(&mut body, sponsorship, height, width);
+    }
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"  <style>text {{ font: 400 12px 'Segoe UI', Ubuntu, sans-serif; fill: {}; }} .title {{ font-weight: 600; font-size: 18px; fill: {}; }}</style>"#,
+        escape(&options.theme.text),
+        escape(&options.theme.title)
+    )
+    .unwrap();
+    // CSS beats the presentation attributes, so this wins in viewers that support it and the
+    // rasterizer falls back to the light colors
+    if let Some(dark) = &options.dark_theme {
+        writeln!(
+            svg,
+            r#"  <style>@media (prefers-color-scheme: dark) {{ text {{ fill: {}; }} .title {{ fill: {}; }} .card {{ fill: {}; stroke: {}; }} }}</style>"#,
+            escape(&dark.text),
+            escape(&dark.title),
+            escape(&dark.background),
+            escape(dark.border())
+        )
+        .unwrap();
+    }
+    if options.animate {
+        animation_styles(&mut svg);
+    }
+    writeln!(
+        svg,
+        r#"  <rect class="card" x="0.5" y="0.5" rx="4.5" width="{}" height="{}" fill="{}" stroke="{}"/>"#,
+        width - 1,
+        height - 1,
+        escape(&options.theme.background),
+        escape(options.theme.border())
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"  <text x="25" y="35" class="title">{}</text>"#,
+        escape(&options.title)
+    )
+    .unwrap();
+    svg.push_str(&body);
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// A stacked bar with a two column legend, and a second bar for the time spent if there's
+/// WakaTime data. Returns the card's width and height, like the other layouts.
+fn normal_layout(
+    svg: &mut String,
+    report: &Report,
+    options: &CardOptions,
+    langs: &[(&str, f32)],
+    color: &dyn Fn(&str) -> String,
+) -> (u32, u32) {
+    let width = 400;
+    stars_line(svg, report, options, 60, width);
+
+    let time = report.wakatime.as_ref().map(|stats| stats.percentages());
+    let legend_y = if time.is_some() { 130 } else { 110 };
+    match &time {
+        Some(time) => {
+            writeln!(svg, r#"  <text x="25" y="83">Code</text>"#).unwrap();
+            stacked_bar(svg, "code", 65, 75, (width - 90) as f32, langs, color);
+            writeln!(svg, r#"  <text x="25" y="103">Time</text>"#).unwrap();
+            stacked_bar(svg, "time", 65, 95, (width - 90) as f32, time, color);
+        }
+        None => stacked_bar(svg, "code", 25, 75, (width - 50) as f32, langs, color),
+    }
+
+    for (i, (lang, percent)) in langs.iter().enumerate() {
+        let x = 25 + (i as u32 % 2) * 175;
+        let y = legend_y + (i as u32 / 2) * 25;
+        let label = format!("{} {percent:.2}%", escape(lang));
+        legend_entry(svg, options, i, x, y, &color(lang), &label);
+    }
+    let rows = (langs.len() as u32).div_ceil(2);
+    (width, legend_y + rows * 25)
+}
+
+/// Only the bar and a tight legend, narrow enough for two cards side by side.
+fn compact_layout(
+    svg: &mut String,
+    options: &CardOptions,
+    langs: &[(&str, f32)],
+    color: &dyn Fn(&str) -> String,
+) -> (u32, u32) {
+    let width = 300;
+    stacked_bar(svg, "code", 25, 55, (width - 50) as f32, langs, color);
+    for (i, (lang, percent)) in langs.iter().enumerate() {
+        let x = 25 + (i as u32 % 2) * 130;
+        let y = 88 + (i as u32 / 2) * 20;
+        let label = format!("{} {percent:.1}%", escape(lang));
+        legend_entry(svg, options, i, x, y, &color(lang), &label);
+    }
+    let rows = (langs.len() as u32).div_ceil(2);
+    (width, 78 + rows * 20)
+}
+
+/// A row per language with its lines of code and a bar of its own.
+fn detailed_layout(
+    svg: &mut String,
+    report: &Report,
+    options: &CardOptions,
+    langs: &[(&str, f32)],
+    color: &dyn Fn(&str) -> String,
+) -> (u32, u32) {
+    let width = 400;
+    let bar_width = (width - 50) as f32;
+    stars_line(svg, report, options, 60, width);
+    for (i, (lang, percent)) in langs.iter().enumerate() {
+        let y = 90 + i as u32 * 35;
+        let loc = report.language_loc.get(*lang).copied().unwrap_or_default();
+        let color = color(lang);
+        if options.animate {
+            writeln!(
+                svg,
+                r#"  <g class="fade-in" style="animation-delay: {:.2}s">"#,
+                i as f32 * 0.1
+            )
+            .unwrap();
+        }
+        writeln!(svg, r#"  <text x="25" y="{y}">{}</text>"#, escape(lang)).unwrap();
+        writeln!(
+            svg,
+            r#"  <text x="{}" y="{y}" text-anchor="end">{loc} lines, {percent:.1}%</text>"#,
+            width - 25
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <rect x="25" y="{}" width="{bar_width}" height="8" rx="4" fill="{color}" opacity="0.2"/>"#,
+            y + 8
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <rect class="bar" x="25" y="{}" width="{:.2}" height="8" rx="4" fill="{color}"/>"#,
+            y + 8,
+            bar_width * percent / 100.0
+        )
+        .unwrap();
+        if options.animate {
+            writeln!(svg, "  </g>").unwrap();
+        }
+    }
+    (width, 85 + langs.len() as u32 * 35)
+}
+
+/// A donut chart to the right of a one column legend.
+fn donut_layout(
+    svg: &mut String,
+    report: &Report,
+    options: &CardOptions,
+    langs: &[(&str, f32)],
+    color: &dyn Fn(&str) -> String,
+) -> (u32, u32) {
+    let width = 400;
+    let (cx, cy, radius) = (310.0, 125.0, 45.0);
+    stars_line(svg, report, options, 60, width);
+
+    let circumference = 2.0 * std::f32::consts::PI * radius;
+    let mut offset = 0.0;
+    if options.animate {
+        writeln!(svg, r#"  <g class="fade-in">"#).unwrap();
+    }
+    for (lang, percent) in langs {
+        let length = circumference * percent / 100.0;
+        writeln!(
+            svg,
+            r#"  <circle cx="{cx}" cy="{cy}" r="{radius}" fill="none" stroke="{}" stroke-width="18" stroke-dasharray="{length:.2} {circumference:.2}" stroke-dashoffset="{:.2}" transform="rotate(-90 {cx} {cy})"/>"#,
+            color(lang),
+            -offset
+        )
+        .unwrap();
+        offset += length;
+    }
+    if options.animate {
+        writeln!(svg, "  </g>").unwrap();
+    }
+
+    for (i, (lang, percent)) in langs.iter().enumerate() {
+        let y = 90 + i as u32 * 22;
+        let label = format!("{} {percent:.2}%", escape(lang));
+        legend_entry(svg, options, i, 25, y, &color(lang), &label);
+    }
+    (width, (80 + langs.len() as u32 * 22).max(190))
+}
+
+/// The total stars at height `y`, counting up if animated, with the total lines of code
+/// right-aligned on a card `width` wide.
+fn stars_line(svg: &mut String, report: &Report, options: &CardOptions, y: u32, width: u32) {
+    writeln!(
+        svg,
+        r#"  <text x="{}" y="{y}" text-anchor="end">{} lines of code</text>"#,
+        width - 25,
+        short_count(report.total_loc)
+    )
+    .unwrap();
+    let stars_label = if report.weighted {
+        "Total stars (weighted)"
+    } else {
+        "Total stars"
+    };
+    if !options.animate {
+        writeln!(
+            svg,
+            r#"  <text x="25" y="{y}">{stars_label}: {:.0}</text>"#,
+            report.total_stars
+        )
+        .unwrap();
+        return;
+    }
+    // Every step of the count is its own text, each shown only for its moment. The last one is
+    // visible when not animated.
+    let step_seconds = ANIMATION_SECONDS / COUNT_STEPS as f32;
+    for step in 0..COUNT_STEPS {
+        let stars = report.total_stars * step as f32 / COUNT_STEPS as f32;
+        writeln!(
+            svg,
+            r#"  <text x="25" y="{y}" class="count-step" style="animation-delay: {:.3}s">{stars_label}: {stars:.0}</text>"#,
+            step as f32 * step_seconds
+        )
+        .unwrap();
+    }
+    writeln!(
+        svg,
+        r#"  <text x="25" y="{y}" class="count-last" style="animation-delay: {ANIMATION_SECONDS}s">{stars_label}: {:.0}</text>"#,
+        report.total_stars
+    )
+    .unwrap();
+}
+
+/// The sponsor count and monthly total below everything else, at height `y`. Returns how much
+/// taller the card gets.
+fn sponsors_line(svg: &mut String, sponsorship: &SponsorStats, y: u32, width: u32) -> u32 {
+    writeln!(
+        svg,
+        r#"  <text x="25" y="{y}">Sponsors: {}</text>"#,
+        sponsorship.sponsors
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        r#"  <text x="{}" y="{y}" text-anchor="end">{} a month</text>"#,
+        width - 25,
+        escape(&sponsorship.monthly_dollars())
+    )
+    .unwrap();
+    25
+}
+
+/// Counts shortened to fit the card, e.g. `12.3k`.
+fn short_count(count: u64) -> String {
+    match count {
+        0..=999 => count.to_string(),
+        1_000..=999_999 => format!("{:.1}k", count as f64 / 1e3),
+        _ => format!("{:.1}M", count as f64 / 1e6),
+    }
+}
+
+/// A colored dot followed by `label`, which must already be escaped. Entries fade in one after
+/// another if animated.
+fn legend_entry(
+    svg: &mut String,
+    options: &CardOptions,
+    index: usize,
+    x: u32,
+    y: u32,
+    color: &str,
+    label: &str,
+) {
+    if options.animate {
+        writeln!(
+            svg,
+            r#"  <g class="fade-in" style="animation-delay: {:.2}s">"#,
+            index as f32 * 0.1
+        )
+        .unwrap();
+    }
+    writeln!(
+        svg,
+        r#"  <circle cx="{}" cy="{}" r="5" fill="{color}"/>"#,
+        x + 5,
+        y - 4
+    )
+    .unwrap();
+    writeln!(svg, r#"  <text x="{}" y="{y}">{label}</text>"#, x + 15).unwrap();
+    if options.animate {
+        writeln!(svg, "  </g>").unwrap();
+    }
+}
+
+/// Keyframes for [`CardOptions::animate`]. Each animation ends on the element's own style, so
+/// the card looks finished wherever animations are ignored.
+fn animation_styles(svg: &mut String) {
+    writeln!(
+        svg,
+        "  <style>\
+         .bar {{ transform-box: fill-box; transform-origin: left; animation: fill {ANIMATION_SECONDS}s ease-out; }} \
+         @keyframes fill {{ from {{ transform: scaleX(0); }} }} \
+         .count-step {{ opacity: 0; animation: flash {:.3}s linear; }} \
+         @keyframes flash {{ from, to {{ opacity: 1; }} }} \
+         .count-last {{ animation: appear 0s backwards; }} \
+         @keyframes appear {{ from {{ opacity: 0; }} }} \
+         .fade-in {{ animation: fade-in 0.4s ease-in backwards; }} \
+         @keyframes fade-in {{ from {{ opacity: 0; }} }}\
+         </style>",
+        ANIMATION_SECONDS / COUNT_STEPS as f32
+    )
+    .unwrap();
+}
+
+/// Draws a bar of rounded ends split between the languages, `bar_width` wide from `x`.
+fn stacked_bar(
+    svg: &mut String,
+    id: &str,
+    x: u32,
+    y: u32,
+    bar_width: f32,
+    langs: &[(&str, f32)],
+    color: &dyn Fn(&str) -> String,
+) {
+    writeln!(svg, r#"  <mask id="{id}-mask"><rect class="bar" x="{x}" y="{y}" width="{bar_width}" height="8" rx="4" fill="white"/></mask>"#).unwrap();
+    writeln!(svg, r#"  <g mask="url(#{id}-mask)">"#).unwrap();
+    let mut offset = x as f32;
+    for (lang, percent) in langs {
+        let width = bar_width * percent / 100.0;
+        writeln!(
+            svg,
+            r#"    <rect x="{offset:.2}" y="{y}" width="{width:.2}" height="8" fill="{}"/>"#,
+            color(lang)
+        )
+        .unwrap();
+        offset += width;
+    }
+    writeln!(svg, "  </g>").unwrap();
+}
+
+/// Rasterizes a rendered card to PNG, `scale` times its size in the SVG. Text is drawn with
+/// the system fonts.
+pub fn render_png(svg: &str, scale: f32) -> Result<Vec<u8>, Box<dyn Error>> {
+    let mut options = usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = usvg::Tree::from_str(svg, &options)?;
+
+    let size = tree
+        .size()
+        .to_int_size()
+        .scale_by(scale)
+        .ok_or("PNG scale must be positive")?;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).ok_or("PNG would be empty")?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    Ok(pixmap.encode_png()?)
+}
@@ -0,0 +1,70 @@
+use std::fmt::Write;
+
+use crate::{
+    colors::{language_color_or_fallback, paint},
+    format::NumberFormat,
+    Report,
+};
+
+/// Partial blocks from one to seven eighths wide, for the fractional end of a bar.
+const PARTIAL_BLOCKS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Renders the language percentages as horizontal bars, `width` characters for 100%, with a
+/// stacked bar of every language above them. Bars use Linguist colors if `color` is set.
+pub fn render_bar_chart(
+    report: &Report,
+    width: usize,
+    color: bool,
+    format: &NumberFormat,
+) -> String {
+    let langs = format.sorted_languages(report);
+    let name_width = langs.iter().map(|(lang, _)| lang.len()).max().unwrap_or(0);
+    let paint = |text: &str, lang: &str| {
+        if color {
+            paint(text, language_color_or_fallback(lang))
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut out = String::new();
+    if color {
+        let mut used = 0;
+        for &(lang, percent) in &langs {
+            let cells = ((percent / 100.0 * width as f32).round() as usize).min(width - used);
+            out.push_str(&paint(&"█".repeat(cells), lang));
+            used += cells;
+        }
+        out.push('\n');
+    }
+
+    for (lang, percent) in langs {
+        write!(
+            out,
+            "{lang:<name_width$} {} {:>8}",
+            paint(&bar(percent, width), lang),
+            format.percent(percent)
+        )
+        .unwrap();
+        if let Some(loc) = report.language_loc.get(lang) {
+            write!(out, " {:>12} lines", format.count(*loc)).unwrap();
+        }
+        if let Some(loc) = report.unweighted_language_loc.get(lang) {
+            write!(out, " of {:>12}", format.count(*loc)).unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    out
+}
+
+/// A bar `percent`% of `width` long, padded to `width` so the percentages line up.
+fn bar(percent: f32, width: usize) -> String {
+    let eighths = (percent / 100.0 * width as f32 * 8.0).round() as usize;
+    let mut bar = "█".repeat(eighths / 8);
+    if !eighths.is_multiple_of(8) {
+        bar.push(PARTIAL_BLOCKS[eighths % 8 - 1]);
+    }
+    let len = bar.chars().count();
+    bar.extend(std::iter::repeat_n(' ', width.saturating_sub(len)));
+    bar
+}
@@ -0,0 +1,169 @@
+use std::io::{self, IsTerminal};
+
+/// Colors from GitHub Linguist's `languages.yml`, so languages look the same as on GitHub.
+const LINGUIST_COLORS: &[(&str, &str)] = &[
+    ("ActionScript", "#882b0f"),
+    ("Ada", "#02f88c"),
+    ("Agda", "#315665"),
+    ("Apex", "#1797c0"),
+    ("AppleScript", "#101f1f"),
+    ("Assembly", "#6e4c13"),
+    ("Astro", "#ff5a03"),
+    ("AutoHotkey", "#6594b9"),
+    ("Ballerina", "#ff5000"),
+    ("Batchfile", "#c1f12e"),
+    ("C", "#555555"),
+    ("C#", "#178600"),
+    ("C++", "#f34b7d"),
+    ("Cairo", "#ff4a48"),
+    ("Chapel", "#8dc63f"),
+    ("Clojure", "#db5855"),
+    ("CMake", "#da3434"),
+    ("CoffeeScript", "#244776"),
+    ("Common Lisp", "#3fb68b"),
+    ("Crystal", "#000100"),
+    ("CSS", "#563d7c"),
+    ("Cuda", "#3a4e3a"),
+    ("D", "#ba595e"),
+    ("Dart", "#00b4ab"),
+    ("Dockerfile", "#384d54"),
+    ("EJS", "#a91e50"),
+    ("Elixir", "#6e4a7e"),
+    ("Elm", "#60b5cc"),
+    ("Emacs Lisp", "#c065db"),
+    ("Erlang", "#b83998"),
+    ("F#", "#b845fc"),
+    ("Fennel", "#fff3d7"),
+    ("Fortran", "#4d41b1"),
+    ("Gherkin", "#5b2063"),
+    ("Gleam", "#ffaff3"),
+    ("GLSL", "#5686a5"),
+    ("Go", "#00add8"),
+    ("GraphQL", "#e10098"),
+    ("Groovy", "#4298b8"),
+    ("Handlebars", "#f7931e"),
+    ("Hare", "#9d7424"),
+    ("Haskell", "#5e5086"),
+    ("Haxe", "#df7900"),
+    ("HCL", "#844fba"),
+    ("HLSL", "#aace60"),
+    ("HTML", "#e34c26"),
+    ("Idris", "#b30000"),
+    ("Janet", "#0886a5"),
+    ("Java", "#b07219"),
+    ("JavaScript", "#f1e05a"),
+    ("Jinja", "#a52a22"),
+    ("JSON", "#292929"),
+    ("Jsonnet", "#0064bd"),
+    ("Julia", "#a270ba"),
+    ("Jupyter Notebook", "#da5b0b"),
+    ("Just", "#384d54"),
+    ("Kotlin", "#a97bff"),
+    ("Less", "#1d365d"),
+    ("Liquid", "#67b8de"),
+    ("LiveScript", "#499886"),
+    ("Lua", "#000080"),
+    ("Makefile", "#427819"),
+    ("Markdown", "#083fa1"),
+    ("MATLAB", "#e16737"),
+    ("MDX", "#fcb32c"),
+    ("Meson", "#007800"),
+    ("Move", "#4a137a"),
+    ("Mustache", "#724b3b"),
+    ("Nim", "#ffc200"),
+    ("Nix", "#7e7eff"),
+    ("Nunjucks", "#3d8137"),
+    ("Nushell", "#4e9906"),
+    ("Objective-C", "#438eff"),
+    ("Objective-C++", "#6866fb"),
+    ("OCaml", "#ef7a08"),
+    ("Odin", "#60affe"),
+    ("Pascal", "#e3f171"),
+    ("Perl", "#0298c3"),
+    ("PHP", "#4f5d95"),
+    ("PLpgSQL", "#336790"),
+    ("PostCSS", "#dc3a0c"),
+    ("PowerShell", "#012456"),
+    ("Processing", "#0096d8"),
+    ("Prolog", "#74283c"),
+    ("Pug", "#a86454"),
+    ("PureScript", "#1d222d"),
+    ("Python", "#3572a5"),
+    ("QML", "#44a51c"),
+    ("R", "#198ce7"),
+    ("Racket", "#3c5caa"),
+    ("Raku", "#0000fb"),
+    ("Reason", "#ff5847"),
+    ("ReScript", "#ed5051"),
+    ("Ruby", "#701516"),
+    ("Rust", "#dea584"),
+    ("Sass", "#a53b70"),
+    ("Scala", "#c22d40"),
+    ("Scheme", "#1e4aec"),
+    ("SCSS", "#c6538c"),
+    ("Shell", "#89e051"),
+    ("Smalltalk", "#596706"),
+    ("Solidity", "#aa6746"),
+    ("SQL", "#e38c00"),
+    ("Starlark", "#76d275"),
+    ("Stylus", "#ff6347"),
+    ("Svelte", "#ff3e00"),
+    ("Swift", "#f05138"),
+    ("SystemVerilog", "#dae1c2"),
+    ("Tcl", "#e4cc98"),
+    ("TeX", "#3d6117"),
+    ("TOML", "#9c4221"),
+    ("Twig", "#c1d026"),
+    ("TypeScript", "#3178c6"),
+    ("Typst", "#239dad"),
+    ("V", "#4f87c4"),
+    ("Vala", "#a56de2"),
+    ("Verilog", "#b2b7f8"),
+    ("VHDL", "#adb2cb"),
+    ("Vim Script", "#199f4b"),
+    ("Visual Basic .NET", "#945db7"),
+    ("Vue", "#41b883"),
+    ("WebAssembly", "#04133b"),
+    ("XML", "#0060ac"),
+    ("YAML", "#cb171e"),
+    ("Zig", "#ec915c"),
+];
+
+/// Used for languages Linguist doesn't give a color.
+pub const FALLBACK_COLOR: &str = "#cccccc";
+
+/// The Linguist color for a language, matched case-insensitively.
+pub fn language_color(language: &str) -> Option<&'static str> {
+    LINGUIST_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(language))
+        .map(|(_, color)| *color)
+}
+
+/// [`language_color`], or [`FALLBACK_COLOR`] if there isn't one.
+pub fn language_color_or_fallback(language: &str) -> &'static str {
+    language_color(language).unwrap_or(FALLBACK_COLOR)
+}
+
+/// Whether stdout is a terminal and `NO_COLOR` isn't set.
+pub fn stdout_supports_color() -> bool {
+    io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Wraps `text` in ANSI escapes for a `#rrggbb` truecolor foreground.
+pub fn paint(text: &str, hex: &str) -> String {
+    let Some((r, g, b)) = parse_hex(hex) else {
+        return text.to_string();
+    };
+    format!("\x1b[38;2;{r};{g};{b}m{text}\x1b[0m")
+}
+
+/// Splits a `#rrggbb` color into its channels.
+pub fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
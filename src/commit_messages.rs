@@ -0,0 +1,97 @@
+use std::{collections::BTreeMap, error::Error};
+
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{GitHub, RepoInfo};
+
+/// How many of the most common words are kept.
+const TOP_WORDS: usize = 10;
+
+/// Used for messages that don't start with a conventional commit type.
+pub const NO_TYPE: &str = "none";
+
+/// Words too common to say anything about the commits.
+const STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "with", "from", "into", "that", "this", "not", "are", "was", "when",
+    "use", "via", "its", "but", "all", "now", "also", "instead", "more", "some",
+];
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct CommitMessageStats {
+    pub commits: u32,
+    /// Characters per message, without surrounding whitespace
+    pub average_length: f32,
+    /// Characters in the first line
+    pub average_subject_length: f32,
+    /// Commits per conventional commit type (`feat`, `fix`, ...), or [`NO_TYPE`]
+    pub types: BTreeMap<String, u32>,
+    /// Most common words first
+    pub top_words: Vec<(String, u32)>,
+}
+
+/// Reads the messages of the user's commits to every repo from clones. Repos that can't be
+/// cloned are skipped.
+pub async fn collect_commit_messages(
+    connection: &GitHub,
+    repos: &[RepoInfo],
+    concurrency: usize,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let messages: Vec<Option<Vec<String>>> = futures::stream::iter(repos)
+        .map(|repo| connection.commit_messages(&repo.full_name))
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await?;
+    Ok(messages.into_iter().flatten().flatten().collect())
+}
+
+/// Summarizes commit messages. Messages of several accounts should be passed together, since
+/// the most common words can't be merged afterwards.
+pub fn message_stats(messages: &[String]) -> CommitMessageStats {
+    let mut types: BTreeMap<String, u32> = BTreeMap::new();
+    let mut words: BTreeMap<String, u32> = BTreeMap::new();
+    let (mut length, mut subject_length) = (0, 0);
+    for message in messages {
+        let subject = message.lines().next().unwrap_or_default();
+        length += message.chars().count();
+        subject_length += subject.chars().count();
+        *types
+            .entry(conventional_type(subject).unwrap_or(NO_TYPE).to_string())
+            .or_default() += 1;
+
+        for word in message.split(|c: char| !c.is_alphanumeric()) {
+            let word = word.to_lowercase();
+            if word.chars().count() < 3
+                || word.chars().all(|c| c.is_ascii_digit())
+                || STOP_WORDS.contains(&word.as_str())
+            {
+                continue;
+            }
+            *words.entry(word).or_default() += 1;
+        }
+    }
+
+    let mut top_words: Vec<(String, u32)> = words.into_iter().collect();
+    top_words.sort_by(|(_, a), (_, b)| b.cmp(a));
+    top_words.truncate(TOP_WORDS);
+    let count = messages.len().max(1) as f32;
+    CommitMessageStats {
+        commits: messages.len() as u32,
+        average_length: length as f32 / count,
+        average_subject_length: subject_length as f32 / count,
+        types,
+        top_words,
+    }
+}
+
+/// The type of a subject like `feat(parser)!: ...`.
+fn conventional_type(subject: &str) -> Option<&str> {
+    let (prefix, _) = subject.split_once(':')?;
+    let prefix = prefix.trim_end_matches('!');
+    let kind = match prefix.split_once('(') {
+        Some((kind, scope)) if scope.ends_with(')') => kind,
+        Some(_) => return None,
+        None => prefix,
+    };
+    (!kind.is_empty() && kind.chars().all(|c| c.is_ascii_lowercase())).then_some(kind)
+}
@@ -0,0 +1,104 @@
+use std::{collections::BTreeMap, error::Error, time::SystemTime};
+
+use futures::{StreamExt, TryStreamExt};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{error::MetricsError, models::CommitData, GitHub, RepoInfo};
+
+/// How many months back the commit histogram goes, including the current one.
+pub(crate) const MONTHS: i32 = 12;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct CommitStats {
+    /// Commits by the user over the last year
+    pub total: u32,
+    /// Commits per `YYYY-MM` month, including months without any
+    pub per_month: BTreeMap<String, u32>,
+}
+impl CommitStats {
+    /// Adds another account's commits to these.
+    pub fn merge(&mut self, other: CommitStats) {
+        self.total += other.total;
+        for (month, count) in other.per_month {
+            *self.per_month.entry(month).or_default() += count;
+        }
+    }
+}
+
+/// Counts the user's commits to each repo over the last year from the commit list, which
+/// unlike `stats/participation` can be filtered by author.
+pub async fn collect_commits(
+    connection: &GitHub,
+    repos: &[RepoInfo],
+    concurrency: usize,
+) -> Result<CommitStats, Box<dyn Error>> {
+    let months = recent_months(MONTHS);
+    let since = format!("{}-01T00:00:00Z", months[0]);
+
+    let dates: Vec<Vec<String>> = futures::stream::iter(repos)
+        .map(|repo| commit_dates(connection, &repo.full_name, &since))
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    let mut stats = CommitStats {
+        total: 0,
+        per_month: months.into_iter().map(|month| (month, 0)).collect(),
+    };
+    for date in dates.iter().flatten() {
+        let Some(count) = date
+            .get(..7)
+            .and_then(|month| stats.per_month.get_mut(month))
+        else {
+            continue;
+        };
+        *count += 1;
+        stats.total += 1;
+    }
+    Ok(stats)
+}
+
+/// Author dates of the user's commits to the repo's default branch since `since`.
+pub(crate) async fn commit_dates(
+    connection: &GitHub,
+    full_name: &str,
+    since: &str,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let url = connection.api_url(&format!(
+        "/repos/{full_name}/commits?author={}&since={since}",
+        connection.user
+    ));
+    let commits: Vec<CommitData> = match connection.get_all(url).await {
+        Ok(commits) => commits,
+        // Empty repos respond with 409 Conflict
+        Err(MetricsError::Status {
+            status: StatusCode::CONFLICT,
+            ..
+        }) => {
+            debug!("No commits for {full_name}, it's empty");
+            return Ok(Vec::new());
+        }
+        Err(err) => return Err(err.into()),
+    };
+    debug!("Found {} commits in {full_name}", commits.len());
+    Ok(commits
+        .into_iter()
+        .filter_map(|commit| commit.commit.author)
+        .map(|author| author.date)
+        .collect())
+}
+
+/// The last `count` months as `YYYY-MM`, oldest first.
+pub(crate) fn recent_months(count: i32) -> Vec<String> {
+    let now = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    let year: i32 = now[..4]
+        .parse()
+        .expect("RFC 3339 timestamps start with the year");
+    let month: i32 = now[5..7].parse().expect("RFC 3339 timestamps have a month");
+    let current = year * 12 + month - 1;
+    (current - count + 1..=current)
+        .map(|index| format!("{:04}-{:02}", index / 12, index % 12 + 1))
+        .collect()
+}
@@ -0,0 +1,163 @@
+use std::{collections::BTreeSet, fmt::Write};
+
+use serde::Serialize;
+
+use crate::{colors::paint, Report};
+
+/// How many of the largest differences are highlighted.
+const HIGHLIGHTED: usize = 3;
+
+const HIGHLIGHT_COLOR: &str = "#f0b400";
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ComparisonRow {
+    pub label: String,
+    pub first: f32,
+    pub second: f32,
+}
+impl ComparisonRow {
+    pub fn difference(&self) -> f32 {
+        self.second - self.first
+    }
+
+    /// The difference relative to the larger value, so counts of different sizes compare.
+    fn relative_difference(&self) -> f32 {
+        let larger = self.first.abs().max(self.second.abs());
+        if larger == 0.0 {
+            0.0
+        } else {
+            self.difference().abs() / larger
+        }
+    }
+}
+
+/// Two reports side by side.
+#[derive(Serialize, Debug, Clone)]
+pub struct Comparison {
+    pub first: String,
+    pub second: String,
+    /// Percent of each language, largest difference first
+    pub languages: Vec<ComparisonRow>,
+    /// Stars, repos and whichever contribution counts both reports have
+    pub totals: Vec<ComparisonRow>,
+}
+
+pub fn compare_reports(
+    first_name: &str,
+    first: &Report,
+    second_name: &str,
+    second: &Report,
+) -> Comparison {
+    let all_languages: BTreeSet<&String> = first
+        .languages
+        .keys()
+        .chain(second.languages.keys())
+        .collect();
+    let mut languages: Vec<ComparisonRow> = all_languages
+        .into_iter()
+        .map(|language| ComparisonRow {
+            label: language.clone(),
+            first: first.languages.get(language).copied().unwrap_or(0.0),
+            second: second.languages.get(language).copied().unwrap_or(0.0),
+        })
+        .collect();
+    languages.sort_by(|a, b| b.difference().abs().total_cmp(&a.difference().abs()));
+
+    let row = |label: &str, first: f32, second: f32| ComparisonRow {
+        label: label.to_string(),
+        first,
+        second,
+    };
+    let mut totals = vec![
+        row("Stars", first.total_stars, second.total_stars),
+        row("Forks", first.total_forks, second.total_forks),
+        row("Watchers", first.total_watchers, second.total_watchers),
+        row(
+            "Repos contributed to",
+            first.repos.len() as f32,
+            second.repos.len() as f32,
+        ),
+    ];
+    if let (Some(a), Some(b)) = (&first.commits, &second.commits) {
+        totals.push(row("Commits (last year)", a.total as f32, b.total as f32));
+    }
+    if let (Some(a), Some(b)) = (&first.contributions, &second.contributions) {
+        totals.push(row(
+            "Contributions (last year)",
+            a.total as f32,
+            b.total as f32,
+        ));
+    }
+    if let (Some(a), Some(b)) = (&first.pull_requests, &second.pull_requests) {
+        totals.push(row(
+            "Pull requests opened",
+            a.opened as f32,
+            b.opened as f32,
+        ));
+    }
+
+    Comparison {
+        first: first_name.to_string(),
+        second: second_name.to_string(),
+        languages,
+        totals,
+    }
+}
+
+/// A side by side table, marking the largest differences with `*`, and coloring them if
+/// `color` is set.
+pub fn render_comparison(comparison: &Comparison, color: bool) -> String {
+    let mut out = String::new();
+    let header = |out: &mut String, title: &str| {
+        writeln!(
+            out,
+            "  {:<26} {:>12} {:>12} {:>10}",
+            title, comparison.first, comparison.second, "Difference"
+        )
+        .unwrap();
+    };
+    let line = |out: &mut String, text: String, highlighted: bool| {
+        let marker = if highlighted { '*' } else { ' ' };
+        let text = format!("{marker} {text}");
+        if highlighted && color {
+            writeln!(out, "{}", paint(&text, HIGHLIGHT_COLOR)).unwrap();
+        } else {
+            writeln!(out, "{text}").unwrap();
+        }
+    };
+
+    header(&mut out, "Language");
+    // Languages are already sorted by the size of the difference
+    for (i, row) in comparison.languages.iter().enumerate() {
+        let text = format!(
+            "{:<26} {:>11.2}% {:>11.2}% {:>+10.2}",
+            row.label,
+            row.first,
+            row.second,
+            row.difference()
+        );
+        line(&mut out, text, i < HIGHLIGHTED && row.difference() != 0.0);
+    }
+
+    writeln!(out).unwrap();
+    header(&mut out, "Total");
+    let mut by_difference: Vec<&ComparisonRow> = comparison.totals.iter().collect();
+    by_difference.sort_by(|a, b| b.relative_difference().total_cmp(&a.relative_difference()));
+    let highlighted: Vec<&str> = by_difference
+        .iter()
+        .take(HIGHLIGHTED)
+        .filter(|row| row.difference() != 0.0)
+        .map(|row| row.label.as_str())
+        .collect();
+    for row in &comparison.totals {
+        let text = format!(
+            "{:<26} {:>12.0} {:>12.0} {:>+10.0}",
+            row.label,
+            row.first,
+            row.second,
+            row.difference()
+        );
+        line(&mut out, text, highlighted.contains(&row.label.as_str()));
+    }
+    out
+}
@@ -0,0 +1,112 @@
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use toml::Value;
+
+pub const CONFIG_FILE_NAME: &str = "lets-git-the-metrics.toml";
+
+/// Looks for a config file in the working directory, then in `$XDG_CONFIG_HOME` (or
+/// `~/.config`).
+pub fn default_config_path() -> Option<PathBuf> {
+    let local = PathBuf::from(CONFIG_FILE_NAME);
+    if local.is_file() {
+        return Some(local);
+    }
+    let global = config_dir()?.join(CONFIG_FILE_NAME);
+    global.is_file().then_some(global)
+}
+
+/// `$XDG_CONFIG_HOME`, or `~/.config`.
+fn config_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
+
+/// Options that decide where the token is sent, which a config file outside the user's config
+/// directory, like one in a cloned repo, shouldn't quietly change.
+pub const SENSITIVE_OPTIONS: [&str; 3] = ["api_url", "proxy", "ca_bundle"];
+
+/// Whether `path` is in the user's config directory.
+pub fn in_config_dir(path: &Path) -> bool {
+    let (Some(config_dir), Ok(path)) = (config_dir(), path.canonicalize()) else {
+        return false;
+    };
+    config_dir
+        .canonicalize()
+        .is_ok_and(|config_dir| path.starts_with(config_dir))
+}
+
+/// Finds the value of `--config` in raw command line arguments.
+pub fn explicit_config_path(args: &[OsString]) -> Option<PathBuf> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let arg = arg.to_string_lossy();
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+/// Reads each `key = value` in the config file as the values of the option named `key`, as
+/// they'd be written on the command line. The caller uses them as the options' defaults, so the
+/// file can set any option the CLI has and anything passed on the command line wins.
+///
+/// Each entry of a `[section]` becomes a `name=value` value, with arrays joined by commas, so
+/// `[language_groups]` with `Web = ["CSS", "HTML"]` is the same as
+/// `--language-groups Web=CSS,HTML`.
+pub fn load_options(path: &Path) -> Result<BTreeMap<String, Vec<String>>, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read config file {}: {err}", path.display()))?;
+    parse_options(&contents)
+        .map_err(|err| format!("Invalid config file {}: {err}", path.display()).into())
+}
+
+fn parse_options(contents: &str) -> Result<BTreeMap<String, Vec<String>>, Box<dyn Error>> {
+    let table: toml::Table = contents.parse()?;
+    let mut options = BTreeMap::new();
+    for (key, value) in table {
+        let values = match value {
+            Value::Array(values) => values
+                .iter()
+                .map(scalar_to_string)
+                .collect::<Result<_, _>>()?,
+            Value::Table(entries) => entries
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        Value::Array(values) => values
+                            .iter()
+                            .map(scalar_to_string)
+                            .collect::<Result<Vec<_>, _>>()?
+                            .join(","),
+                        value => scalar_to_string(&value)?,
+                    };
+                    Ok(format!("{name}={value}"))
+                })
+                .collect::<Result<_, Box<dyn Error>>>()?,
+            value => vec![scalar_to_string(&value)?],
+        };
+        options.insert(key, values);
+    }
+    Ok(options)
+}
+
+fn scalar_to_string(value: &Value) -> Result<String, Box<dyn Error>> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        value => Err(format!("Unsupported value: {value}").into()),
+    }
+}
@@ -0,0 +1,229 @@
+use std::error::Error;
+
+use futures::{StreamExt, TryStreamExt};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::{
+    error::{parse_json, parse_url, MetricsError},
+    http::USER_AGENT,
+    models::DockerHubRepoData,
+    GitHub, RepoInfo,
+};
+
+pub const DOCKER_HUB_URL: &str = "https://hub.docker.com/v2";
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ContainerStats {
+    /// Pulls of every image
+    pub pulls: u64,
+    /// Most pulled first
+    pub images: Vec<ContainerPulls>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ContainerPulls {
+    /// `Docker Hub` or `GHCR`
+    pub registry: String,
+    /// e.g. `ghcr.io/user/app`
+    pub image: String,
+    /// The repo the image is built from
+    pub full_name: String,
+    pub pulls: u64,
+}
+
+/// An image reference on a registry pull counts can be looked up on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Image {
+    /// `namespace/name`, `library/name` for official images
+    DockerHub(String),
+    /// `owner/name`
+    Ghcr(String),
+}
+impl Image {
+    /// Parses `ghcr.io/owner/name`, `docker.io/namespace/name` or the short Docker Hub forms,
+    /// ignoring any tag. `None` for other registries.
+    fn parse(reference: &str) -> Option<Image> {
+        let reference = reference.split(['@', ':']).next()?.to_ascii_lowercase();
+        if let Some(path) = reference.strip_prefix("ghcr.io/") {
+            return Some(Image::Ghcr(path.to_string()));
+        }
+        let path = reference
+            .strip_prefix("docker.io/")
+            .or_else(|| reference.strip_prefix("index.docker.io/"))
+            .unwrap_or(&reference);
+        match path.split('/').collect::<Vec<_>>()[..] {
+            // A host in front, like `quay.io/...`
+            [host, ..] if host.contains('.') => None,
+            [name] => Some(Image::DockerHub(format!("library/{name}"))),
+            [namespace, name] => Some(Image::DockerHub(format!("{namespace}/{name}"))),
+            _ => None,
+        }
+    }
+
+    fn registry(&self) -> &'static str {
+        match self {
+            Image::DockerHub(_) => "Docker Hub",
+            Image::Ghcr(_) => "GHCR",
+        }
+    }
+
+    fn reference(&self) -> String {
+        match self {
+            Image::DockerHub(path) => format!("docker.io/{path}"),
+            Image::Ghcr(path) => format!("ghcr.io/{path}"),
+        }
+    }
+}
+
+/// Counts the pulls of the images built from each repo. `images` maps repos to their images;
+/// other repos with a `Dockerfile` at the root are assumed to publish an image named after the
+/// repo on both registries, and only those that exist are counted.
+pub async fn collect_containers(
+    connection: &GitHub,
+    repos: &[RepoInfo],
+    images: &[(String, Vec<String>)],
+    concurrency: usize,
+    docker_hub_url: &str,
+) -> Result<ContainerStats, Box<dyn Error>> {
+    if connection.offline {
+        return Err(MetricsError::Offline("--metrics containers").into());
+    }
+    let per_repo: Vec<Vec<ContainerPulls>> = futures::stream::iter(repos)
+        .map(|repo| repo_containers(connection, repo, images, docker_hub_url))
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    let mut images: Vec<ContainerPulls> = per_repo.into_iter().flatten().collect();
+    images.sort_by_key(|image| std::cmp::Reverse(image.pulls));
+    Ok(ContainerStats {
+        pulls: images.iter().map(|image| image.pulls).sum(),
+        images,
+    })
+}
+
+async fn repo_containers(
+    connection: &GitHub,
+    repo: &RepoInfo,
+    images: &[(String, Vec<String>)],
+    docker_hub_url: &str,
+) -> Result<Vec<ContainerPulls>, Box<dyn Error>> {
+    let full_name = &repo.full_name;
+    let mapped = images
+        .iter()
+        .find(|(mapped, _)| mapped.eq_ignore_ascii_case(full_name));
+    let candidates: Vec<Image> = match mapped {
+        Some((_, references)) => references
+            .iter()
+            .filter_map(|reference| {
+                let image = Image::parse(reference);
+                if image.is_none() {
+                    warn!(
+                        "Can't count pulls of {reference}, only Docker Hub and GHCR are supported"
+                    );
+                }
+                image
+            })
+            .collect(),
+        None => {
+            if connection
+                .file_contents(full_name, "Dockerfile")
+                .await?
+                .is_none()
+            {
+                return Ok(Vec::new());
+            }
+            let path = full_name.to_ascii_lowercase();
+            vec![Image::DockerHub(path.clone()), Image::Ghcr(path)]
+        }
+    };
+
+    let mut pulls = Vec::new();
+    for image in candidates {
+        let count = match &image {
+            Image::DockerHub(path) => docker_hub_pulls(connection, docker_hub_url, path).await?,
+            Image::Ghcr(path) => ghcr_pulls(connection, full_name, path).await?,
+        };
+        let Some(count) = count else {
+            debug!("{} from {full_name} wasn't found", image.reference());
+            continue;
+        };
+        pulls.push(ContainerPulls {
+            registry: image.registry().to_string(),
+            image: image.reference(),
+            full_name: full_name.clone(),
+            pulls: count,
+        });
+    }
+    Ok(pulls)
+}
+
+/// `None` if Docker Hub has no such repository.
+async fn docker_hub_pulls(
+    connection: &GitHub,
+    docker_hub_url: &str,
+    path: &str,
+) -> Result<Option<u64>, MetricsError> {
+    let url = format!(
+        "{}/repositories/{path}/",
+        docker_hub_url.trim_end_matches('/')
+    );
+    let Some(body) = get_text(connection, url.as_str()).await? else {
+        return Ok(None);
+    };
+    let data: DockerHubRepoData = parse_json(&parse_url(&url)?, &body)?;
+    Ok(Some(data.pull_count))
+}
+
+/// The API has no download counts for container packages, so this reads the "Total downloads"
+/// of the package's page instead. `None` if there is no such package linked to the repo, or the
+/// page has no count.
+async fn ghcr_pulls(
+    connection: &GitHub,
+    full_name: &str,
+    path: &str,
+) -> Result<Option<u64>, MetricsError> {
+    if connection.is_enterprise() {
+        return Ok(None);
+    }
+    let name = path.split_once('/').map_or(path, |(_, name)| name);
+    let url = format!("{}/{full_name}/pkgs/container/{name}", connection.web_url());
+    let Some(page) = get_text(connection, url.as_str()).await? else {
+        return Ok(None);
+    };
+    Ok(total_downloads(&page))
+}
+
+/// The number in the `title` following "Total downloads" on a package page.
+fn total_downloads(page: &str) -> Option<u64> {
+    let after = &page[page.find("Total downloads")?..];
+    let title = &after[after.find("title=\"")? + "title=\"".len()..];
+    let digits: String = title
+        .chars()
+        .take_while(|c| *c != '"')
+        .filter(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+/// `None` if the server responds with 404 Not Found.
+async fn get_text(connection: &GitHub, url: &str) -> Result<Option<String>, MetricsError> {
+    let url = parse_url(url)?;
+    let response = connection
+        .client
+        .get(url.clone())
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .await?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(MetricsError::from_response(&url, status, &body));
+    }
+    Ok(Some(body))
+}
@@ -0,0 +1,88 @@
+use std::{fmt::Write, io, path::Path};
+
+use crate::Report;
+
+/// One row per repo, with the stars, the user's share of commits and the top language.
+pub fn render_repos_csv(report: &Report) -> String {
+    let mut csv = String::from("repository,stars,contribution_ratio,top_language\n");
+    for repo in &report.repos {
+        writeln!(
+            csv,
+            "{},{},{},{}",
+            field(&repo.full_name),
+            repo.stars,
+            repo.ratio_of_commits_from_user,
+            field(repo.primary_language().unwrap_or(""))
+        )
+        .unwrap();
+    }
+    csv
+}
+
+/// One row per language in each repo, with its lines of code.
+pub fn render_languages_csv(report: &Report) -> String {
+    let mut csv = String::from("repository,language,loc\n");
+    for repo in &report.repos {
+        let mut langs: Vec<_> = repo.language_loc_map.iter().collect();
+        langs.sort_by(|(_, a), (_, b)| b.cmp(a));
+        for (lang, loc) in langs {
+            writeln!(csv, "{},{},{loc}", field(&repo.full_name), field(lang)).unwrap();
+        }
+    }
+    csv
+}
+
+/// One row per organization member, in leaderboard order, if the report has any.
+pub fn render_members_csv(report: &Report) -> Option<String> {
+    if report.members.is_empty() {
+        return None;
+    }
+    let mut csv = String::from("rank,member,contributions,repos,stars,primary_language\n");
+    for (rank, member) in report.members.iter().enumerate() {
+        writeln!(
+            csv,
+            "{},{},{},{},{},{}",
+            rank + 1,
+            field(&member.login),
+            member.contributions,
+            member.repos,
+            member.stars,
+            field(member.primary_language.as_deref().unwrap_or(""))
+        )
+        .unwrap();
+    }
+    Some(csv)
+}
+
+/// One row per month of the contribution calendar, if the report has it.
+pub fn render_contributions_csv(report: &Report) -> Option<String> {
+    let contributions = report.contributions.as_ref()?;
+    let mut csv = String::from("month,contributions\n");
+    for (month, count) in &contributions.per_month {
+        writeln!(csv, "{month},{count}").unwrap();
+    }
+    Some(csv)
+}
+
+/// Writes `repos.csv` and `languages.csv` into `dir`, creating it if needed, plus
+/// `members.csv` for organizations and `contributions.csv` with `--metrics contributions`.
+pub fn write_csv(report: &Report, dir: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(dir.join("repos.csv"), render_repos_csv(report))?;
+    if let Some(members) = render_members_csv(report) {
+        std::fs::write(dir.join("members.csv"), members)?;
+    }
+    if let Some(contributions) = render_contributions_csv(report) {
+        std::fs::write(dir.join("contributions.csv"), contributions)?;
+    }
+    std::fs::write(dir.join("languages.csv"), render_languages_csv(report))
+}
+
+/// Quotes a field if it contains anything that would break the row, per RFC 4180.
+fn field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use crate::Report;
+
+pub struct LanguageDelta {
+    pub language: String,
+    pub old_percent: f32,
+    pub new_percent: f32,
+}
+
+pub struct StarDelta {
+    pub full_name: String,
+    pub old_stars: u32,
+    pub new_stars: u32,
+}
+
+/// What changed between two reports.
+pub struct ReportDiff {
+    /// Sorted by the size of the change, biggest first
+    pub languages: Vec<LanguageDelta>,
+    pub added_repos: Vec<String>,
+    pub removed_repos: Vec<String>,
+    pub star_changes: Vec<StarDelta>,
+    pub old_total_stars: f32,
+    pub new_total_stars: f32,
+}
+
+pub fn diff_reports(old: &Report, new: &Report) -> ReportDiff {
+    let all_languages: BTreeSet<&String> =
+        old.languages.keys().chain(new.languages.keys()).collect();
+    let mut languages: Vec<LanguageDelta> = all_languages
+        .into_iter()
+        .map(|language| LanguageDelta {
+            language: language.clone(),
+            old_percent: old.languages.get(language).copied().unwrap_or(0.0),
+            new_percent: new.languages.get(language).copied().unwrap_or(0.0),
+        })
+        .filter(|delta| delta.old_percent != delta.new_percent)
+        .collect();
+    languages.sort_by(|a, b| {
+        (b.new_percent - b.old_percent)
+            .abs()
+            .total_cmp(&(a.new_percent - a.old_percent).abs())
+    });
+
+    let old_repos: BTreeMap<&str, u32> = old
+        .repos
+        .iter()
+        .map(|repo| (repo.full_name.as_str(), repo.stars))
+        .collect();
+    let new_repos: BTreeMap<&str, u32> = new
+        .repos
+        .iter()
+        .map(|repo| (repo.full_name.as_str(), repo.stars))
+        .collect();
+
+    let added_repos = new_repos
+        .keys()
+        .filter(|name| !old_repos.contains_key(*name))
+        .map(|name| name.to_string())
+        .collect();
+    let removed_repos = old_repos
+        .keys()
+        .filter(|name| !new_repos.contains_key(*name))
+        .map(|name| name.to_string())
+        .collect();
+    let mut star_changes: Vec<StarDelta> = new_repos
+        .iter()
+        .filter_map(|(name, new_stars)| {
+            let old_stars = *old_repos.get(name)?;
+            (old_stars != *new_stars).then(|| StarDelta {
+                full_name: name.to_string(),
+                old_stars,
+                new_stars: *new_stars,
+            })
+        })
+        .collect();
+    star_changes.sort_by_key(|delta| {
+        std::cmp::Reverse((delta.new_stars as i64 - delta.old_stars as i64).abs())
+    });
+
+    ReportDiff {
+        languages,
+        added_repos,
+        removed_repos,
+        star_changes,
+        old_total_stars: old.total_stars,
+        new_total_stars: new.total_stars,
+    }
+}
+
+impl fmt::Display for ReportDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Language changes:")?;
+        if self.languages.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for delta in &self.languages {
+            writeln!(
+                f,
+                "  {}: {:.2}% -> {:.2}% ({:+.2})",
+                delta.language,
+                delta.old_percent,
+                delta.new_percent,
+                delta.new_percent - delta.old_percent
+            )?;
+        }
+
+        if !self.added_repos.is_empty() {
+            writeln!(f, "New repos:")?;
+            for name in &self.added_repos {
+                writeln!(f, "  + {name}")?;
+            }
+        }
+        if !self.removed_repos.is_empty() {
+            writeln!(f, "Removed repos:")?;
+            for name in &self.removed_repos {
+                writeln!(f, "  - {name}")?;
+            }
+        }
+        if !self.star_changes.is_empty() {
+            writeln!(f, "Star changes:")?;
+            for delta in &self.star_changes {
+                writeln!(
+                    f,
+                    "  {}: {} -> {} ({:+})",
+                    delta.full_name,
+                    delta.old_stars,
+                    delta.new_stars,
+                    delta.new_stars as i64 - delta.old_stars as i64
+                )?;
+            }
+        }
+
+        writeln!(
+            f,
+            "Total stars: {} -> {} ({:+})",
+            self.old_total_stars,
+            self.new_total_stars,
+            self.new_total_stars - self.old_total_stars
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RepoInfo;
+
+    fn report(languages: &[(&str, f32)], repos: &[(&str, u32)]) -> Report {
+        let repos = repos
+            .iter()
+            .map(|(full_name, stars)| RepoInfo {
+                full_name: full_name.to_string(),
+                language_loc_map: BTreeMap::new(),
+                ratio_of_commits_from_user: 1.0,
+                stars: *stars,
+                forks: 0,
+                watchers: 0,
+                pushed_at: None,
+                created_at: None,
+                license: None,
+                authored_lines: None,
+                ecosystems: Vec::new(),
+                collaborator: false,
+            })
+            .collect::<Vec<_>>();
+        Report {
+            languages: languages
+                .iter()
+                .map(|(lang, percent)| (lang.to_string(), *percent))
+                .collect(),
+            total_stars: repos.iter().map(|repo| repo.stars as f32).sum(),
+            ..Report::new(repos, false)
+        }
+    }
+
+    #[test]
+    fn languages_are_sorted_by_the_biggest_change() {
+        let old = report(&[("Rust", 50.0), ("Python", 40.0), ("C", 10.0)], &[]);
+        let new = report(&[("Rust", 70.0), ("Python", 30.0)], &[]);
+        let diff = diff_reports(&old, &new);
+        let changes: Vec<(&str, f32, f32)> = diff
+            .languages
+            .iter()
+            .map(|delta| {
+                (
+                    delta.language.as_str(),
+                    delta.old_percent,
+                    delta.new_percent,
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("Rust", 50.0, 70.0),
+                ("C", 10.0, 0.0),
+                ("Python", 40.0, 30.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn repos_are_added_removed_and_restarred() {
+        let old = report(&[], &[("octocat/old", 1), ("octocat/tools", 5)]);
+        let new = report(&[], &[("octocat/tools", 8), ("octocat/new", 0)]);
+        let diff = diff_reports(&old, &new);
+        assert_eq!(diff.added_repos, ["octocat/new"]);
+        assert_eq!(diff.removed_repos, ["octocat/old"]);
+        assert_eq!(diff.star_changes.len(), 1);
+        assert_eq!(diff.star_changes[0].full_name, "octocat/tools");
+        assert_eq!(
+            (
+                diff.star_changes[0].old_stars,
+                diff.star_changes[0].new_stars
+            ),
+            (5, 8)
+        );
+        assert_eq!((diff.old_total_stars, diff.new_total_stars), (6.0, 8.0));
+    }
+}
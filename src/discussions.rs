@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{error::MetricsError, GitHub, RepoInfo};
+
+/// Aliased to `items` so pages of both queries deserialize the same way.
+const CREATED_QUERY: &str = r#"
+query($login: String!, $cursor: String) {
+  user(login: $login) {
+    items: repositoryDiscussions(first: 100, after: $cursor) {
+      pageInfo { hasNextPage endCursor }
+      nodes { repository { nameWithOwner } }
+    }
+  }
+}
+"#;
+
+const ANSWERS_QUERY: &str = r#"
+query($login: String!, $cursor: String) {
+  user(login: $login) {
+    items: repositoryDiscussionComments(first: 100, after: $cursor, onlyAnswers: true) {
+      pageInfo { hasNextPage endCursor }
+      nodes { discussion { repository { nameWithOwner } } }
+    }
+  }
+}
+"#;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct DiscussionStats {
+    /// Discussions the user started in the analyzed repos
+    pub created: u32,
+    /// Discussions where the user's comment was marked as the answer
+    pub answered: u32,
+    /// Repos with at least one of either, most active first
+    pub repos: Vec<RepoDiscussions>,
+}
+impl DiscussionStats {
+    /// Adds another account's discussions to these.
+    pub fn merge(&mut self, other: DiscussionStats) {
+        self.created += other.created;
+        self.answered += other.answered;
+        for repo in other.repos {
+            match self
+                .repos
+                .iter_mut()
+                .find(|existing| existing.full_name == repo.full_name)
+            {
+                Some(existing) => {
+                    existing.created += repo.created;
+                    existing.answered += repo.answered;
+                }
+                None => self.repos.push(repo),
+            }
+        }
+        self.sort_repos();
+    }
+
+    fn sort_repos(&mut self) {
+        self.repos.sort_by(|a, b| {
+            (b.created + b.answered)
+                .cmp(&(a.created + a.answered))
+                .then_with(|| a.full_name.cmp(&b.full_name))
+        });
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RepoDiscussions {
+    pub full_name: String,
+    pub created: u32,
+    pub answered: u32,
+}
+
+/// Counts the discussions the user started and answered, keeping those in `repos`. Requires a
+/// token like the rest of the GraphQL API.
+pub async fn collect_discussions(
+    connection: &GitHub,
+    repos: &[RepoInfo],
+) -> Result<DiscussionStats, MetricsError> {
+    // Keyed by the lowercased name, which GitHub matches case-insensitively
+    let mut per_repo: BTreeMap<String, RepoDiscussions> = repos
+        .iter()
+        .map(|repo| {
+            let discussions = RepoDiscussions {
+                full_name: repo.full_name.clone(),
+                created: 0,
+                answered: 0,
+            };
+            (repo.full_name.to_lowercase(), discussions)
+        })
+        .collect();
+
+    let created: Vec<DiscussionNode> = all_pages(connection, CREATED_QUERY).await?;
+    for node in created {
+        if let Some(repo) = per_repo.get_mut(&node.repository.name_with_owner.to_lowercase()) {
+            repo.created += 1;
+        }
+    }
+    let answers: Vec<AnswerNode> = all_pages(connection, ANSWERS_QUERY).await?;
+    for node in answers {
+        let name = node.discussion.repository.name_with_owner.to_lowercase();
+        if let Some(repo) = per_repo.get_mut(&name) {
+            repo.answered += 1;
+        }
+    }
+
+    let mut stats = DiscussionStats {
+        repos: per_repo
+            .into_values()
+            .filter(|repo| repo.created + repo.answered > 0)
+            .collect(),
+        ..DiscussionStats::default()
+    };
+    stats.created = stats.repos.iter().map(|repo| repo.created).sum();
+    stats.answered = stats.repos.iter().map(|repo| repo.answered).sum();
+    stats.sort_repos();
+    Ok(stats)
+}
+
+async fn all_pages<T: DeserializeOwned>(
+    connection: &GitHub,
+    query: &str,
+) -> Result<Vec<T>, MetricsError> {
+    let mut nodes = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let data: PageData<T> = connection
+            .graphql(query, json!({ "login": connection.user, "cursor": cursor }))
+            .await?;
+        let items = data.user.items;
+        nodes.extend(items.nodes);
+        if !items.page_info.has_next_page {
+            break;
+        }
+        cursor = items.page_info.end_cursor;
+    }
+    Ok(nodes)
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "T: DeserializeOwned")]
+struct PageData<T> {
+    user: PageUser<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "T: DeserializeOwned")]
+struct PageUser<T> {
+    items: Page<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", bound = "T: DeserializeOwned")]
+struct Page<T> {
+    page_info: PageInfo,
+    nodes: Vec<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct DiscussionNode {
+    repository: RepositoryName,
+}
+
+#[derive(Deserialize)]
+struct AnswerNode {
+    discussion: DiscussionNode,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RepositoryName {
+    name_with_owner: String,
+}
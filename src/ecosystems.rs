@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::RepoInfo;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct EcosystemStats {
+    /// Most repos first
+    pub ecosystems: Vec<EcosystemShare>,
+    /// Repos without any recognized manifest
+    pub other_repos: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EcosystemShare {
+    /// e.g. `Cargo`, `npm` or `PyPI`
+    pub ecosystem: String,
+    pub repos: u32,
+    /// Percent of the report's repos targeting the ecosystem. A repo can target several, so
+    /// these can add up to more than 100
+    pub repo_percent: f32,
+}
+
+/// Counts the repos targeting each ecosystem, regardless of how much code they have.
+pub fn ecosystem_stats(repos: &[RepoInfo]) -> EcosystemStats {
+    let mut by_ecosystem: BTreeMap<&str, u32> = BTreeMap::new();
+    let mut other_repos = 0;
+    for repo in repos {
+        if repo.ecosystems.is_empty() {
+            other_repos += 1;
+        }
+        for ecosystem in &repo.ecosystems {
+            *by_ecosystem.entry(ecosystem).or_default() += 1;
+        }
+    }
+
+    let total_repos = repos.len().max(1) as f32;
+    let mut ecosystems: Vec<EcosystemShare> = by_ecosystem
+        .into_iter()
+        .map(|(ecosystem, repos)| EcosystemShare {
+            ecosystem: ecosystem.to_string(),
+            repos,
+            repo_percent: repos as f32 / total_repos * 100.0,
+        })
+        .collect();
+    ecosystems.sort_by_key(|ecosystem| std::cmp::Reverse(ecosystem.repos));
+    EcosystemStats {
+        ecosystems,
+        other_repos,
+    }
+}
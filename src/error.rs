@@ -0,0 +1,90 @@
+use reqwest::{StatusCode, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use thiserror::Error;
+
+/// Errors from talking to GitHub or Gitea.
+#[derive(Error, Debug)]
+pub enum MetricsError {
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Unexpected response from {url}: {source}")]
+    Deserialize {
+        url: String,
+        source: serde_json::Error,
+    },
+    #[error(
+        "Rate limited at {url}. Pass a token with --token for a higher limit, or try again later"
+    )]
+    RateLimited { url: String },
+    #[error("{url} was not found. Check that the user or organization name is spelled correctly")]
+    NotFound { url: String },
+    #[error("{url} rejected the token ({message}). Check that it's valid and hasn't expired")]
+    Unauthorized { url: String, message: String },
+    #[error("{url} responded with {status}: {message}")]
+    Status {
+        url: String,
+        status: StatusCode,
+        message: String,
+    },
+    #[error("{0} requires a token (--token)")]
+    TokenRequired(&'static str),
+    #[error("GraphQL error: {0}")]
+    GraphQL(String),
+    #[error("No cached response for {url}. Run once without --offline to fill the cache")]
+    NotCached { url: String },
+    #[error("{0} needs the network, so it can't be used with --offline")]
+    Offline(&'static str),
+    #[error("GitHub is still computing {url}, try again in a minute")]
+    Pending { url: String },
+    #[error("{url} isn't a valid URL: {source}")]
+    InvalidUrl {
+        url: String,
+        source: url::ParseError,
+    },
+}
+
+impl MetricsError {
+    /// Turns an unsuccessful response into an error, using the message from the body if the
+    /// server sent one.
+    pub(crate) fn from_response(url: &Url, status: StatusCode, body: &str) -> Self {
+        let url = url.to_string();
+        let message = serde_json::from_str::<ErrorBody>(body)
+            .map(|body| body.message)
+            .unwrap_or_else(|_| body.trim().to_string());
+        match status {
+            StatusCode::NOT_FOUND => Self::NotFound { url },
+            StatusCode::UNAUTHORIZED => Self::Unauthorized { url, message },
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited { url },
+            StatusCode::FORBIDDEN if message.to_ascii_lowercase().contains("rate limit") => {
+                Self::RateLimited { url }
+            }
+            _ => Self::Status {
+                url,
+                status,
+                message,
+            },
+        }
+    }
+}
+
+/// Deserializes a response body, keeping track of where it came from for the error message.
+pub(crate) fn parse_json<T: DeserializeOwned>(url: &Url, body: &str) -> Result<T, MetricsError> {
+    serde_json::from_str(body).map_err(|source| MetricsError::Deserialize {
+        url: url.to_string(),
+        source,
+    })
+}
+
+/// Parses a URL built from the base URL or taken from a response.
+pub(crate) fn parse_url(url: &str) -> Result<Url, MetricsError> {
+    Url::parse(url).map_err(|source| MetricsError::InvalidUrl {
+        url: url.to_string(),
+        source,
+    })
+}
+
+/// Both GitHub and Gitea put a `message` field in error bodies.
+#[derive(Deserialize)]
+struct ErrorBody {
+    message: String,
+}
@@ -0,0 +1,371 @@
+use std::collections::HashSet;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use tracing::debug;
+
+use crate::{models::RepoData, RepoInfo};
+
+/// The repo metadata filters look at, implemented by each provider's repo listing type.
+pub trait RepoMetadata {
+    fn full_name(&self) -> &str;
+    fn is_fork(&self) -> bool;
+    fn is_archived(&self) -> bool {
+        false
+    }
+    fn is_disabled(&self) -> bool {
+        false
+    }
+    fn is_private(&self) -> bool {
+        false
+    }
+    fn topics(&self) -> Vec<&str> {
+        Vec::new()
+    }
+}
+
+impl RepoMetadata for RepoData {
+    fn full_name(&self) -> &str {
+        &self.full_name
+    }
+    fn is_fork(&self) -> bool {
+        self.fork
+    }
+    fn is_archived(&self) -> bool {
+        self.archived
+    }
+    fn is_disabled(&self) -> bool {
+        self.disabled
+    }
+    fn is_private(&self) -> bool {
+        self.private
+    }
+    fn topics(&self) -> Vec<&str> {
+        self.topics.iter().map(String::as_str).collect()
+    }
+}
+
+/// Drops repos listed more than once, like an owned repo that also shows up through one of the
+/// user's organizations, keeping the first listing. Returns how many were dropped.
+pub fn dedupe_repos<R: RepoMetadata>(repos: &mut Vec<R>) -> usize {
+    let mut seen = HashSet::new();
+    let listed = repos.len();
+    repos.retain(|repo| seen.insert(repo.full_name().to_ascii_lowercase()));
+    listed - repos.len()
+}
+
+/// Which organizations' repos to list, checked before their repos are fetched. Names are
+/// matched case-insensitively.
+#[derive(Clone, Debug, Default)]
+pub struct OrgFilter {
+    /// If not empty, repos of other organizations are skipped
+    pub only: Vec<String>,
+    /// Repos of these organizations are skipped
+    pub exclude: Vec<String>,
+}
+impl OrgFilter {
+    pub fn allows(&self, org: &str) -> bool {
+        let listed = |orgs: &[String]| orgs.iter().any(|name| name.eq_ignore_ascii_case(org));
+        (self.only.is_empty() || listed(&self.only)) && !listed(&self.exclude)
+    }
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    Private,
+    #[default]
+    All,
+}
+
+/// Decides which discovered repos are worth analyzing, before any per-repo API calls are made.
+/// The `min_*` thresholds need the analyzed repos, so they're checked afterwards by
+/// [`RepoFilter::apply_thresholds`].
+#[derive(Default, Clone, Debug)]
+pub struct RepoFilter {
+    pub include_forks: bool,
+    pub include_archived: bool,
+    pub visibility: Visibility,
+    /// Repos whose `owner/name` matches any of these are skipped
+    pub exclude_repos: Option<GlobSet>,
+    /// If set, only repos whose `owner/name` matches one of these are analyzed
+    pub only_repos: Option<GlobSet>,
+    /// If not empty, only repos tagged with one of these topics are analyzed
+    pub include_topics: Vec<String>,
+    /// Repos tagged with any of these topics are skipped
+    pub exclude_topics: Vec<String>,
+    /// Repos where the user's share of commits is below this are dropped
+    pub min_contribution: f32,
+    pub min_stars: u32,
+    /// Repos with fewer lines of code than this, after excluded languages, are dropped
+    pub min_loc: u32,
+}
+impl RepoFilter {
+    /// Returns why `repo` should be skipped, or `None` if it should be analyzed.
+    pub fn skip_reason(&self, repo: &impl RepoMetadata) -> Option<String> {
+        if let Some(only_repos) = &self.only_repos {
+            if !only_repos.is_match(repo.full_name()) {
+                return Some("not matched by --only-repo".to_string());
+            }
+        }
+        if let Some(exclude_repos) = &self.exclude_repos {
+            if exclude_repos.is_match(repo.full_name()) {
+                return Some("matched by --exclude-repo".to_string());
+            }
+        }
+        let topics = repo.topics();
+        let has_topic = |wanted: &[String]| {
+            topics
+                .iter()
+                .find(|topic| {
+                    wanted
+                        .iter()
+                        .any(|wanted| wanted.eq_ignore_ascii_case(topic))
+                })
+                .copied()
+        };
+        if !self.include_topics.is_empty() && has_topic(&self.include_topics).is_none() {
+            return Some("not tagged with an --include-topic".to_string());
+        }
+        if let Some(topic) = has_topic(&self.exclude_topics) {
+            return Some(format!("tagged with {topic}"));
+        }
+        if repo.is_fork() && !self.include_forks {
+            return Some("fork".to_string());
+        }
+        if repo.is_archived() && !self.include_archived {
+            return Some("archived".to_string());
+        }
+        match (self.visibility, repo.is_private()) {
+            (Visibility::Public, true) => return Some("private".to_string()),
+            (Visibility::Private, false) => return Some("public".to_string()),
+            _ => {}
+        }
+        if repo.is_disabled() {
+            return Some("disabled".to_string());
+        }
+        None
+    }
+
+    pub fn apply<R: RepoMetadata>(&self, repos: Vec<R>) -> Vec<R> {
+        let total = repos.len();
+        let repos: Vec<R> = repos
+            .into_iter()
+            .filter(|repo| self.skip_reason(repo).is_none())
+            .collect();
+        if repos.len() < total {
+            debug!("Skipping {} filtered out repos.", total - repos.len());
+        }
+        repos
+    }
+
+    /// Returns why an analyzed repo should be left out of the report, or `None` if it should
+    /// be kept.
+    pub fn threshold_reason(&self, repo: &RepoInfo) -> Option<String> {
+        if repo.ratio_of_commits_from_user < self.min_contribution {
+            return Some("below --min-contribution".to_string());
+        }
+        if repo.stars < self.min_stars {
+            return Some("below --min-stars".to_string());
+        }
+        if repo.language_loc_map.values().sum::<u32>() < self.min_loc {
+            return Some("below --min-loc".to_string());
+        }
+        None
+    }
+
+    pub fn apply_thresholds(&self, repos: Vec<RepoInfo>) -> Vec<RepoInfo> {
+        let total = repos.len();
+        let repos: Vec<RepoInfo> = repos
+            .into_iter()
+            .filter(|repo| match self.threshold_reason(repo) {
+                Some(reason) => {
+                    debug!("Leaving out {}: {reason}", repo.full_name);
+                    false
+                }
+                None => true,
+            })
+            .collect();
+        if repos.len() < total {
+            debug!(
+                "Left out {} repos below the thresholds.",
+                total - repos.len()
+            );
+        }
+        repos
+    }
+}
+
+/// Builds a case-insensitive glob set, or `None` if there are no patterns.
+pub fn glob_set(patterns: &[String]) -> Result<Option<GlobSet>, globset::Error> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(GlobBuilder::new(pattern).case_insensitive(true).build()?);
+    }
+    Ok(Some(builder.build()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    struct Repo {
+        full_name: &'static str,
+        fork: bool,
+        private: bool,
+        topics: Vec<&'static str>,
+    }
+    impl RepoMetadata for Repo {
+        fn full_name(&self) -> &str {
+            self.full_name
+        }
+        fn is_fork(&self) -> bool {
+            self.fork
+        }
+        fn is_private(&self) -> bool {
+            self.private
+        }
+        fn topics(&self) -> Vec<&str> {
+            self.topics.clone()
+        }
+    }
+
+    fn repo(full_name: &'static str) -> Repo {
+        Repo {
+            full_name,
+            fork: false,
+            private: false,
+            topics: Vec::new(),
+        }
+    }
+
+    fn info(full_name: &str, stars: u32, ratio: f32, loc: u32) -> RepoInfo {
+        RepoInfo {
+            full_name: full_name.to_string(),
+            language_loc_map: BTreeMap::from([("Rust".to_string(), loc)]),
+            ratio_of_commits_from_user: ratio,
+            stars,
+            forks: 0,
+            watchers: 0,
+            pushed_at: None,
+            created_at: None,
+            license: None,
+            authored_lines: None,
+            ecosystems: Vec::new(),
+            collaborator: false,
+        }
+    }
+
+    #[test]
+    fn org_filter_matches_names_case_insensitively() {
+        let filter = OrgFilter {
+            only: vec!["Rust-Lang".to_string(), "tokio-rs".to_string()],
+            exclude: vec!["TOKIO-RS".to_string()],
+        };
+        assert!(filter.allows("rust-lang"));
+        assert!(!filter.allows("tokio-rs"));
+        assert!(!filter.allows("serde-rs"));
+        assert!(OrgFilter::default().allows("serde-rs"));
+    }
+
+    #[test]
+    fn forks_and_private_repos_are_skipped_unless_asked_for() {
+        let fork = Repo {
+            fork: true,
+            ..repo("octocat/fork")
+        };
+        let private = Repo {
+            private: true,
+            ..repo("octocat/secret")
+        };
+        let filter = RepoFilter {
+            visibility: Visibility::Public,
+            ..Default::default()
+        };
+        assert_eq!(filter.skip_reason(&fork).as_deref(), Some("fork"));
+        assert_eq!(filter.skip_reason(&private).as_deref(), Some("private"));
+        assert_eq!(filter.skip_reason(&repo("octocat/public")), None);
+
+        let filter = RepoFilter {
+            include_forks: true,
+            visibility: Visibility::Private,
+            ..Default::default()
+        };
+        assert_eq!(filter.skip_reason(&fork).as_deref(), Some("public"));
+        assert_eq!(filter.skip_reason(&private), None);
+    }
+
+    #[test]
+    fn repo_globs_and_topics_pick_repos() {
+        let filter = RepoFilter {
+            only_repos: glob_set(&["octocat/*".to_string()]).unwrap(),
+            exclude_repos: glob_set(&["*/Old-*".to_string()]).unwrap(),
+            exclude_topics: vec!["archive".to_string()],
+            ..Default::default()
+        };
+        let tagged = Repo {
+            topics: vec!["Archive"],
+            ..repo("octocat/tagged")
+        };
+        assert_eq!(filter.skip_reason(&repo("octocat/tools")), None);
+        assert!(filter.skip_reason(&repo("other/tools")).is_some());
+        assert!(filter.skip_reason(&repo("octocat/old-tools")).is_some());
+        assert_eq!(
+            filter.skip_reason(&tagged).as_deref(),
+            Some("tagged with Archive")
+        );
+
+        let filter = RepoFilter {
+            include_topics: vec!["cli".to_string()],
+            ..Default::default()
+        };
+        assert!(filter.skip_reason(&repo("octocat/untagged")).is_some());
+    }
+
+    #[test]
+    fn dedupe_keeps_the_first_listing() {
+        let mut repos = vec![
+            repo("octocat/tools"),
+            repo("OctoCat/Tools"),
+            repo("octocat/hello-world"),
+        ];
+        assert_eq!(dedupe_repos(&mut repos), 1);
+        assert_eq!(repos[0].full_name, "octocat/tools");
+        assert_eq!(repos.len(), 2);
+    }
+
+    #[test]
+    fn thresholds_leave_out_small_repos() {
+        let filter = RepoFilter {
+            min_contribution: 0.1,
+            min_stars: 5,
+            min_loc: 100,
+            ..Default::default()
+        };
+        let repos = vec![
+            info("octocat/kept", 5, 0.1, 100),
+            info("octocat/drive-by", 50, 0.05, 1000),
+            info("octocat/unstarred", 4, 1.0, 1000),
+            info("octocat/tiny", 50, 1.0, 99),
+        ];
+        assert_eq!(
+            filter.threshold_reason(&repos[1]).as_deref(),
+            Some("below --min-contribution")
+        );
+        assert_eq!(
+            filter.threshold_reason(&repos[2]).as_deref(),
+            Some("below --min-stars")
+        );
+        assert_eq!(
+            filter.threshold_reason(&repos[3]).as_deref(),
+            Some("below --min-loc")
+        );
+        let kept = filter.apply_thresholds(repos);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].full_name, "octocat/kept");
+    }
+}
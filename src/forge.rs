@@ -0,0 +1,88 @@
+use std::{collections::BTreeMap, error::Error};
+
+use futures::future::LocalBoxFuture;
+
+use crate::{filter::RepoMetadata, models::ContributorData};
+
+/// Something a forge may or may not be able to supply. Metrics needing a capability the forge
+/// lacks are left out of the report with a warning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// Repos of the organizations the user belongs to
+    Organizations,
+    /// Lines of code per language, rather than bytes
+    LinesOfCode,
+    /// Commit listings filtered by author, for commit counts and the heatmap
+    CommitsByAuthor,
+    /// Issue and pull request search
+    Search,
+    /// The contribution calendar and review counts from a GraphQL API
+    ContributionGraph,
+    /// The license of each repo
+    Licenses,
+    /// Local clones of the repos, for package manifests and commit messages
+    LocalClones,
+    /// Releases and the download counts of their assets
+    Releases,
+    /// The files in the repos, for package manifests without cloning
+    FileContents,
+    /// The user's sponsors and their tiers
+    Sponsors,
+    /// Discussions the user started and answered
+    Discussions,
+    /// Views and clones of the repos the token can push to
+    Traffic,
+}
+impl Capability {
+    pub fn description(self) -> &'static str {
+        match self {
+            Capability::Organizations => "organization repos",
+            Capability::LinesOfCode => "lines of code",
+            Capability::CommitsByAuthor => "commits by author",
+            Capability::Search => "issue and pull request search",
+            Capability::ContributionGraph => "the contribution graph",
+            Capability::Licenses => "repo licenses",
+            Capability::LocalClones => "local clones",
+            Capability::Releases => "releases",
+            Capability::FileContents => "repo file contents",
+            Capability::Sponsors => "sponsors",
+            Capability::Discussions => "discussions",
+            Capability::Traffic => "repo traffic",
+        }
+    }
+}
+
+/// What the calls of a [`ForgeProvider`] return.
+pub type ForgeFuture<'a, T> = LocalBoxFuture<'a, Result<T, Box<dyn Error>>>;
+
+/// The calls every forge the scraper supports can answer, for the user it was created for.
+pub trait ForgeProvider {
+    type Repo: RepoMetadata;
+
+    /// For messages, e.g. `GitHub`
+    fn name(&self) -> &'static str;
+
+    /// What this forge can supply. Whether the settings allow it, like having a token, is up to
+    /// the metrics to check.
+    fn capabilities(&self) -> Vec<Capability>;
+
+    fn supports(&self, capability: Capability) -> bool {
+        self.capabilities().contains(&capability)
+    }
+
+    /// The user's own repos and those of their organizations.
+    fn list_repos(&self) -> ForgeFuture<'_, Vec<Self::Repo>>;
+
+    /// Names of the organizations the user belongs to.
+    fn list_orgs(&self) -> ForgeFuture<'_, Vec<String>>;
+
+    /// Commits to the repo per author.
+    fn contributors<'a>(&'a self, repo: &'a Self::Repo) -> ForgeFuture<'a, Vec<ContributorData>>;
+
+    /// Lines of code per language, or bytes without [`Capability::LinesOfCode`], minus excluded
+    /// languages. `None` if the forge has no language data for the repo.
+    fn languages<'a>(
+        &'a self,
+        repo: &'a Self::Repo,
+    ) -> ForgeFuture<'a, Option<BTreeMap<String, u32>>>;
+}
@@ -0,0 +1,94 @@
+use std::env;
+
+use crate::Report;
+
+/// Order the languages are listed in.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+    /// Largest share first
+    #[default]
+    Percent,
+    /// Alphabetically
+    Name,
+    /// Most lines of code first. Only differs from `percent` for reports from older versions
+    Loc,
+}
+
+/// How numbers in the text, Markdown and HTML output are written.
+#[derive(Clone, Copy, Debug)]
+pub struct NumberFormat {
+    /// Decimal places of percentages
+    pub precision: usize,
+    /// Put between groups of three digits in line counts
+    pub thousands_separator: Option<char>,
+    pub sort_by: SortBy,
+}
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            precision: 2,
+            thousands_separator: locale_thousands_separator(),
+            sort_by: SortBy::default(),
+        }
+    }
+}
+impl NumberFormat {
+    pub fn percent(&self, percent: f32) -> String {
+        format!("{percent:.*}%", self.precision)
+    }
+
+    pub fn count(&self, count: u64) -> String {
+        let digits = count.to_string();
+        let Some(separator) = self.thousands_separator else {
+            return digits;
+        };
+        let mut out = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push(separator);
+            }
+            out.push(digit);
+        }
+        out
+    }
+
+    /// The report's languages with their percentages, in [`NumberFormat::sort_by`] order.
+    pub fn sorted_languages<'a>(&self, report: &'a Report) -> Vec<(&'a str, f32)> {
+        let mut langs: Vec<_> = report
+            .languages
+            .iter()
+            .map(|(lang, percent)| (lang.as_str(), *percent))
+            .collect();
+        match self.sort_by {
+            SortBy::Percent => langs.sort_by(|(_, a), (_, b)| b.total_cmp(a)),
+            SortBy::Name => langs.sort_by_key(|(lang, _)| lang.to_lowercase()),
+            SortBy::Loc => langs.sort_by_key(|(lang, _)| {
+                std::cmp::Reverse(report.language_loc.get(*lang).copied().unwrap_or(0))
+            }),
+        }
+        langs
+    }
+}
+
+/// The digit group separator of the locale in `LC_ALL`, `LC_NUMERIC` or `LANG`, e.g. `.` for
+/// `de_DE.UTF-8`. Defaults to `,`, and is `None` for the `C` and `POSIX` locales.
+pub fn locale_thousands_separator() -> Option<char> {
+    let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .into_iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|locale| !locale.is_empty())
+        .unwrap_or_default();
+    let language = locale
+        .split(['_', '.', '@', '-'])
+        .next()
+        .unwrap_or_default();
+    match language {
+        "C" | "POSIX" => None,
+        "de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el" => Some('.'),
+        // A narrow no-break space, as recommended for these locales
+        "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" => {
+            Some('\u{202f}')
+        }
+        _ => Some(','),
+    }
+}
@@ -0,0 +1,346 @@
+use std::{collections::BTreeMap, error::Error};
+
+use futures::{StreamExt, TryStreamExt};
+use reqwest::{Client, Response, Url};
+use serde::{de::DeserializeOwned, Deserialize};
+use tracing::{debug, info, info_span, Instrument};
+
+use crate::{
+    error::{parse_json, parse_url, MetricsError},
+    filter::{dedupe_repos, OrgFilter, RepoFilter, RepoMetadata},
+    forge::{Capability, ForgeFuture, ForgeProvider},
+    github::next_page,
+    http::USER_AGENT,
+    models::ContributorData,
+    progress::{processed_message, Progress},
+    report::RepoInfo,
+    retry::RetryPolicy,
+};
+
+/// How many of the latest commits a repo's contributors are counted from, 20 pages of 50.
+pub const CONTRIBUTOR_COMMITS: usize = 1000;
+
+/// Client for Gitea-compatible forges such as Forgejo and Codeberg.
+pub struct Gitea {
+    client: Client,
+    base_url: String,
+    pub user: String,
+    auth_code: Option<String>,
+    pub excluded_langs: Vec<String>,
+    pub retry: RetryPolicy,
+    /// Which organizations' repos [`ForgeProvider::list_repos`] lists
+    pub org_filter: OrgFilter,
+}
+impl Gitea {
+    /// `host` may be a bare domain (`codeberg.org`) or a full URL for instances not served
+    /// over https or not at the domain root.
+    pub fn new(host: &str, user: impl Into<String>, token: Option<String>) -> Self {
+        let host = host.trim_end_matches('/');
+        let base_url = if host.starts_with("http://") || host.starts_with("https://") {
+            format!("{host}/api/v1")
+        } else {
+            format!("https://{host}/api/v1")
+        };
+        Self {
+            client: Client::new(),
+            base_url,
+            user: user.into(),
+            auth_code: token,
+            excluded_langs: Vec::new(),
+            retry: RetryPolicy::default(),
+            org_filter: OrgFilter::default(),
+        }
+    }
+
+    /// Languages (matched case-insensitively) to leave out of every repo.
+    pub fn with_excluded_langs(mut self, langs: impl IntoIterator<Item = String>) -> Self {
+        self.excluded_langs = langs.into_iter().map(|s| s.to_ascii_lowercase()).collect();
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_org_filter(mut self, org_filter: OrgFilter) -> Self {
+        self.org_filter = org_filter;
+        self
+    }
+
+    /// Sends requests with `client` instead of a default one, to share its connection pool and
+    /// timeouts, see [`ClientOptions`](crate::http::ClientOptions).
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Sends a GET request, turning unsuccessful responses into errors.
+    async fn get(&self, url: Url) -> Result<Response, MetricsError> {
+        let mut builder = self
+            .client
+            .get(url.clone())
+            .header("User-Agent", USER_AGENT);
+        if let Some(auth) = &self.auth_code {
+            builder = builder.header("Authorization", format!("token {auth}"));
+        }
+        let response = self.retry.send(builder).await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(MetricsError::from_response(&url, status, &body));
+        }
+        Ok(response)
+    }
+
+    pub async fn get_one<T: DeserializeOwned>(&self, path: &str) -> Result<T, MetricsError> {
+        let url = parse_url(&format!("{}{path}", self.base_url))?;
+        let json = self.get(url.clone()).await?.text().await?;
+        parse_json(&url, &json)
+    }
+
+    /// Fetches every page of a list endpoint by following `Link: rel="next"` headers.
+    pub async fn get_all<T: DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, MetricsError> {
+        self.get_up_to(path, usize::MAX).await
+    }
+
+    /// [`Gitea::get_all`], stopping after the page that reaches `max_items`.
+    pub async fn get_up_to<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        max_items: usize,
+    ) -> Result<Vec<T>, MetricsError> {
+        let mut url = parse_url(&format!("{}{path}", self.base_url))?;
+        url.query_pairs_mut().append_pair("limit", "50");
+
+        let mut items = Vec::new();
+        let mut next = Some(url);
+        while let Some(url) = next.filter(|_| items.len() < max_items) {
+            let response = self.get(url.clone()).await?;
+            next = next_page(&response);
+            let page: Vec<T> = parse_json(&url, &response.text().await?)?;
+            items.extend(page);
+        }
+        Ok(items)
+    }
+
+    /// The user's share of commits is counted from the contributors, and languages are byte
+    /// counts rather than lines of code.
+    pub async fn handle_repo(
+        &self,
+        repo: GiteaRepoData,
+    ) -> Result<Option<RepoInfo>, Box<dyn Error>> {
+        if repo.empty {
+            return Ok(None);
+        }
+
+        let contributors = self.contributors(&repo).await?;
+        let total_commits: u32 = contributors
+            .iter()
+            .map(|contributor| contributor.contributions)
+            .sum();
+        let Some(user_commits) = contributors
+            .iter()
+            .find(|contributor| contributor.login.eq_ignore_ascii_case(&self.user))
+            .map(|contributor| contributor.contributions)
+        else {
+            return Ok(None);
+        };
+        let ratio_of_contributions = user_commits as f32 / total_commits as f32;
+
+        let Some(language_loc_map) = self.languages(&repo).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(RepoInfo {
+            full_name: repo.full_name,
+            language_loc_map,
+            ratio_of_commits_from_user: ratio_of_contributions,
+            stars: repo.stars_count,
+            forks: repo.forks_count,
+            watchers: repo.watchers_count,
+            pushed_at: repo.updated_at,
+            created_at: repo.created_at,
+            license: None,
+            authored_lines: None,
+            ecosystems: Vec::new(),
+            collaborator: false,
+        }))
+    }
+}
+
+impl ForgeProvider for Gitea {
+    type Repo = GiteaRepoData;
+
+    fn name(&self) -> &'static str {
+        "Gitea"
+    }
+
+    fn capabilities(&self) -> Vec<Capability> {
+        vec![Capability::Organizations]
+    }
+
+    fn list_repos(&self) -> ForgeFuture<'_, Vec<GiteaRepoData>> {
+        Box::pin(async move {
+            let mut repos: Vec<GiteaRepoData> =
+                self.get_all(&format!("/users/{}/repos", self.user)).await?;
+            info!("Found all {} user repos!", repos.len());
+
+            for org in self.list_orgs().await? {
+                if !self.org_filter.allows(&org) {
+                    debug!("Skipping the repos of organization {org}");
+                    continue;
+                }
+                let repos_data: Vec<GiteaRepoData> =
+                    self.get_all(&format!("/orgs/{org}/repos")).await?;
+                info!("Found {} organization repos!", repos_data.len());
+                repos.extend(repos_data);
+            }
+
+            let duplicates = dedupe_repos(&mut repos);
+            if duplicates > 0 {
+                info!("Dropped {duplicates} repos listed more than once");
+            }
+            Ok(repos)
+        })
+    }
+
+    fn list_orgs(&self) -> ForgeFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let orgs: Vec<GiteaOrgData> =
+                self.get_all(&format!("/users/{}/orgs", self.user)).await?;
+            Ok(orgs.into_iter().map(|org| org.username).collect())
+        })
+    }
+
+    /// Gitea has no contributors endpoint, so these are counted from the latest
+    /// [`CONTRIBUTOR_COMMITS`] commits of the default branch, rather than paging through the
+    /// whole history of large repos. Commits by accounts unknown to Gitea count under an empty
+    /// login.
+    fn contributors<'a>(
+        &'a self,
+        repo: &'a GiteaRepoData,
+    ) -> ForgeFuture<'a, Vec<ContributorData>> {
+        Box::pin(async move {
+            let commits: Vec<GiteaCommitData> = self
+                .get_up_to(
+                    &format!(
+                        "/repos/{}/commits?stat=false&verification=false&files=false",
+                        repo.full_name
+                    ),
+                    CONTRIBUTOR_COMMITS,
+                )
+                .await?;
+            let mut by_login: BTreeMap<String, u32> = BTreeMap::new();
+            for commit in commits {
+                let login = commit.author.map(|author| author.login).unwrap_or_default();
+                *by_login.entry(login).or_default() += 1;
+            }
+            Ok(by_login
+                .into_iter()
+                .map(|(login, contributions)| ContributorData {
+                    login,
+                    contributions,
+                })
+                .collect())
+        })
+    }
+
+    fn languages<'a>(
+        &'a self,
+        repo: &'a GiteaRepoData,
+    ) -> ForgeFuture<'a, Option<BTreeMap<String, u32>>> {
+        Box::pin(async move {
+            let langs: BTreeMap<String, u32> = self
+                .get_one(&format!("/repos/{}/languages", repo.full_name))
+                .await?;
+            Ok(Some(
+                langs
+                    .into_iter()
+                    .filter(|(lang, _)| !self.excluded_langs.contains(&lang.to_ascii_lowercase()))
+                    .collect(),
+            ))
+        })
+    }
+}
+
+pub async fn collect_repo_infos(
+    connection: &Gitea,
+    filter: &RepoFilter,
+    concurrency: usize,
+) -> Result<Vec<RepoInfo>, Box<dyn Error>> {
+    let repos = filter.apply(connection.list_repos().await?);
+    let progress = Progress::new(repos.len());
+    futures::stream::iter(repos)
+        .map(|repo| {
+            let progress = &progress;
+            let span = info_span!("repo", name = %repo.full_name);
+            async move {
+                progress.start(&repo.full_name);
+                let info = connection.handle_repo(repo).await?;
+                progress.finish_repo(info.as_ref().map(processed_message));
+                Ok::<_, Box<dyn Error>>(info)
+            }
+            .instrument(span)
+        })
+        .buffered(concurrency.max(1))
+        .try_filter_map(|info| async move { Ok(info) })
+        .try_collect()
+        .await
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GiteaRepoData {
+    pub full_name: String,
+    pub stars_count: u32,
+    #[serde(default)]
+    pub forks_count: u32,
+    #[serde(default)]
+    pub watchers_count: u32,
+    /// Gitea doesn't track pushes separately from other updates
+    pub updated_at: Option<String>,
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub empty: bool,
+    #[serde(default)]
+    pub fork: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+impl RepoMetadata for GiteaRepoData {
+    fn full_name(&self) -> &str {
+        &self.full_name
+    }
+    fn is_fork(&self) -> bool {
+        self.fork
+    }
+    fn is_archived(&self) -> bool {
+        self.archived
+    }
+    fn is_private(&self) -> bool {
+        self.private
+    }
+    fn topics(&self) -> Vec<&str> {
+        self.topics.iter().map(String::as_str).collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GiteaOrgData {
+    pub username: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GiteaCommitData {
+    pub author: Option<GiteaUserData>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GiteaUserData {
+    pub login: String,
+}
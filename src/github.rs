@@ -0,0 +1,969 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use futures::{future, stream, Stream, StreamExt, TryStreamExt};
+use reqwest::{header::HeaderMap, Client, IntoUrl, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::{
+    cache::{Cache, CacheEntry},
+    error::{parse_json, parse_url, MetricsError},
+    filter::{dedupe_repos, OrgFilter},
+    forge::{Capability, ForgeFuture, ForgeProvider},
+    graphql,
+    http::USER_AGENT,
+    loc::{self, LocBackend},
+    models::{
+        AuthenticatedUserData, CommitData, ContributorData, ContributorStatsData, LOCData, OrgData,
+        RepoData, RepoDetailsData, SearchCountData, TrafficData, UserData,
+    },
+    progress::{processed_message, Progress},
+    report::{RepoInfo, WeightMode},
+    retry::RetryPolicy,
+};
+
+/// Where the REST and GraphQL APIs are served from.
+pub const DEFAULT_BASE_URL: &str = "https://api.github.com";
+
+/// Path of the REST API on GitHub Enterprise Server hosts, whose GraphQL API is at `/api/graphql`.
+const ENTERPRISE_API_PATH: &str = "/api/v3";
+
+/// The host of a GitHub Enterprise Server API URL like `https://github.mycorp.com/api/v3`.
+pub fn enterprise_host(api_url: &str) -> Option<String> {
+    let root = api_url
+        .trim_end_matches('/')
+        .strip_suffix(ENTERPRISE_API_PATH)?;
+    Url::parse(root).ok()?.host_str().map(str::to_string)
+}
+
+pub struct GitHub {
+    pub(crate) client: Client,
+    /// API root without a trailing slash, [`DEFAULT_BASE_URL`] unless overridden
+    pub base_url: String,
+    /// Root of the codetabs LOC API, [`loc::DEFAULT_CODETABS_URL`] unless overridden
+    pub codetabs_url: String,
+    pub user: String,
+    pub(crate) auth_code: Option<String>,
+    pub weighted: bool,
+    pub weight_mode: WeightMode,
+    pub excluded_langs: Vec<String>,
+    pub loc_backend: LocBackend,
+    pub loc_fallback: Option<LocBackend>,
+    /// Leave [`loc::DEFAULT_EXCLUDES`] out of local counts
+    pub default_excludes: bool,
+    pub retry: RetryPolicy,
+    /// Which organizations' repos [`GitHub::repos`] lists
+    pub org_filter: OrgFilter,
+    rate_limit: Mutex<Option<RateLimit>>,
+    cache: Option<Cache>,
+    /// Answer every request from the cache, failing for anything that isn't cached
+    pub offline: bool,
+    /// Repos dropped for a reason other than the user not having committed to them, with the
+    /// reason
+    skipped: Mutex<Vec<(String, String)>>,
+}
+impl GitHub {
+    pub fn new(user: impl Into<String>, token: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            codetabs_url: loc::DEFAULT_CODETABS_URL.to_string(),
+            user: user.into(),
+            auth_code: token,
+            weighted: false,
+            weight_mode: WeightMode::default(),
+            excluded_langs: Vec::new(),
+            loc_backend: LocBackend::default(),
+            loc_fallback: None,
+            default_excludes: true,
+            retry: RetryPolicy::default(),
+            org_filter: OrgFilter::default(),
+            rate_limit: Mutex::new(None),
+            cache: Cache::new(),
+            offline: false,
+            skipped: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Scale each repo's languages and stars by the user's share of its commits.
+    pub fn with_weighted(mut self, weighted: bool) -> Self {
+        self.weighted = weighted;
+        self
+    }
+
+    pub fn with_weight_mode(mut self, weight_mode: WeightMode) -> Self {
+        self.weight_mode = weight_mode;
+        self
+    }
+
+    /// Languages (matched case-insensitively) to leave out of every repo.
+    pub fn with_excluded_langs(mut self, langs: impl IntoIterator<Item = String>) -> Self {
+        self.excluded_langs = langs.into_iter().map(|s| s.to_ascii_lowercase()).collect();
+        self
+    }
+
+    pub fn with_loc_backend(mut self, loc_backend: LocBackend) -> Self {
+        self.loc_backend = loc_backend;
+        self
+    }
+
+    /// Backend to retry with when the primary one has no data for a repo.
+    pub fn with_loc_fallback(mut self, loc_fallback: Option<LocBackend>) -> Self {
+        self.loc_fallback = loc_fallback;
+        self
+    }
+
+    pub fn with_default_excludes(mut self, default_excludes: bool) -> Self {
+        self.default_excludes = default_excludes;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_org_filter(mut self, org_filter: OrgFilter) -> Self {
+        self.org_filter = org_filter;
+        self
+    }
+
+    /// Sends requests with `client` instead of a default one, to share its connection pool and
+    /// timeouts, see [`ClientOptions`](crate::http::ClientOptions).
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Replaces the default on-disk response cache. `None` disables caching.
+    pub fn with_cache(mut self, cache: Option<Cache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Sends API requests somewhere other than api.github.com, e.g.
+    /// `https://github.mycorp.com/api/v3` for GitHub Enterprise Server or a mock server in tests.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn with_codetabs_url(mut self, codetabs_url: &str) -> Self {
+        self.codetabs_url = codetabs_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// `path` (starting with `/`) under the API root.
+    pub(crate) fn api_url(&self, path: &str) -> String {
+        format!("{}{path}", self.base_url)
+    }
+
+    /// Whether this talks to GitHub Enterprise Server rather than github.com.
+    pub fn is_enterprise(&self) -> bool {
+        enterprise_host(&self.base_url).is_some()
+    }
+
+    pub(crate) fn graphql_url(&self) -> String {
+        match self.base_url.strip_suffix(ENTERPRISE_API_PATH) {
+            Some(host) => format!("{host}/api/graphql"),
+            None => self.api_url("/graphql"),
+        }
+    }
+
+    /// Root of the web UI that repos are cloned from.
+    pub(crate) fn web_url(&self) -> &str {
+        if self.base_url == DEFAULT_BASE_URL {
+            return "https://github.com";
+        }
+        self.base_url
+            .strip_suffix(ENTERPRISE_API_PATH)
+            .unwrap_or(&self.base_url)
+    }
+
+    /// Value of the `Authorization` header. Older Enterprise Server releases only accept the
+    /// `token` scheme, while github.com takes either.
+    pub(crate) fn authorization(&self) -> Option<String> {
+        let auth = self.auth_code.as_ref()?;
+        Some(if self.is_enterprise() {
+            format!("token {auth}")
+        } else {
+            format!("Bearer {auth}")
+        })
+    }
+
+    pub async fn user_data(&self) -> Result<UserData, MetricsError> {
+        let url = parse_url(&self.api_url(&format!("/users/{}", self.user)))?;
+        let page = self.get_page(url.clone()).await?;
+        parse_json(&url, &page.body)
+    }
+
+    /// Whether the token, if any, belongs to the user being analyzed.
+    pub async fn is_authenticated_as_user(&self) -> bool {
+        if self.auth_code.is_none() {
+            return false;
+        }
+        // Installation tokens, like the `GITHUB_TOKEN` of Actions, aren't allowed to read `/user`
+        let page = match self.get_page(self.api_url("/user")).await {
+            Ok(page) => page,
+            Err(err) => {
+                debug!("Couldn't tell who the token belongs to: {err}");
+                return false;
+            }
+        };
+        serde_json::from_str::<AuthenticatedUserData>(&page.body)
+            .is_ok_and(|data| data.login.eq_ignore_ascii_case(&self.user))
+    }
+
+    /// Requests left before the rate limit, as of the last response.
+    pub fn rate_limit_remaining(&self) -> Option<u32> {
+        self.rate_limit
+            .lock()
+            .unwrap()
+            .map(|rate_limit| rate_limit.remaining)
+    }
+
+    pub(crate) async fn send(
+        &self,
+        url: Url,
+        etag: Option<&str>,
+        media_type: Option<&str>,
+    ) -> reqwest::Result<Response> {
+        loop {
+            self.wait_for_rate_limit().await;
+
+            let mut builder = self
+                .client
+                .get(url.clone())
+                .header("User-Agent", USER_AGENT);
+            if let Some(authorization) = self.authorization() {
+                builder = builder.header("Authorization", authorization);
+            }
+            if let Some(etag) = etag {
+                builder = builder.header("If-None-Match", etag);
+            }
+            if let Some(media_type) = media_type {
+                builder = builder.header("Accept", media_type);
+            }
+            let response = self.retry.send(builder).await?;
+
+            let rate_limit = RateLimit::from_headers(response.headers());
+            if rate_limit.is_some() {
+                *self.rate_limit.lock().unwrap() = rate_limit;
+            }
+            if !matches!(
+                response.status(),
+                StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+            ) {
+                return Ok(response);
+            }
+
+            // Secondary rate limits tell us how long to back off for directly
+            if let Some(retry_after) = response
+                .headers()
+                .get("Retry-After")
+                .and_then(|value| value.to_str().ok()?.parse::<u64>().ok())
+            {
+                warn!("Hit a secondary rate limit, retrying in {retry_after}s...");
+                tokio::time::sleep(Duration::from_secs(retry_after)).await;
+                continue;
+            }
+            if rate_limit.is_none_or(|limit| limit.remaining != 0) {
+                return Ok(response);
+            }
+        }
+    }
+
+    /// Records that `full_name` was left out of the report, for the summary at the end.
+    pub(crate) fn skip_repo(&self, full_name: &str, reason: impl Into<String>) {
+        let reason = reason.into();
+        debug!("Skipping {full_name}: {reason}");
+        self.skipped
+            .lock()
+            .unwrap()
+            .push((full_name.to_string(), reason));
+    }
+
+    /// The repos recorded with [`GitHub::skip_repo`] since the last call, with the reasons.
+    pub fn take_skipped(&self) -> Vec<(String, String)> {
+        std::mem::take(&mut *self.skipped.lock().unwrap())
+    }
+
+    /// Sleeps until the rate limit resets if the last response said it was exhausted.
+    async fn wait_for_rate_limit(&self) {
+        let Some(rate_limit) = *self.rate_limit.lock().unwrap() else {
+            return;
+        };
+        if rate_limit.remaining > 0 {
+            return;
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let wait = rate_limit.reset.saturating_sub(now) + 1;
+        warn!(
+            "GitHub rate limit exhausted, resuming in {}m {}s...",
+            wait / 60,
+            wait % 60
+        );
+        tokio::time::sleep(Duration::from_secs(wait)).await;
+        *self.rate_limit.lock().unwrap() = None;
+    }
+
+    /// Fetches a response body, reusing the cached copy if GitHub says it hasn't changed.
+    pub async fn get_page(&self, url: impl IntoUrl) -> Result<Page, MetricsError> {
+        self.get_page_as(url.into_url()?, None).await
+    }
+
+    /// [`GitHub::get_page`] with a custom `Accept` media type, for endpoints with optional
+    /// extra fields.
+    async fn get_page_as(&self, url: Url, media_type: Option<&str>) -> Result<Page, MetricsError> {
+        let authorization = self.authorization();
+        let cached = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.load(url.as_str(), authorization.as_deref()));
+        if self.offline {
+            return match cached {
+                Some(entry) => Ok(Page {
+                    body: entry.body,
+                    next: entry.next.and_then(|next| Url::parse(&next).ok()),
+                }),
+                None => Err(MetricsError::NotCached {
+                    url: url.to_string(),
+                }),
+            };
+        }
+
+        let mut attempt = 0;
+        let response = loop {
+            let response = self
+                .send(
+                    url.clone(),
+                    cached.as_ref().map(|entry| entry.etag.as_str()),
+                    media_type,
+                )
+                .await?;
+            // GitHub computes statistics in the background the first time they're asked for,
+            // responding with 202 Accepted until they're ready
+            if response.status() != StatusCode::ACCEPTED {
+                break response;
+            }
+            if attempt == self.retry.max_retries {
+                return Err(MetricsError::Pending {
+                    url: url.to_string(),
+                });
+            }
+            debug!("{url} isn't ready yet, retrying...");
+            tokio::time::sleep(self.retry.delay(attempt)).await;
+            attempt += 1;
+        };
+        if response.status() == StatusCode::NOT_MODIFIED {
+            if let Some(entry) = cached {
+                return Ok(Page {
+                    body: entry.body,
+                    next: entry.next.and_then(|next| Url::parse(&next).ok()),
+                });
+            }
+        }
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(MetricsError::from_response(&url, status, &body));
+        }
+        let etag = response
+            .headers()
+            .get("ETag")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let next = next_page(&response);
+        let body = response.text().await?;
+
+        if let (Some(cache), Some(etag)) = (&self.cache, etag) {
+            cache.store(
+                url.as_str(),
+                authorization.as_deref(),
+                &CacheEntry {
+                    etag,
+                    next: next.as_ref().map(Url::to_string),
+                    body: body.clone(),
+                },
+            );
+        }
+        Ok(Page { body, next })
+    }
+
+    /// Lines of code per language, and the repo's ecosystems if the backend can tell.
+    pub async fn lines_of_code(
+        &self,
+        repo: &RepoData,
+        backend: LocBackend,
+    ) -> Result<Option<(Vec<LOCData>, Vec<String>)>, Box<dyn Error>> {
+        if self.offline && backend != LocBackend::Linguist {
+            return Err(
+                MetricsError::Offline("Counting lines with codetabs or a local clone").into(),
+            );
+        }
+        let langs = match backend {
+            LocBackend::Codetabs => {
+                loc::codetabs(
+                    &self.client,
+                    self.retry,
+                    &self.codetabs_url,
+                    &repo.full_name,
+                )
+                .await?
+            }
+            LocBackend::Local => {
+                return loc::local(
+                    &repo.clone_url,
+                    self.clone_token(repo),
+                    self.default_excludes,
+                )
+                .await
+            }
+            LocBackend::Linguist => loc::linguist(self, &repo.languages_url).await?,
+        };
+        Ok(langs.map(|langs| (langs, Vec::new())))
+    }
+
+    /// The token to clone the repo with, only needed for private repos.
+    fn clone_token(&self, repo: &RepoData) -> Option<&str> {
+        self.auth_code.as_deref().filter(|_| repo.private)
+    }
+
+    /// SHAs of the user's commits to the repo's default branch.
+    async fn user_commit_shas(&self, full_name: &str) -> Result<HashSet<String>, MetricsError> {
+        let commits: Vec<CommitData> = self
+            .get_all(self.api_url(&format!("/repos/{full_name}/commits?author={}", self.user)))
+            .await?;
+        Ok(commits.into_iter().map(|commit| commit.sha).collect())
+    }
+
+    /// Lines per language that `git blame` attributes to the user's commits, minus excluded
+    /// languages. Returns `None` if the repo couldn't be cloned.
+    pub async fn authored_lines(
+        &self,
+        repo: &RepoData,
+    ) -> Result<Option<BTreeMap<String, u32>>, Box<dyn Error>> {
+        if self.offline {
+            return Err(MetricsError::Offline("--weight-mode blame").into());
+        }
+        let commits = self.user_commit_shas(&repo.full_name).await?;
+
+        let Some(mut lines) = loc::blame(
+            &repo.clone_url,
+            self.clone_token(repo),
+            commits,
+            self.default_excludes,
+        )
+        .await?
+        else {
+            warn!("Failed to clone {} to blame it", repo.full_name);
+            return Ok(None);
+        };
+        lines.retain(|lang, _| !self.excluded_langs.contains(&lang.to_ascii_lowercase()));
+        Ok(Some(lines))
+    }
+
+    /// Messages of the user's commits to the repo, read from a clone. Returns `None` if the repo
+    /// couldn't be cloned.
+    pub async fn commit_messages(
+        &self,
+        full_name: &str,
+    ) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+        if self.offline {
+            return Err(MetricsError::Offline("--metrics commit-messages").into());
+        }
+        let commits = match self.user_commit_shas(full_name).await {
+            Ok(commits) => commits,
+            // Empty repos respond with 409 Conflict
+            Err(MetricsError::Status {
+                status: StatusCode::CONFLICT,
+                ..
+            }) => {
+                debug!("No commits for {full_name}, it's empty");
+                return Ok(Some(Vec::new()));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if commits.is_empty() {
+            return Ok(Some(Vec::new()));
+        }
+        // Whether the repo is private isn't known from its name, so the token is only sent if
+        // cloning without it fails
+        let clone_url = format!("{}/{full_name}.git", self.web_url());
+        let mut messages = loc::commit_messages(&clone_url, None, commits.clone()).await?;
+        if let (None, Some(token)) = (&messages, &self.auth_code) {
+            messages = loc::commit_messages(&clone_url, Some(token), commits).await?;
+        }
+        if messages.is_none() {
+            warn!("Failed to clone {full_name} to read its commit messages");
+        }
+        Ok(messages)
+    }
+
+    /// Lines of code per language in the repo, minus excluded languages, and its ecosystems if
+    /// it was counted locally. Tries the fallback backend if the primary one has no data.
+    pub async fn language_loc_map(
+        &self,
+        repo: &RepoData,
+    ) -> Result<Option<(BTreeMap<String, u32>, Vec<String>)>, Box<dyn Error>> {
+        let mut langs = self.lines_of_code(repo, self.loc_backend).await?;
+        if let (None, Some(fallback)) = (&langs, self.loc_fallback) {
+            debug!(
+                "Falling back to {fallback:?} for language data for repo: {}",
+                repo.full_name
+            );
+            langs = self.lines_of_code(repo, fallback).await?;
+        }
+        let Some((langs, ecosystems)) = langs else {
+            return Ok(None);
+        };
+
+        let langs = langs
+            .into_iter()
+            .filter(|data| {
+                data.language != "Total"
+                    && !self
+                        .excluded_langs
+                        .contains(&data.language.to_ascii_lowercase())
+            })
+            .map(|data| (data.language, data.lines_of_code))
+            .collect();
+        Ok(Some((langs, ecosystems)))
+    }
+
+    /// The raw contents of the file at `path` in the repo's default branch, or `None` if there is
+    /// no such file.
+    pub async fn file_contents(
+        &self,
+        full_name: &str,
+        path: &str,
+    ) -> Result<Option<String>, MetricsError> {
+        let url = parse_url(&self.api_url(&format!("/repos/{full_name}/contents/{path}")))?;
+        match self
+            .get_page_as(url, Some("application/vnd.github.raw+json"))
+            .await
+        {
+            Ok(page) => Ok(Some(page.body)),
+            Err(MetricsError::NotFound { .. }) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// People watching the repo. Repo lists only have `watchers_count`, which is actually the
+    /// stargazer count, so this takes a request of its own. Counts none if that fails, rather
+    /// than leaving out the whole repo.
+    pub async fn watchers(&self, full_name: &str) -> u32 {
+        let watchers = async {
+            let url = parse_url(&self.api_url(&format!("/repos/{full_name}")))?;
+            let page = self.get_page(url.clone()).await?;
+            parse_json::<RepoDetailsData>(&url, &page.body)
+        };
+        match watchers.await {
+            Ok(data) => data.subscribers_count,
+            Err(err) => {
+                warn!("Couldn't count the watchers of {full_name}, counting none: {err}");
+                0
+            }
+        }
+    }
+
+    /// The repo's daily `views` or `clones` over the last 14 days, or `None` if the token can't
+    /// push to the repo, which GitHub requires to see its traffic.
+    pub async fn traffic(
+        &self,
+        full_name: &str,
+        kind: &str,
+    ) -> Result<Option<TrafficData>, MetricsError> {
+        let url = parse_url(&self.api_url(&format!("/repos/{full_name}/traffic/{kind}")))?;
+        let page = match self.get_page(url.clone()).await {
+            Ok(page) => page,
+            Err(MetricsError::NotFound { .. }) => return Ok(None),
+            Err(MetricsError::Status { status, .. }) if status == StatusCode::FORBIDDEN => {
+                return Ok(None)
+            }
+            Err(err) => return Err(err),
+        };
+        parse_json(&url, &page.body).map(Some)
+    }
+
+    /// The user's share of the lines added to the repo, from the weekly `stats/contributors`
+    /// totals. Returns `None` if GitHub is still computing them after a few tries.
+    pub async fn lines_added_ratio(&self, full_name: &str) -> Result<Option<f32>, MetricsError> {
+        let url = parse_url(&self.api_url(&format!("/repos/{full_name}/stats/contributors")))?;
+        let page = match self.get_page(url.clone()).await {
+            Ok(page) => page,
+            Err(MetricsError::Pending { .. }) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let stats: Vec<ContributorStatsData> = parse_json(&url, &page.body)?;
+
+        let mut total = 0;
+        let mut user = 0;
+        for contributor in stats {
+            let added: u64 = contributor.weeks.iter().map(|week| week.a).sum();
+            total += added;
+            if contributor
+                .author
+                .is_some_and(|author| author.login.eq_ignore_ascii_case(&self.user))
+            {
+                user += added;
+            }
+        }
+        Ok((total > 0).then(|| user as f32 / total as f32))
+    }
+
+    /// Number of issues and pull requests matching a search query, e.g.
+    /// `type:pr author:octocat is:merged`.
+    pub async fn search_count(&self, query: &str) -> Result<u32, MetricsError> {
+        let mut url = parse_url(&self.api_url("/search/issues"))?;
+        url.query_pairs_mut()
+            .append_pair("q", query)
+            .append_pair("per_page", "1");
+        let page = self.get_page(url.clone()).await?;
+        let data: SearchCountData = parse_json(&url, &page.body)?;
+        Ok(data.total_count)
+    }
+
+    /// Fetches every page of a list endpoint by following `Link: rel="next"` headers.
+    pub async fn get_all<T: DeserializeOwned>(
+        &self,
+        url: impl IntoUrl,
+    ) -> Result<Vec<T>, MetricsError> {
+        self.get_all_as(url, None).await
+    }
+
+    /// [`GitHub::get_all`] with a custom `Accept` media type, e.g.
+    /// `application/vnd.github.star+json` for when each stargazer starred the repo.
+    pub async fn get_all_as<T: DeserializeOwned>(
+        &self,
+        url: impl IntoUrl,
+        media_type: Option<&str>,
+    ) -> Result<Vec<T>, MetricsError> {
+        self.stream_all_as(url.into_url()?, media_type)
+            .try_collect()
+            .await
+    }
+
+    /// [`GitHub::get_all`] as a stream, fetching the next page only once the items of the
+    /// previous one have been consumed.
+    pub fn stream_all<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        url: Url,
+    ) -> impl Stream<Item = Result<T, MetricsError>> + 'a {
+        self.stream_all_as(url, None)
+    }
+
+    /// [`GitHub::stream_all`] with a custom `Accept` media type.
+    pub fn stream_all_as<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        mut url: Url,
+        media_type: Option<&'a str>,
+    ) -> impl Stream<Item = Result<T, MetricsError>> + 'a {
+        // GitHub caps page size at 100
+        url.query_pairs_mut().append_pair("per_page", "100");
+
+        stream::try_unfold(Some(url), move |next| async move {
+            let Some(url) = next else {
+                return Ok(None);
+            };
+            let page = self.get_page_as(url.clone(), media_type).await?;
+            // Empty repos answer lists like their contributors with 204 No Content
+            let items: Vec<T> = if page.body.trim().is_empty() {
+                Vec::new()
+            } else {
+                parse_json(&url, &page.body)?
+            };
+            Ok::<_, MetricsError>(Some((stream::iter(items.into_iter().map(Ok)), page.next)))
+        })
+        .try_flatten()
+    }
+
+    /// The user's repos and those of their organizations, like [`collect_repos`], but listed a
+    /// page at a time as the stream is polled, so consumers that stop early skip the rest.
+    pub fn repos(&self) -> impl Stream<Item = Result<RepoData, MetricsError>> + '_ {
+        stream::once(async move {
+            // The public listings below never include private repos, but if the token belongs
+            // to the user we can ask for everything they can see instead.
+            if self.is_authenticated_as_user().await {
+                info!("Token belongs to the selected user, listing private repos too...");
+                let url = parse_url(
+                    &self.api_url("/user/repos?affiliation=owner,collaborator,organization_member"),
+                )?;
+                let repos = self.stream_all::<RepoData>(url).try_filter(|repo| {
+                    future::ready(
+                        !repo.owner.is_organization() || self.org_filter.allows(&repo.owner.login),
+                    )
+                });
+                return Ok(repos.left_stream());
+            }
+
+            let user_data = self.user_data().await?;
+            info!(
+                "Successfully found user. listing repos at `{}` and organizations at `{}`...",
+                user_data.repos_url, user_data.organizations_url
+            );
+            let mut repos_url = parse_url(&user_data.repos_url)?;
+            if self.auth_code.is_some() {
+                // Also the public repos the user is an outside collaborator on
+                repos_url.query_pairs_mut().append_pair("type", "all");
+            }
+            let user_repos = self.stream_all(repos_url);
+            let org_repos = self
+                .stream_all::<OrgData>(parse_url(&user_data.organizations_url)?)
+                .try_filter(|org| {
+                    let allowed = self.org_filter.allows(&org.login);
+                    if !allowed {
+                        debug!("Skipping the repos of organization {}", org.login);
+                    }
+                    future::ready(allowed)
+                })
+                .and_then(|org| future::ready(parse_url(&org.repos_url)))
+                .map_ok(|url| self.stream_all(url))
+                .try_flatten();
+            Ok::<_, MetricsError>(user_repos.chain(org_repos).right_stream())
+        })
+        .try_flatten()
+    }
+}
+
+pub struct Page {
+    pub body: String,
+    pub next: Option<Url>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct RateLimit {
+    remaining: u32,
+    /// Unix timestamp at which the limit resets
+    reset: u64,
+}
+impl RateLimit {
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| -> Option<u64> { headers.get(name)?.to_str().ok()?.parse().ok() };
+        Some(Self {
+            remaining: header("X-RateLimit-Remaining")? as u32,
+            reset: header("X-RateLimit-Reset")?,
+        })
+    }
+}
+
+pub(crate) fn next_page(response: &Response) -> Option<Url> {
+    let link = response.headers().get("Link")?.to_str().ok()?;
+    link.split(',').find_map(|part| {
+        let (url, params) = part.split_once(';')?;
+        if !params.contains(r#"rel="next""#) {
+            return None;
+        }
+        Url::parse(url.trim().trim_start_matches('<').trim_end_matches('>')).ok()
+    })
+}
+
+/// Every repo from [`GitHub::repos`].
+pub async fn collect_repos(connection: &GitHub) -> Result<Vec<RepoData>, Box<dyn Error>> {
+    let mut repos: Vec<RepoData> = connection.repos().try_collect().await?;
+    let duplicates = dedupe_repos(&mut repos);
+    if duplicates > 0 {
+        info!("Dropped {duplicates} repos listed more than once");
+    }
+    info!("Found all {} repos!", repos.len());
+    Ok(repos)
+}
+
+impl ForgeProvider for GitHub {
+    type Repo = RepoData;
+
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn capabilities(&self) -> Vec<Capability> {
+        vec![
+            Capability::Organizations,
+            Capability::LinesOfCode,
+            Capability::CommitsByAuthor,
+            Capability::Search,
+            Capability::ContributionGraph,
+            Capability::Licenses,
+            Capability::LocalClones,
+            Capability::Releases,
+            Capability::FileContents,
+            Capability::Sponsors,
+            Capability::Discussions,
+            Capability::Traffic,
+        ]
+    }
+
+    fn list_repos(&self) -> ForgeFuture<'_, Vec<RepoData>> {
+        Box::pin(collect_repos(self))
+    }
+
+    fn list_orgs(&self) -> ForgeFuture<'_, Vec<String>> {
+        Box::pin(async move {
+            let user_data = self.user_data().await?;
+            let orgs: Vec<OrgData> = self.get_all(&user_data.organizations_url).await?;
+            Ok(orgs.into_iter().map(|org| org.login).collect())
+        })
+    }
+
+    fn contributors<'a>(&'a self, repo: &'a RepoData) -> ForgeFuture<'a, Vec<ContributorData>> {
+        Box::pin(async move { Ok(self.get_all(&repo.contributors_url).await?) })
+    }
+
+    fn languages<'a>(
+        &'a self,
+        repo: &'a RepoData,
+    ) -> ForgeFuture<'a, Option<BTreeMap<String, u32>>> {
+        Box::pin(async move {
+            let langs = self.language_loc_map(repo).await?;
+            Ok(langs.map(|(langs, _)| langs))
+        })
+    }
+}
+
+/// Repos owned by someone else that the user committed to, found through GraphQL since REST
+/// has no equivalent listing. Needs a token.
+pub async fn collect_contributed_repos(
+    connection: &GitHub,
+) -> Result<Vec<RepoData>, Box<dyn Error>> {
+    let mut repos = Vec::new();
+    for name in graphql::contributed_repo_names(connection).await? {
+        let url = parse_url(&connection.api_url(&format!("/repos/{name}")))?;
+        let page = connection.get_page(url.clone()).await?;
+        repos.push(parse_json(&url, &page.body)?);
+    }
+    Ok(repos)
+}
+
+pub async fn handle_repo(
+    repo: RepoData,
+    connection: &GitHub,
+) -> Result<Option<RepoInfo>, Box<dyn Error>> {
+    // Get the ratio of all contributions to contributions from the user
+    let contributors = match connection.contributors(&repo).await {
+        Ok(contributors) => contributors,
+        Err(err) => {
+            connection.skip_repo(
+                &repo.full_name,
+                format!("couldn't list contributors: {err}"),
+            );
+            return Ok(None);
+        }
+    };
+    if contributors.is_empty() {
+        connection.skip_repo(&repo.full_name, "empty repository");
+        return Ok(None);
+    }
+
+    let total_contributions = contributors
+        .iter()
+        .map(|data| data.contributions)
+        .sum::<u32>();
+    let Some(user_contributor) = contributors
+        .iter()
+        .find(|contributor| contributor.login.eq_ignore_ascii_case(&connection.user))
+    else {
+        return Ok(None);
+    };
+
+    let mut ratio_of_contributions =
+        user_contributor.contributions as f32 / total_contributions as f32;
+    if connection.weighted && connection.weight_mode == WeightMode::Lines {
+        match connection.lines_added_ratio(&repo.full_name).await {
+            Ok(Some(ratio)) => ratio_of_contributions = ratio,
+            Ok(None) => warn!(
+                "No line statistics for {}, weighting by commits instead",
+                repo.full_name
+            ),
+            Err(err) => warn!(
+                "Couldn't read the line statistics of {}, weighting by commits instead: {err}",
+                repo.full_name
+            ),
+        }
+    }
+
+    // Get the ratio of all languages in the repo
+    let Some((language_loc_map, ecosystems)) = connection.language_loc_map(&repo).await? else {
+        connection.skip_repo(&repo.full_name, "no language data");
+        return Ok(None);
+    };
+
+    let authored_lines = match connection.weight_mode {
+        WeightMode::Blame if connection.weighted => connection.authored_lines(&repo).await?,
+        _ => None,
+    };
+
+    let watchers = connection.watchers(&repo.full_name).await;
+
+    let collaborator =
+        !repo.owner.is_organization() && !repo.owner.login.eq_ignore_ascii_case(&connection.user);
+    Ok(Some(RepoInfo {
+        full_name: repo.full_name,
+        language_loc_map,
+        ratio_of_commits_from_user: ratio_of_contributions,
+        stars: repo.stargazers_count,
+        forks: repo.forks_count,
+        watchers,
+        pushed_at: repo.pushed_at,
+        created_at: repo.created_at,
+        license: repo.license.and_then(|license| license.spdx_id),
+        authored_lines,
+        ecosystems,
+        collaborator,
+    }))
+}
+
+/// Runs [`handle_repo`] over every repo, `concurrency` at a time, keeping the original order
+/// and dropping repos the user hasn't contributed to.
+pub async fn process_repos(
+    connection: &GitHub,
+    repos: Vec<RepoData>,
+    concurrency: usize,
+) -> Result<Vec<RepoInfo>, Box<dyn Error>> {
+    let progress = Progress::new(repos.len());
+    let infos = futures::stream::iter(repos)
+        .map(|repo| {
+            let progress = &progress;
+            let span = info_span!("repo", name = %repo.full_name);
+            async move {
+                progress.start(&repo.full_name);
+                let info = handle_repo(repo, connection).await?;
+                progress.set_api_budget(connection.rate_limit_remaining());
+                progress.finish_repo(info.as_ref().map(processed_message));
+                Ok::<_, Box<dyn Error>>(info)
+            }
+            .instrument(span)
+        })
+        .buffered(concurrency.max(1))
+        .try_filter_map(|info| async move { Ok(info) })
+        .try_collect()
+        .await;
+
+    warn_skipped(connection);
+    infos
+}
+
+/// Lists the repos recorded with [`GitHub::skip_repo`], with the reasons.
+pub(crate) fn warn_skipped(connection: &GitHub) {
+    let skipped = connection.take_skipped();
+    if !skipped.is_empty() {
+        warn!("Skipped {} repos:", skipped.len());
+        for (full_name, reason) in skipped {
+            warn!("  {full_name}: {reason}");
+        }
+    }
+}
@@ -0,0 +1,443 @@
+use std::{collections::BTreeMap, error::Error};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::json;
+use tracing::info;
+
+use crate::{
+    error::{parse_json, parse_url, MetricsError},
+    filter::{RepoFilter, RepoMetadata},
+    progress::{processed_message, Progress},
+    report::RepoInfo,
+    GitHub,
+};
+
+const USER_ID_QUERY: &str = r#"
+query($login: String!) {
+  user(login: $login) { id }
+}
+"#;
+
+const REPOS_QUERY: &str = r#"
+query($login: String!, $userId: ID!, $cursor: String) {
+  user(login: $login) {
+    repositories(first: 50, after: $cursor, ownerAffiliations: [OWNER, ORGANIZATION_MEMBER, COLLABORATOR]) {
+      pageInfo { hasNextPage endCursor }
+      nodes { ...RepoFields }
+    }
+  }
+}
+"#;
+
+/// Aliased to `repositories` so pages of both queries deserialize the same way.
+const CONTRIBUTED_QUERY: &str = r#"
+query($login: String!, $userId: ID!, $cursor: String) {
+  user(login: $login) {
+    repositories: repositoriesContributedTo(first: 50, after: $cursor, includeUserRepositories: false, contributionTypes: [COMMIT]) {
+      pageInfo { hasNextPage endCursor }
+      nodes { ...RepoFields }
+    }
+  }
+}
+"#;
+
+const CONTRIBUTED_NAMES_QUERY: &str = r#"
+query($login: String!, $cursor: String) {
+  user(login: $login) {
+    repositories: repositoriesContributedTo(first: 100, after: $cursor, includeUserRepositories: false, contributionTypes: [COMMIT]) {
+      pageInfo { hasNextPage endCursor }
+      nodes { nameWithOwner }
+    }
+  }
+}
+"#;
+
+const REPO_FIELDS: &str = r#"
+fragment RepoFields on Repository {
+  nameWithOwner
+  owner { __typename login }
+  stargazerCount
+  forkCount
+  watchers { totalCount }
+  pushedAt
+  createdAt
+  isFork
+  isArchived
+  isDisabled
+  isPrivate
+  repositoryTopics(first: 20) { nodes { topic { name } } }
+  licenseInfo { spdxId }
+  languages(first: 25) { edges { size node { name } } }
+  defaultBranchRef {
+    target {
+      ... on Commit {
+        total: history { totalCount }
+        user: history(author: { id: $userId }) { totalCount }
+      }
+    }
+  }
+}
+"#;
+
+impl GitHub {
+    pub async fn graphql<T: DeserializeOwned>(
+        &self,
+        query: &str,
+        variables: serde_json::Value,
+    ) -> Result<T, MetricsError> {
+        if self.offline {
+            return Err(MetricsError::Offline("The GraphQL API"));
+        }
+        let Some(authorization) = self.authorization() else {
+            return Err(MetricsError::TokenRequired("The GraphQL API"));
+        };
+        let url = parse_url(&self.graphql_url())?;
+        let body = json!({ "query": query, "variables": variables });
+        let request = self
+            .client
+            .post(url.clone())
+            .header("User-Agent", "GitHub user stats scraper (reqwest/hyper)")
+            .header("Authorization", authorization)
+            .header("Content-Type", "application/json")
+            .body(body.to_string());
+        let response = self.retry.send(request).await?;
+        let status = response.status();
+        let json = response.text().await?;
+        if !status.is_success() {
+            return Err(MetricsError::from_response(&url, status, &json));
+        }
+
+        let response: GraphQLResponse<T> = parse_json(&url, &json)?;
+        if let Some(error) = response.errors.into_iter().next() {
+            return Err(MetricsError::GraphQL(error.message));
+        }
+        response
+            .data
+            .ok_or_else(|| MetricsError::GraphQL("the response contained no data".to_string()))
+    }
+}
+
+/// Names of repos owned by someone else that the user committed to. GitHub only tracks these
+/// for roughly the last year.
+pub async fn contributed_repo_names(connection: &GitHub) -> Result<Vec<String>, MetricsError> {
+    let mut names = Vec::new();
+    let mut cursor: Option<String> = None;
+    loop {
+        let page: RepoNamesData = connection
+            .graphql(
+                CONTRIBUTED_NAMES_QUERY,
+                json!({ "login": connection.user, "cursor": cursor }),
+            )
+            .await?;
+        let repositories = page.user.repositories;
+        names.extend(
+            repositories
+                .nodes
+                .into_iter()
+                .map(|node| node.name_with_owner),
+        );
+        if !repositories.page_info.has_next_page {
+            break;
+        }
+        cursor = repositories.page_info.end_cursor;
+    }
+    info!("Found {} repos contributed to!", names.len());
+    Ok(names)
+}
+
+/// Collects the same per-repo info as the REST path in a few batched queries.
+/// Languages are reported by GitHub as byte counts rather than lines of code.
+///
+/// With `include_contributed`, repos owned by others that the user committed to are added too.
+pub async fn collect_repo_infos(
+    connection: &GitHub,
+    filter: &RepoFilter,
+    include_contributed: bool,
+) -> Result<Vec<RepoInfo>, Box<dyn Error>> {
+    let user: UserIdData = connection
+        .graphql(USER_ID_QUERY, json!({ "login": connection.user }))
+        .await?;
+    let user_id = user.user.id;
+
+    let mut repos_info = Vec::new();
+    let progress = Progress::new(0);
+    let queries = if include_contributed {
+        &[REPOS_QUERY, CONTRIBUTED_QUERY][..]
+    } else {
+        &[REPOS_QUERY][..]
+    };
+    for query in queries {
+        let query = format!("{query}{REPO_FIELDS}");
+        collect_pages(
+            connection,
+            &query,
+            &user_id,
+            filter,
+            &progress,
+            &mut repos_info,
+        )
+        .await?;
+    }
+    // Org repos the user committed to can turn up in both queries
+    repos_info.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+    repos_info.dedup_by(|a, b| a.full_name == b.full_name);
+    Ok(repos_info)
+}
+
+async fn collect_pages(
+    connection: &GitHub,
+    query: &str,
+    user_id: &str,
+    filter: &RepoFilter,
+    progress: &Progress,
+    repos_info: &mut Vec<RepoInfo>,
+) -> Result<(), Box<dyn Error>> {
+    let mut cursor: Option<String> = None;
+    loop {
+        let page: ReposData = connection
+            .graphql(
+                query,
+                json!({ "login": connection.user, "userId": user_id, "cursor": cursor }),
+            )
+            .await?;
+        let repositories = page.user.repositories;
+        info!("Found {} repos!", repositories.nodes.len());
+
+        let repos = repositories
+            .nodes
+            .into_iter()
+            .filter(|repo| {
+                repo.owner.typename != "Organization"
+                    || connection.org_filter.allows(&repo.owner.login)
+            })
+            .collect();
+        let repos = filter.apply(repos);
+        progress.add_to_total(repos.len());
+        for repo in repos {
+            progress.start(&repo.name_with_owner);
+            let Some(branch) = repo.default_branch_ref else {
+                continue;
+            };
+            let (Some(total), Some(user)) = (branch.target.total, branch.target.user) else {
+                continue;
+            };
+            if user.total_count == 0 || total.total_count == 0 {
+                continue;
+            }
+            let ratio_of_contributions = user.total_count as f32 / total.total_count as f32;
+
+            let language_loc_map: BTreeMap<String, u32> = repo
+                .languages
+                .edges
+                .into_iter()
+                .filter(|edge| {
+                    !connection
+                        .excluded_langs
+                        .contains(&edge.node.name.to_ascii_lowercase())
+                })
+                .map(|edge| (edge.node.name, edge.size))
+                .collect();
+
+            let collaborator = repo.owner.typename == "User"
+                && !repo.owner.login.eq_ignore_ascii_case(&connection.user);
+            let info = RepoInfo {
+                full_name: repo.name_with_owner,
+                language_loc_map,
+                ratio_of_commits_from_user: ratio_of_contributions,
+                stars: repo.stargazer_count,
+                forks: repo.fork_count,
+                watchers: repo.watchers.total_count,
+                pushed_at: repo.pushed_at,
+                created_at: repo.created_at,
+                license: repo.license_info.and_then(|license| license.spdx_id),
+                authored_lines: None,
+                ecosystems: Vec::new(),
+                collaborator,
+            };
+            progress.finish_repo(Some(processed_message(&info)));
+            repos_info.push(info);
+        }
+
+        if !repositories.page_info.has_next_page {
+            break;
+        }
+        cursor = repositories.page_info.end_cursor;
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphQLResponse<T> {
+    data: Option<T>,
+    #[serde(default)]
+    errors: Vec<GraphQLError>,
+}
+
+#[derive(Deserialize, Debug)]
+struct GraphQLError {
+    message: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct UserIdData {
+    user: UserId,
+}
+
+#[derive(Deserialize, Debug)]
+struct UserId {
+    id: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReposData {
+    user: UserRepositories,
+}
+
+#[derive(Deserialize, Debug)]
+struct UserRepositories {
+    repositories: RepositoryConnection,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RepositoryConnection {
+    page_info: PageInfo,
+    nodes: Vec<RepositoryNode>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RepoNamesData {
+    user: UserRepoNames,
+}
+
+#[derive(Deserialize, Debug)]
+struct UserRepoNames {
+    repositories: RepoNameConnection,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RepoNameConnection {
+    page_info: PageInfo,
+    nodes: Vec<RepoName>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RepoName {
+    name_with_owner: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RepositoryNode {
+    name_with_owner: String,
+    owner: RepositoryOwner,
+    stargazer_count: u32,
+    fork_count: u32,
+    watchers: TotalCount,
+    pushed_at: Option<String>,
+    created_at: Option<String>,
+    is_fork: bool,
+    is_archived: bool,
+    is_disabled: bool,
+    is_private: bool,
+    repository_topics: TopicConnection,
+    license_info: Option<LicenseInfo>,
+    languages: LanguageConnection,
+    default_branch_ref: Option<BranchRef>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RepositoryOwner {
+    #[serde(rename = "__typename")]
+    typename: String,
+    login: String,
+}
+
+impl RepoMetadata for RepositoryNode {
+    fn full_name(&self) -> &str {
+        &self.name_with_owner
+    }
+    fn is_fork(&self) -> bool {
+        self.is_fork
+    }
+    fn is_archived(&self) -> bool {
+        self.is_archived
+    }
+    fn is_disabled(&self) -> bool {
+        self.is_disabled
+    }
+    fn is_private(&self) -> bool {
+        self.is_private
+    }
+    fn topics(&self) -> Vec<&str> {
+        self.repository_topics
+            .nodes
+            .iter()
+            .map(|node| node.topic.name.as_str())
+            .collect()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LicenseInfo {
+    spdx_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TopicConnection {
+    nodes: Vec<TopicNode>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TopicNode {
+    topic: Topic,
+}
+
+#[derive(Deserialize, Debug)]
+struct Topic {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct LanguageConnection {
+    edges: Vec<LanguageEdge>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LanguageEdge {
+    size: u32,
+    node: LanguageNode,
+}
+
+#[derive(Deserialize, Debug)]
+struct LanguageNode {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct BranchRef {
+    target: CommitTarget,
+}
+
+#[derive(Deserialize, Debug)]
+struct CommitTarget {
+    total: Option<TotalCount>,
+    user: Option<TotalCount>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TotalCount {
+    total_count: u32,
+}
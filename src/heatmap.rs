@@ -0,0 +1,171 @@
+use std::{error::Error, fmt::Write, time::UNIX_EPOCH};
+
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commits::{commit_dates, recent_months, MONTHS},
+    GitHub, RepoInfo,
+};
+
+pub const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct CommitHeatmap {
+    /// Offset from UTC the hours are in, in minutes
+    pub utc_offset_minutes: i32,
+    /// Commits over the last year per weekday, Monday first, and hour of the day
+    pub counts: [[u32; 24]; 7],
+}
+impl CommitHeatmap {
+    /// Adds another account's commits to these.
+    pub fn merge(&mut self, other: CommitHeatmap) {
+        for (day, other_day) in self.counts.iter_mut().zip(other.counts) {
+            for (hour, count) in day.iter_mut().zip(other_day) {
+                *hour += count;
+            }
+        }
+    }
+
+    /// The weekday index and hour with the most commits.
+    pub fn busiest(&self) -> Option<(usize, usize)> {
+        (0..7)
+            .flat_map(|day| (0..24).map(move |hour| (day, hour)))
+            .filter(|(day, hour)| self.counts[*day][*hour] > 0)
+            .max_by_key(|(day, hour)| self.counts[*day][*hour])
+    }
+}
+
+/// Parses offsets like `+02:00`, `-0530` or `Z`.
+pub fn parse_utc_offset(offset: &str) -> Result<i32, String> {
+    if offset.eq_ignore_ascii_case("z") || offset == "0" {
+        return Ok(0);
+    }
+    let invalid = || format!("`{offset}` isn't an offset like +02:00");
+    let (sign, rest) = match offset.as_bytes().first() {
+        Some(b'+') => (1, &offset[1..]),
+        Some(b'-') => (-1, &offset[1..]),
+        _ => return Err(invalid()),
+    };
+    let digits = rest.replace(':', "");
+    let (hours, minutes) = match digits.len() {
+        1 | 2 => (digits.as_str(), "0"),
+        4 => digits.split_at(2),
+        _ => return Err(invalid()),
+    };
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 14 || minutes >= 60 {
+        return Err(invalid());
+    }
+    Ok(sign * (hours * 60 + minutes))
+}
+
+/// Buckets the user's commits to each repo over the last year by weekday and hour. GitHub
+/// reports commit times in UTC, so they are shifted by `utc_offset_minutes`.
+pub async fn collect_heatmap(
+    connection: &GitHub,
+    repos: &[RepoInfo],
+    concurrency: usize,
+    utc_offset_minutes: i32,
+) -> Result<CommitHeatmap, Box<dyn Error>> {
+    let since = format!("{}-01T00:00:00Z", recent_months(MONTHS)[0]);
+    let dates: Vec<Vec<String>> = futures::stream::iter(repos)
+        .map(|repo| commit_dates(connection, &repo.full_name, &since))
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    let mut heatmap = CommitHeatmap {
+        utc_offset_minutes,
+        ..Default::default()
+    };
+    for date in dates.iter().flatten() {
+        let Ok(time) = humantime::parse_rfc3339(date) else {
+            continue;
+        };
+        let Ok(since_epoch) = time.duration_since(UNIX_EPOCH) else {
+            continue;
+        };
+        let seconds = since_epoch.as_secs() as i64 + utc_offset_minutes as i64 * 60;
+        let days = seconds.div_euclid(86_400);
+        let hour = seconds.rem_euclid(86_400) / 3600;
+        // 1970-01-01 was a Thursday
+        let weekday = (days + 3).rem_euclid(7);
+        heatmap.counts[weekday as usize][hour as usize] += 1;
+    }
+    Ok(heatmap)
+}
+
+/// Draws the heatmap as a grid of shaded cells, one row per weekday and two columns per hour.
+pub fn render_heatmap(heatmap: &CommitHeatmap) -> String {
+    let max = heatmap.counts.iter().flatten().copied().max().unwrap_or(0);
+    let mut out = String::new();
+    write!(out, "    ").unwrap();
+    for hour in (0..24).step_by(3) {
+        write!(out, "{hour:<6}").unwrap();
+    }
+    writeln!(out).unwrap();
+    for (day, hours) in WEEKDAYS.iter().zip(&heatmap.counts) {
+        write!(out, "{day} ").unwrap();
+        for count in hours {
+            let shade = match (*count, max) {
+                (0, _) | (_, 0) => SHADES[0],
+                (count, max) => {
+                    let level = (count * (SHADES.len() as u32 - 1)).div_ceil(max);
+                    SHADES[level as usize]
+                }
+            };
+            write!(out, "{shade}{shade}").unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offsets_parse_with_or_without_minutes() {
+        assert_eq!(parse_utc_offset("Z"), Ok(0));
+        assert_eq!(parse_utc_offset("+02:00"), Ok(120));
+        assert_eq!(parse_utc_offset("-0530"), Ok(-330));
+        assert_eq!(parse_utc_offset("+9"), Ok(540));
+        assert_eq!(parse_utc_offset("+14:00"), Ok(840));
+    }
+
+    #[test]
+    fn invalid_offsets_are_rejected() {
+        for offset in ["02:00", "+15:00", "+01:60", "+1:30", "+ab:cd", ""] {
+            assert!(parse_utc_offset(offset).is_err(), "{offset}");
+        }
+    }
+
+    #[test]
+    fn busiest_hour_is_the_most_commits() {
+        let mut heatmap = CommitHeatmap::default();
+        assert_eq!(heatmap.busiest(), None);
+        heatmap.counts[0][9] = 3;
+        let mut other = CommitHeatmap::default();
+        other.counts[0][9] = 1;
+        other.counts[4][17] = 3;
+        heatmap.merge(other);
+        assert_eq!(heatmap.counts[0][9], 4);
+        assert_eq!(heatmap.busiest(), Some((0, 9)));
+    }
+
+    #[test]
+    fn cells_are_shaded_relative_to_the_busiest() {
+        let mut heatmap = CommitHeatmap::default();
+        heatmap.counts[0][0] = 4;
+        heatmap.counts[0][1] = 1;
+        let rendered = render_heatmap(&heatmap);
+        let monday = rendered.lines().nth(1).unwrap();
+        assert!(monday.starts_with("Mon ██░░  "));
+        assert_eq!(rendered.lines().count(), 8);
+    }
+}
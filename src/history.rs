@@ -0,0 +1,209 @@
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rusqlite::{params, Connection};
+
+use crate::{RepoInfo, Report};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    timestamp INTEGER NOT NULL,
+    subject TEXT NOT NULL,
+    weighted INTEGER NOT NULL,
+    total_stars REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS run_languages (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    language TEXT NOT NULL,
+    loc INTEGER NOT NULL,
+    percent REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS run_repos (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    full_name TEXT NOT NULL,
+    stars INTEGER NOT NULL,
+    contribution_ratio REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS run_repo_languages (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    full_name TEXT NOT NULL,
+    language TEXT NOT NULL,
+    loc INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS repo_traffic (
+    full_name TEXT NOT NULL,
+    date TEXT NOT NULL,
+    views INTEGER NOT NULL,
+    unique_visitors INTEGER NOT NULL,
+    clones INTEGER NOT NULL,
+    unique_cloners INTEGER NOT NULL,
+    PRIMARY KEY (full_name, date)
+);
+";
+
+/// SQLite database that keeps every run's results for trend analysis.
+pub struct History {
+    conn: Connection,
+}
+impl History {
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Stores the report as a new run. `subject` identifies who the report is about, e.g. the
+    /// user name. Returns the id of the run.
+    pub fn record(&mut self, subject: &str, report: &Report) -> rusqlite::Result<i64> {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO runs (timestamp, subject, weighted, total_stars) VALUES (?1, ?2, ?3, ?4)",
+            params![timestamp, subject, report.weighted, report.total_stars],
+        )?;
+        let run_id = tx.last_insert_rowid();
+
+        let mut language_locs: BTreeMap<&str, u64> = BTreeMap::new();
+        for repo in &report.repos {
+            tx.execute(
+                "INSERT INTO run_repos (run_id, full_name, stars, contribution_ratio) VALUES (?1, ?2, ?3, ?4)",
+                params![run_id, repo.full_name, repo.stars, repo.ratio_of_commits_from_user],
+            )?;
+            for (language, loc) in &repo.language_loc_map {
+                tx.execute(
+                    "INSERT INTO run_repo_languages (run_id, full_name, language, loc) VALUES (?1, ?2, ?3, ?4)",
+                    params![run_id, repo.full_name, language, loc],
+                )?;
+                *language_locs.entry(language).or_default() += *loc as u64;
+            }
+        }
+        for (language, percent) in &report.languages {
+            let loc = report
+                .language_loc
+                .get(language)
+                .or(language_locs.get(language.as_str()))
+                .copied()
+                .unwrap_or(0);
+            tx.execute(
+                "INSERT INTO run_languages (run_id, language, loc, percent) VALUES (?1, ?2, ?3, ?4)",
+                params![run_id, language, loc as i64, percent],
+            )?;
+        }
+
+        // Days rather than runs, since GitHub only keeps 14 days of traffic and consecutive runs
+        // overlap. The latest numbers for a day win.
+        if let Some(traffic) = &report.traffic {
+            for repo in &traffic.repos {
+                for day in &repo.days {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO repo_traffic (full_name, date, views, unique_visitors, clones, unique_cloners) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                        params![
+                            repo.full_name,
+                            day.date,
+                            day.views as i64,
+                            day.unique_visitors as i64,
+                            day.clones as i64,
+                            day.unique_cloners as i64
+                        ],
+                    )?;
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(run_id)
+    }
+
+    /// Ids of the most recent runs, newest first. Only runs about `subject` are included if
+    /// it's given.
+    pub fn latest_runs(&self, subject: Option<&str>, limit: usize) -> rusqlite::Result<Vec<i64>> {
+        let mut statement = self.conn.prepare(
+            "SELECT id FROM runs WHERE ?1 IS NULL OR subject = ?1 ORDER BY timestamp DESC, id DESC LIMIT ?2",
+        )?;
+        let ids = statement
+            .query_map(params![subject, limit as i64], |row| row.get(0))?
+            .collect();
+        ids
+    }
+
+    /// Rebuilds the report that was recorded for a run.
+    pub fn load_run(&self, run_id: i64) -> rusqlite::Result<Report> {
+        let (weighted, total_stars) = self.conn.query_row(
+            "SELECT weighted, total_stars FROM runs WHERE id = ?1",
+            params![run_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let mut statement = self
+            .conn
+            .prepare("SELECT language, percent, loc FROM run_languages WHERE run_id = ?1")?;
+        let mut languages = BTreeMap::new();
+        let mut language_loc = BTreeMap::new();
+        let rows = statement.query_map(params![run_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get(1)?, row.get::<_, i64>(2)?))
+        })?;
+        for row in rows {
+            let (language, percent, loc) = row?;
+            language_loc.insert(language.clone(), loc as u64);
+            languages.insert(language, percent);
+        }
+
+        let mut statement = self
+            .conn
+            .prepare("SELECT full_name, language, loc FROM run_repo_languages WHERE run_id = ?1")?;
+        let mut repo_languages: BTreeMap<String, BTreeMap<String, u32>> = BTreeMap::new();
+        let rows = statement.query_map(params![run_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?))
+        })?;
+        for row in rows {
+            let (full_name, language, loc) = row?;
+            repo_languages
+                .entry(full_name)
+                .or_default()
+                .insert(language, loc);
+        }
+
+        let mut statement = self.conn.prepare(
+            "SELECT full_name, stars, contribution_ratio FROM run_repos WHERE run_id = ?1",
+        )?;
+        let repos = statement
+            .query_map(params![run_id], |row| {
+                let full_name: String = row.get(0)?;
+                Ok(RepoInfo {
+                    language_loc_map: repo_languages.remove(&full_name).unwrap_or_default(),
+                    full_name,
+                    stars: row.get(1)?,
+                    ratio_of_commits_from_user: row.get(2)?,
+                    // Not recorded in the history
+                    forks: 0,
+                    watchers: 0,
+                    pushed_at: None,
+                    created_at: None,
+                    license: None,
+                    authored_lines: None,
+                    ecosystems: Vec::new(),
+                    collaborator: false,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        Ok(Report {
+            weighted,
+            languages,
+            total_loc: language_loc.values().sum(),
+            language_loc,
+            total_stars,
+            repos,
+            // Nothing else is recorded in the history
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,408 @@
+use std::fmt::Write;
+
+use crate::{
+    activity::RECENT_DAYS, card::escape, colors::language_color_or_fallback, format::NumberFormat,
+    markdown::push_date, sponsors::dollars, wakatime::combined_languages, Report,
+};
+
+const STYLE: &str = "
+body { font-family: 'Segoe UI', Ubuntu, sans-serif; color: #24292f; max-width: 960px; margin: 2em auto; padding: 0 1em; }
+h1, h2 { font-weight: 600; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; }
+th, td { text-align: left; padding: 6px 10px; border-bottom: 1px solid #d0d7de; }
+td.num, th.num { text-align: right; }
+.swatch { display: inline-block; width: 10px; height: 10px; border-radius: 50%; margin-right: 6px; }
+.bar { display: flex; height: 8px; border-radius: 4px; overflow: hidden; min-width: 120px; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 4px 16px; }
+dt { font-weight: 600; }
+";
+
+/// Renders the report as a single HTML file with no external resources. `chart_svg` is
+/// embedded as the overview chart, and `settings` are listed so readers know how the numbers
+/// were produced.
+pub fn render_html(
+    report: &Report,
+    chart_svg: &str,
+    settings: &[(&str, String)],
+    format: &NumberFormat,
+) -> String {
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>").unwrap();
+    writeln!(html, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">").unwrap();
+    writeln!(
+        html,
+        "<title>GitHub metrics</title>\n<style>{STYLE}</style>\n</head>\n<body>"
+    )
+    .unwrap();
+
+    writeln!(html, "<h1>GitHub metrics</h1>").unwrap();
+    for profile in &report.profiles {
+        writeln!(
+            html,
+            "<p><strong>{}</strong> · {} followers · {} following · {} public repos · joined {}</p>",
+            escape(profile.name.as_deref().unwrap_or(&profile.login)),
+            profile.followers,
+            profile.following,
+            profile.public_repos,
+            escape(profile.created_date())
+        )
+        .unwrap();
+    }
+    writeln!(html, "{chart_svg}").unwrap();
+
+    writeln!(html, "<h2>Settings</h2>\n<dl>").unwrap();
+    for (name, value) in settings {
+        writeln!(html, "<dt>{}</dt><dd>{}</dd>", escape(name), escape(value)).unwrap();
+    }
+    writeln!(html, "</dl>").unwrap();
+
+    writeln!(html, "<h2>Languages</h2>\n<table>").unwrap();
+    writeln!(
+        html,
+        "<tr><th>Language</th><th class=\"num\">Percent</th><th class=\"num\">Lines of code</th></tr>"
+    )
+    .unwrap();
+    for (lang, percent) in format.sorted_languages(report) {
+        let loc = report
+            .language_loc
+            .get(lang)
+            .map(|loc| format.count(*loc))
+            .unwrap_or_default();
+        writeln!(
+            html,
+            "<tr><td><span class=\"swatch\" style=\"background: {}\"></span>{}</td><td class=\"num\">{}</td><td class=\"num\">{loc}</td></tr>",
+            language_color_or_fallback(lang),
+            escape(lang),
+            format.percent(percent)
+        )
+        .unwrap();
+    }
+    writeln!(html, "</table>").unwrap();
+
+    if let Some(stats) = &report.wakatime {
+        writeln!(html, "<h2>Code and time spent</h2>\n<table>").unwrap();
+        writeln!(
+            html,
+            "<tr><th>Language</th><th class=\"num\">Code</th><th class=\"num\">Time spent</th><th class=\"num\">Hours ({})</th></tr>",
+            escape(&stats.range)
+        )
+        .unwrap();
+        for row in combined_languages(report) {
+            writeln!(
+                html,
+                "<tr><td><span class=\"swatch\" style=\"background: {}\"></span>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{:.1}</td></tr>",
+                language_color_or_fallback(row.language),
+                escape(row.language),
+                format.percent(row.code_percent),
+                format.percent(row.time_percent),
+                row.hours
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    if let Some(licenses) = &report.licenses {
+        writeln!(html, "<h2>Licenses</h2>\n<table>").unwrap();
+        writeln!(
+            html,
+            "<tr><th>License</th><th class=\"num\">Repos</th><th class=\"num\">Lines of code</th></tr>"
+        )
+        .unwrap();
+        for share in &licenses.licenses {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{:.2}%</td></tr>",
+                escape(&share.license),
+                share.repos,
+                share.loc_percent
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    if let Some(packages) = &report.packages {
+        writeln!(
+            html,
+            "<h2>Package downloads</h2>\n<p><strong>Downloads:</strong> {} · <strong>Last 90 days:</strong> {}</p>\n<table>",
+            format.count(packages.downloads),
+            format.count(packages.recent_downloads)
+        )
+        .unwrap();
+        writeln!(
+            html,
+            "<tr><th>Package</th><th>Registry</th><th>Repository</th><th class=\"num\">Downloads</th><th class=\"num\">Last 90 days</th></tr>"
+        )
+        .unwrap();
+        for package in &packages.packages {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                escape(&package.name),
+                escape(&package.registry),
+                escape(&package.full_name),
+                format.count(package.downloads),
+                package
+                    .recent_downloads
+                    .map_or_else(String::new, |recent| format.count(recent))
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    if let Some(discussions) = &report.discussions {
+        writeln!(
+            html,
+            "<h2>Discussions</h2>\n<p><strong>Started:</strong> {} · <strong>Answered:</strong> {}</p>\n<table>",
+            format.count(discussions.created as u64),
+            format.count(discussions.answered as u64)
+        )
+        .unwrap();
+        writeln!(
+            html,
+            "<tr><th>Repository</th><th class=\"num\">Started</th><th class=\"num\">Answered</th></tr>"
+        )
+        .unwrap();
+        for repo in &discussions.repos {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                escape(&repo.full_name),
+                format.count(repo.created as u64),
+                format.count(repo.answered as u64)
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    if let Some(sponsorship) = &report.sponsorship {
+        writeln!(
+            html,
+            "<h2>Sponsors</h2>\n<p><strong>Sponsors:</strong> {} · <strong>Monthly:</strong> {}</p>\n<table>",
+            format.count(sponsorship.sponsors as u64),
+            sponsorship.monthly_dollars()
+        )
+        .unwrap();
+        writeln!(
+            html,
+            "<tr><th>Tier</th><th class=\"num\">Price</th><th class=\"num\">Sponsors</th><th class=\"num\">Monthly</th></tr>"
+        )
+        .unwrap();
+        for tier in &sponsorship.tiers {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                escape(&tier.name),
+                dollars(tier.monthly_cents),
+                format.count(tier.sponsors as u64),
+                dollars(tier.total_cents())
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    if let Some(containers) = &report.containers {
+        writeln!(
+            html,
+            "<h2>Container pulls</h2>\n<p><strong>Pulls:</strong> {}</p>\n<table>",
+            format.count(containers.pulls)
+        )
+        .unwrap();
+        writeln!(
+            html,
+            "<tr><th>Image</th><th>Registry</th><th>Repository</th><th class=\"num\">Pulls</th></tr>"
+        )
+        .unwrap();
+        for image in &containers.images {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"num\">{}</td></tr>",
+                escape(&image.image),
+                escape(&image.registry),
+                escape(&image.full_name),
+                format.count(image.pulls)
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    if let Some(traffic) = &report.traffic {
+        writeln!(
+            html,
+            "<h2>Traffic</h2>\n<p><strong>Views in the last 14 days:</strong> {} · <strong>Clones:</strong> {}</p>\n<table>",
+            format.count(traffic.views),
+            format.count(traffic.clones)
+        )
+        .unwrap();
+        writeln!(
+            html,
+            "<tr><th>Repository</th><th class=\"num\">Views</th><th class=\"num\">Unique visitors</th><th class=\"num\">Clones</th><th class=\"num\">Unique cloners</th></tr>"
+        )
+        .unwrap();
+        for repo in &traffic.repos {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                escape(&repo.full_name),
+                format.count(repo.views),
+                format.count(repo.unique_visitors),
+                format.count(repo.clones),
+                format.count(repo.unique_cloners)
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    if let Some(releases) = &report.releases {
+        writeln!(
+            html,
+            "<h2>Releases</h2>\n<p><strong>Releases:</strong> {} · <strong>Asset downloads:</strong> {}</p>\n<table>",
+            format.count(releases.releases as u64),
+            format.count(releases.downloads)
+        )
+        .unwrap();
+        writeln!(
+            html,
+            "<tr><th>Repository</th><th class=\"num\">Releases</th><th class=\"num\">Downloads</th><th>Latest</th></tr>"
+        )
+        .unwrap();
+        for repo in &releases.repos {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+                escape(&repo.full_name),
+                format.count(repo.releases as u64),
+                format.count(repo.downloads),
+                escape(repo.latest_tag.as_deref().unwrap_or(""))
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    if let Some(activity) = &report.activity {
+        writeln!(html, "<h2>Activity</h2>\n<dl>").unwrap();
+        for (name, repo) in [
+            ("Oldest repo", &activity.oldest_repo),
+            ("Newest repo", &activity.newest_repo),
+        ] {
+            if let Some(repo) = repo {
+                writeln!(
+                    html,
+                    "<dt>{name}</dt><dd>{} ({})</dd>",
+                    escape(&repo.full_name),
+                    repo.created_date()
+                )
+                .unwrap();
+            }
+        }
+        if let Some(days) = activity.average_age_days {
+            writeln!(html, "<dt>Average repo age</dt><dd>{days} days</dd>").unwrap();
+        }
+        writeln!(
+            html,
+            "<dt>Pushed to in the last {RECENT_DAYS} days</dt><dd>{} repos</dd>",
+            activity.recently_pushed
+        )
+        .unwrap();
+        writeln!(html, "</dl>").unwrap();
+    }
+
+    if let Some(ecosystems) = &report.ecosystems {
+        writeln!(html, "<h2>Ecosystems</h2>\n<table>").unwrap();
+        writeln!(
+            html,
+            "<tr><th>Ecosystem</th><th class=\"num\">Repos</th><th class=\"num\">Share of repos</th></tr>"
+        )
+        .unwrap();
+        for share in &ecosystems.ecosystems {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{:.2}%</td></tr>",
+                escape(&share.ecosystem),
+                share.repos,
+                share.repo_percent
+            )
+            .unwrap();
+        }
+        writeln!(
+            html,
+            "<tr><td>No manifest</td><td class=\"num\">{}</td><td></td></tr>",
+            ecosystems.other_repos
+        )
+        .unwrap();
+        writeln!(html, "</table>").unwrap();
+    }
+
+    if let Some(contributions) = &report.contributions {
+        writeln!(
+            html,
+            "<h2>Contributions</h2>\n<p><strong>Contributions in the last year:</strong> {}</p>\n<table>",
+            contributions.total
+        )
+        .unwrap();
+        writeln!(
+            html,
+            "<tr><th>Month</th><th class=\"num\">Contributions</th></tr>"
+        )
+        .unwrap();
+        for (month, count) in &contributions.per_month {
+            writeln!(
+                html,
+                "<tr><td>{month}</td><td class=\"num\">{count}</td></tr>"
+            )
+            .unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+    }
+
+    writeln!(html, "<h2>Repositories</h2>\n<table>").unwrap();
+    writeln!(
+        html,
+        "<tr><th>Repository</th><th class=\"num\">Stars</th><th class=\"num\">Contribution</th><th>Languages</th><th>Last push</th></tr>"
+    )
+    .unwrap();
+    let mut repos: Vec<_> = report.repos.iter().collect();
+    repos.sort_by_key(|repo| std::cmp::Reverse(repo.stars));
+    for repo in repos {
+        let mut bar = String::new();
+        for (lang, percent) in repo.language_percentages() {
+            write!(
+                bar,
+                "<span title=\"{} {percent:.2}%\" style=\"width: {percent:.2}%; background: {}\"></span>",
+                escape(lang),
+                language_color_or_fallback(lang)
+            )
+            .unwrap();
+        }
+        writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{:.2}%</td><td><div class=\"bar\">{bar}</div></td><td>{}</td></tr>",
+            escape(&repo.full_name),
+            repo.stars,
+            repo.ratio_of_commits_from_user * 100.0,
+            escape(push_date(repo))
+        )
+        .unwrap();
+    }
+    writeln!(html, "</table>").unwrap();
+
+    writeln!(
+        html,
+        "<p><strong>Total stars{}:</strong> {:.0} · <strong>Total forks:</strong> {:.0} · <strong>Total watchers:</strong> {:.0}</p>",
+        if report.weighted { " (weighted)" } else { "" },
+        report.total_stars,
+        report.total_forks,
+        report.total_watchers
+    )
+    .unwrap();
+
+    writeln!(html, "</body>\n</html>").unwrap();
+    html
+}
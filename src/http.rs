@@ -0,0 +1,81 @@
+use std::{error::Error, fs, path::PathBuf, time::Duration};
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use reqwest::{Certificate, Client, Proxy, Url};
+
+/// Sent with every request. GitHub, Gitea and package registries reject requests without one.
+pub(crate) const USER_AGENT: &str = "GitHub user stats scraper (reqwest/hyper)";
+
+/// Environment for `git` that authenticates its requests to `url`'s host with `token`. Passing
+/// the header through `GIT_CONFIG_*` keeps the token out of the command line, where other users
+/// could see it, and out of the clone's `.git/config`.
+pub(crate) fn git_auth_env(url: &str, token: &str) -> Vec<(&'static str, String)> {
+    let Ok(url) = Url::parse(url) else {
+        return Vec::new();
+    };
+    let credentials = BASE64_STANDARD.encode(format!("x-access-token:{token}"));
+    vec![
+        ("GIT_CONFIG_COUNT", "1".to_string()),
+        (
+            "GIT_CONFIG_KEY_0",
+            format!("http.{}/.extraHeader", url.origin().ascii_serialization()),
+        ),
+        (
+            "GIT_CONFIG_VALUE_0",
+            format!("Authorization: Basic {credentials}"),
+        ),
+    ]
+}
+
+/// Timeouts and pooling of the HTTP client. One client is meant to be shared by every
+/// connection of a run, so requests to the same host reuse kept-alive connections.
+#[derive(Clone, Debug)]
+pub struct ClientOptions {
+    /// Limit on a whole request, from connecting until the body is read
+    pub request_timeout: Duration,
+    pub connect_timeout: Duration,
+    /// Idle connections kept open per host, usually the number of repos processed at once
+    pub max_idle_per_host: usize,
+    /// Proxy for every request. Without one, `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` are
+    /// honored.
+    pub proxy: Option<String>,
+    /// PEM file of certificates to trust on top of the system ones, e.g. those of a
+    /// TLS-intercepting proxy
+    pub ca_bundle: Option<PathBuf>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            request_timeout: Duration::from_secs(60),
+            connect_timeout: Duration::from_secs(10),
+            max_idle_per_host: 8,
+            proxy: None,
+            ca_bundle: None,
+        }
+    }
+}
+
+impl ClientOptions {
+    pub fn build(&self) -> Result<Client, Box<dyn Error>> {
+        let mut builder = Client::builder()
+            .timeout(self.request_timeout)
+            .connect_timeout(self.connect_timeout)
+            .pool_max_idle_per_host(self.max_idle_per_host)
+            .tcp_keepalive(Duration::from_secs(60));
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy).map_err(|err| format!("Invalid proxy {proxy}: {err}"))?;
+            builder = builder.proxy(proxy);
+        }
+        if let Some(path) = &self.ca_bundle {
+            let pem = fs::read(path)
+                .map_err(|err| format!("Failed to read CA bundle {}: {err}", path.display()))?;
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .map_err(|err| format!("Invalid CA bundle {}: {err}", path.display()))?;
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        Ok(builder.build()?)
+    }
+}
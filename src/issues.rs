@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{error::MetricsError, GitHub};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+pub struct IssueCounts {
+    pub opened: u32,
+    /// Opened by the user and closed since
+    pub closed: u32,
+    /// Commented on by the user, whoever opened them
+    pub commented: u32,
+}
+impl IssueCounts {
+    fn merge(&mut self, other: IssueCounts) {
+        self.opened += other.opened;
+        self.closed += other.closed;
+        self.commented += other.commented;
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct IssueStats {
+    /// Issues in repos owned by the user
+    pub own_repos: IssueCounts,
+    /// Issues everywhere else
+    pub external: IssueCounts,
+}
+impl IssueStats {
+    /// Adds another account's issues to these.
+    pub fn merge(&mut self, other: IssueStats) {
+        self.own_repos.merge(other.own_repos);
+        self.external.merge(other.external);
+    }
+}
+
+/// Counts the user's issue activity across all of GitHub with the search API.
+pub async fn collect_issues(connection: &GitHub) -> Result<IssueStats, MetricsError> {
+    let user = &connection.user;
+    Ok(IssueStats {
+        own_repos: issue_counts(connection, &format!("user:{user}")).await?,
+        external: issue_counts(connection, &format!("-user:{user}")).await?,
+    })
+}
+
+async fn issue_counts(connection: &GitHub, scope: &str) -> Result<IssueCounts, MetricsError> {
+    let user = &connection.user;
+    let opened = format!("type:issue {scope} author:{user}");
+    Ok(IssueCounts {
+        opened: connection.search_count(&opened).await?,
+        closed: connection
+            .search_count(&format!("{opened} is:closed"))
+            .await?,
+        commented: connection
+            .search_count(&format!("type:issue {scope} commenter:{user}"))
+            .await?,
+    })
+}
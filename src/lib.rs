@@ -0,0 +1,65 @@
+//! Scrapes a GitHub user's repositories and summarizes which languages they write and how many
+//! stars their work has earned.
+
+pub mod actions;
+pub mod activity;
+pub mod anonymize;
+pub mod badge;
+pub mod cache;
+pub mod card;
+pub mod chart;
+pub mod colors;
+pub mod commit_messages;
+pub mod commits;
+pub mod compare;
+pub mod config;
+pub mod containers;
+pub mod csv;
+pub mod diff;
+pub mod discussions;
+pub mod ecosystems;
+pub mod error;
+pub mod filter;
+pub mod forge;
+pub mod format;
+pub mod gists;
+pub mod gitea;
+pub mod github;
+pub mod graphql;
+pub mod heatmap;
+pub mod history;
+pub mod html;
+pub mod http;
+pub mod issues;
+pub mod licenses;
+pub mod loc;
+pub mod markdown;
+pub mod metrics;
+pub mod models;
+pub mod org;
+pub mod packages;
+pub mod plan;
+pub mod profile;
+pub mod progress;
+pub mod prometheus;
+pub mod pulls;
+pub mod rate_limit;
+pub mod readme;
+pub mod releases;
+pub mod report;
+pub mod retry;
+pub mod reviews;
+pub mod server;
+pub mod sponsors;
+pub mod stars;
+pub mod streak;
+pub mod template;
+pub mod theme;
+pub mod token;
+pub mod traffic;
+pub mod tui;
+pub mod wakatime;
+
+pub use error::MetricsError;
+pub use github::GitHub;
+pub use report::{build_report, RepoInfo, Report};
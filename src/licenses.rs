@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::RepoInfo;
+
+/// Used for repos without a license file.
+pub const UNLICENSED: &str = "Unlicensed";
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct LicenseStats {
+    /// Largest share of lines of code first
+    pub licenses: Vec<LicenseShare>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LicenseShare {
+    /// SPDX identifier, `NOASSERTION` for licenses GitHub doesn't recognize, or [`UNLICENSED`]
+    pub license: String,
+    pub repos: u32,
+    /// Percent of the lines of code in the report, weighted like the languages
+    pub loc_percent: f32,
+}
+
+/// Groups the repos by license.
+pub fn license_stats(repos: &[RepoInfo], weighted: bool) -> LicenseStats {
+    let mut by_license: BTreeMap<&str, (u32, f32)> = BTreeMap::new();
+    for repo in repos {
+        let loc = repo.language_loc_map.values().sum::<u32>() as f32 * repo.weight(weighted);
+        let entry = by_license
+            .entry(repo.license.as_deref().unwrap_or(UNLICENSED))
+            .or_default();
+        entry.0 += 1;
+        entry.1 += loc;
+    }
+
+    let total_loc = by_license
+        .values()
+        .map(|(_, loc)| loc)
+        .sum::<f32>()
+        .max(1.0);
+    let mut licenses: Vec<LicenseShare> = by_license
+        .into_iter()
+        .map(|(license, (repos, loc))| LicenseShare {
+            license: license.to_string(),
+            repos,
+            loc_percent: loc / total_loc * 100.0,
+        })
+        .collect();
+    licenses.sort_by(|a, b| b.loc_percent.total_cmp(&a.loc_percent));
+    LicenseStats { licenses }
+}
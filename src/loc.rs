@@ -0,0 +1,428 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    error::Error,
+    io,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use reqwest::Client;
+use tempfile::TempDir;
+use tokei::{Config, LanguageType, Languages};
+use tracing::debug;
+
+use crate::{error::MetricsError, http::git_auth_env, models::LOCData, retry::RetryPolicy, GitHub};
+
+/// `.gitattributes` attributes that make linguist leave files out of a repo's languages.
+const LINGUIST_EXCLUDING_ATTRIBUTES: &[&str] = &[
+    "linguist-vendored",
+    "linguist-generated",
+    "linguist-documentation",
+];
+
+/// Generated artifacts left out of local counts unless `--no-default-excludes` is given.
+pub const DEFAULT_EXCLUDES: &[&str] = &[
+    "**/package-lock.json",
+    "**/yarn.lock",
+    "**/pnpm-lock.yaml",
+    "**/Cargo.lock",
+    "**/poetry.lock",
+    "**/Gemfile.lock",
+    "**/composer.lock",
+    "**/go.sum",
+    "**/*.min.js",
+    "**/*.min.css",
+    "**/*_pb2.py",
+    "**/*.pb.go",
+    "**/target/**",
+    "**/node_modules/**",
+];
+
+/// Files that mark a project of an ecosystem, by file name or by `*.extension`.
+const ECOSYSTEM_MANIFESTS: &[(&str, &str)] = &[
+    ("Cargo.toml", "Cargo"),
+    ("package.json", "npm"),
+    ("pyproject.toml", "PyPI"),
+    ("setup.py", "PyPI"),
+    ("requirements.txt", "PyPI"),
+    ("go.mod", "Go modules"),
+    ("Gemfile", "RubyGems"),
+    ("*.gemspec", "RubyGems"),
+    ("pom.xml", "Maven"),
+    ("build.gradle", "Gradle"),
+    ("build.gradle.kts", "Gradle"),
+    ("composer.json", "Packagist"),
+    ("*.csproj", "NuGet"),
+    ("*.fsproj", "NuGet"),
+    ("mix.exs", "Hex"),
+    ("pubspec.yaml", "Pub"),
+    ("Package.swift", "SwiftPM"),
+    ("*.cabal", "Hackage"),
+    ("flake.nix", "Nix"),
+    ("CMakeLists.txt", "CMake"),
+];
+
+pub const DEFAULT_CODETABS_URL: &str = "https://api.codetabs.com/v1/loc";
+
+/// Rough average line length used to turn linguist's byte counts into line counts.
+const BYTES_PER_LINE: u64 = 40;
+
+/// Where per-language line counts for a repo come from.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LocBackend {
+    /// The codetabs.com LOC API. Quick, but frequently rate limited.
+    #[default]
+    Codetabs,
+    /// Shallow-clone each repo and count lines locally with tokei.
+    Local,
+    /// GitHub's own `/languages` endpoint. Reports bytes, which are converted to approximate
+    /// line counts.
+    Linguist,
+}
+
+/// Returns `None` if codetabs didn't give back usable data for the repo.
+pub async fn codetabs(
+    client: &Client,
+    retry: RetryPolicy,
+    base_url: &str,
+    full_name: &str,
+) -> Result<Option<Vec<LOCData>>, Box<dyn Error>> {
+    let request = client.get(format!("{base_url}/?github={full_name}"));
+    let langs_json = retry.send(request).await?.text().await?;
+    Ok(serde_json::from_str(&langs_json).ok())
+}
+
+/// Returns `None` if GitHub has no language data for the repo.
+pub async fn linguist(
+    connection: &GitHub,
+    languages_url: &str,
+) -> Result<Option<Vec<LOCData>>, Box<dyn Error>> {
+    let page = match connection.get_page(languages_url).await {
+        Ok(page) => page,
+        Err(MetricsError::NotFound { .. }) => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let Ok(langs) = serde_json::from_str::<BTreeMap<String, u64>>(&page.body) else {
+        return Ok(None);
+    };
+    if langs.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(
+        langs
+            .into_iter()
+            .map(|(language, bytes)| LOCData {
+                language,
+                lines_of_code: bytes.div_ceil(BYTES_PER_LINE) as u32,
+            })
+            .collect(),
+    ))
+}
+
+/// Lines of code per language, and the ecosystems the repo's manifests belong to.
+///
+/// Returns `None` if the repo couldn't be cloned.
+pub async fn local(
+    clone_url: &str,
+    token: Option<&str>,
+    default_excludes: bool,
+) -> Result<Option<(Vec<LOCData>, Vec<String>)>, Box<dyn Error>> {
+    let Some(dir) = clone(clone_url, token, &["--depth", "1"]).await? else {
+        return Ok(None);
+    };
+    let path = dir.path().to_path_buf();
+    let counts = tokio::task::spawn_blocking(move || {
+        (
+            count_lines(&path, default_excludes),
+            detect_ecosystems(&path, default_excludes),
+        )
+    })
+    .await?;
+    Ok(Some(counts))
+}
+
+/// Non-blank lines per language that `git blame` attributes to one of `commits`. Needs the
+/// full history, so the repo is cloned without `--depth`.
+///
+/// Returns `None` if the repo couldn't be cloned.
+pub async fn blame(
+    clone_url: &str,
+    token: Option<&str>,
+    commits: HashSet<String>,
+    default_excludes: bool,
+) -> Result<Option<BTreeMap<String, u32>>, Box<dyn Error>> {
+    let Some(dir) = clone(clone_url, token, &[]).await? else {
+        return Ok(None);
+    };
+    let lines =
+        tokio::task::spawn_blocking(move || blame_lines(dir.path(), &commits, default_excludes))
+            .await??;
+    Ok(Some(lines))
+}
+
+/// Full messages of the repo's commits in `commits`, from a clone without file contents.
+///
+/// Returns `None` if the repo couldn't be cloned.
+pub async fn commit_messages(
+    clone_url: &str,
+    token: Option<&str>,
+    commits: HashSet<String>,
+) -> Result<Option<Vec<String>>, Box<dyn Error>> {
+    let options = ["--filter=blob:none", "--no-checkout"];
+    let Some(dir) = clone(clone_url, token, &options).await? else {
+        return Ok(None);
+    };
+    let output = tokio::process::Command::new("git")
+        .current_dir(dir.path())
+        .args(["log", "--no-merges", "--format=%H%x1f%B%x1e"])
+        .stderr(Stdio::null())
+        .output()
+        .await?;
+    let log = String::from_utf8_lossy(&output.stdout);
+    Ok(Some(
+        log.split('\x1e')
+            .filter_map(|entry| entry.trim_start().split_once('\x1f'))
+            .filter(|(sha, _)| commits.contains(*sha))
+            .map(|(_, message)| message.trim().to_string())
+            .collect(),
+    ))
+}
+
+/// Clones into a temporary directory, authenticating with `token` if given. `None` if the clone
+/// failed.
+async fn clone(
+    clone_url: &str,
+    token: Option<&str>,
+    options: &[&str],
+) -> Result<Option<TempDir>, Box<dyn Error>> {
+    let dir = tempfile::tempdir()?;
+    let status = tokio::process::Command::new("git")
+        .envs(token.map_or_else(Vec::new, |token| git_auth_env(clone_url, token)))
+        // Fail rather than ask for credentials when a repo turns out to be private
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .args(["clone", "--quiet"])
+        .args(options)
+        .arg(clone_url)
+        .arg(dir.path())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await?;
+    Ok(status.success().then_some(dir))
+}
+
+fn blame_lines(
+    path: &Path,
+    commits: &HashSet<String>,
+    default_excludes: bool,
+) -> io::Result<BTreeMap<String, u32>> {
+    let files = Command::new("git")
+        .current_dir(path)
+        .args(["ls-files", "-z"])
+        .output()?
+        .stdout;
+
+    let config = Config::default();
+    let excluded = excluded_paths(path, default_excludes);
+    let mut lines = BTreeMap::new();
+    for file in String::from_utf8_lossy(&files).split('\0') {
+        if file.is_empty() || excluded.is_match(file) {
+            continue;
+        }
+        let Some(language) = LanguageType::from_path(path.join(file), &config) else {
+            continue;
+        };
+        let output = Command::new("git")
+            .current_dir(path)
+            .args(["blame", "--line-porcelain", "HEAD", "--", file])
+            .stderr(Stdio::null())
+            .output()?;
+        // Binary files and submodules can't be blamed
+        if !output.status.success() {
+            continue;
+        }
+
+        let porcelain = String::from_utf8_lossy(&output.stdout);
+        let mut authored = 0;
+        let mut commit = "";
+        for line in porcelain.lines() {
+            // Each source line is a header starting with its commit, some `key value` lines,
+            // then the content indented with a tab
+            if let Some(content) = line.strip_prefix('\t') {
+                if !content.trim().is_empty() && commits.contains(commit) {
+                    authored += 1;
+                }
+            } else if is_header(line) {
+                commit = line.split(' ').next().unwrap_or_default();
+            }
+        }
+        if authored > 0 {
+            *lines.entry(language.name().to_string()).or_default() += authored;
+        }
+    }
+    Ok(lines)
+}
+
+/// Ecosystems of the manifests tracked anywhere in the checkout at `path`, in
+/// [`ECOSYSTEM_MANIFESTS`] order. Excluded paths are skipped, so vendored dependencies don't
+/// count.
+pub fn detect_ecosystems(path: &Path, default_excludes: bool) -> Vec<String> {
+    let Ok(output) = Command::new("git")
+        .current_dir(path)
+        .args(["ls-files", "-z"])
+        .output()
+    else {
+        return Vec::new();
+    };
+    let excluded = excluded_paths(path, default_excludes);
+    let files = String::from_utf8_lossy(&output.stdout);
+    let names: HashSet<&str> = files
+        .split('\0')
+        .filter(|file| !file.is_empty() && !excluded.is_match(file))
+        .map(|file| file.rsplit('/').next().unwrap_or(file))
+        .collect();
+
+    let mut ecosystems: Vec<String> = Vec::new();
+    for (manifest, ecosystem) in ECOSYSTEM_MANIFESTS {
+        let found = match manifest.strip_prefix('*') {
+            Some(extension) => names.iter().any(|name| name.ends_with(extension)),
+            None => names.contains(manifest),
+        };
+        if found && !ecosystems.iter().any(|known| known == ecosystem) {
+            ecosystems.push(ecosystem.to_string());
+        }
+    }
+    ecosystems
+}
+
+fn is_header(line: &str) -> bool {
+    line.split(' ')
+        .next()
+        .is_some_and(|sha| sha.len() == 40 && sha.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// Lines of code per language in the checkout at `path`, leaving out files `.gitattributes`
+/// marks as vendored, generated or documentation, and [`DEFAULT_EXCLUDES`] if
+/// `default_excludes` is set.
+pub fn count_lines(path: &Path, default_excludes: bool) -> Vec<LOCData> {
+    let excluded = excluded_paths(path, default_excludes);
+    let mut languages = Languages::new();
+    languages.get_statistics(&[path], &[".git"], &Config::default());
+    languages
+        .iter()
+        .filter_map(|(language_type, language)| {
+            let code: usize = language
+                .reports
+                .iter()
+                .filter(|report| !is_excluded(&excluded, path, &report.name))
+                .map(|report| report.stats.code)
+                .sum();
+            (code > 0).then(|| LOCData {
+                language: language_type.name().to_string(),
+                lines_of_code: code as u32,
+            })
+        })
+        .collect()
+}
+
+/// Paths the repo's top-level `.gitattributes` marks with any of
+/// [`LINGUIST_EXCLUDING_ATTRIBUTES`], e.g. `vendor/** linguist-vendored`, plus
+/// [`DEFAULT_EXCLUDES`] if `default_excludes` is set.
+fn excluded_paths(root: &Path, default_excludes: bool) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    if default_excludes {
+        for pattern in DEFAULT_EXCLUDES {
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .expect("default excludes are valid globs");
+            builder.add(glob);
+        }
+    }
+    let attributes = std::fs::read_to_string(root.join(".gitattributes")).unwrap_or_default();
+    for line in attributes.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(pattern) = fields.next().filter(|pattern| !pattern.starts_with('#')) else {
+            continue;
+        };
+        // `-attr` and `attr=false` unset the attribute
+        let excluding = fields.any(|attribute| {
+            let (name, value) = attribute.split_once('=').unwrap_or((attribute, "true"));
+            LINGUIST_EXCLUDING_ATTRIBUTES.contains(&name) && value != "false"
+        });
+        if !excluding {
+            continue;
+        }
+        // Like in `.gitignore`, patterns without a slash match at any depth
+        let glob = match pattern.strip_prefix('/') {
+            Some(anchored) => anchored.to_string(),
+            None if !pattern.contains('/') => format!("**/{pattern}"),
+            None => pattern.to_string(),
+        };
+        match GlobBuilder::new(&glob).literal_separator(true).build() {
+            Ok(glob) => {
+                debug!("Excluding {pattern} marked in .gitattributes");
+                builder.add(glob);
+            }
+            Err(err) => debug!("Ignoring .gitattributes pattern {pattern}: {err}"),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+fn is_excluded(excluded: &GlobSet, root: &Path, path: &Path) -> bool {
+    path.strip_prefix(root)
+        .is_ok_and(|relative| excluded.is_match(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gitattributes_and_default_excludes_are_left_out() {
+        let root = TempDir::new().unwrap();
+        std::fs::write(
+            root.path().join(".gitattributes"),
+            "# vendored code\n\
+             vendor/** linguist-vendored\n\
+             *.gen.rs linguist-generated=true\n\
+             /docs/** linguist-documentation\n\
+             src/kept.rs -linguist-generated\n\
+             src/also_kept.rs linguist-vendored=false\n",
+        )
+        .unwrap();
+        let root = root.path();
+        let excluded = excluded_paths(root, true);
+        let is_excluded = |path: &str| is_excluded(&excluded, root, &root.join(path));
+        assert!(is_excluded("vendor/lib/mod.rs"));
+        assert!(is_excluded("src/deep/schema.gen.rs"));
+        assert!(is_excluded("docs/index.md"));
+        assert!(is_excluded("Cargo.lock"));
+        assert!(is_excluded("web/node_modules/left-pad/index.js"));
+        assert!(!is_excluded("src/main.rs"));
+        assert!(!is_excluded("src/kept.rs"));
+        assert!(!is_excluded("src/also_kept.rs"));
+        // Anchored patterns only match from the root
+        assert!(!is_excluded("src/docs/index.md"));
+    }
+
+    #[test]
+    fn default_excludes_can_be_turned_off() {
+        let root = TempDir::new().unwrap();
+        std::fs::write(
+            root.path().join(".gitattributes"),
+            "vendor/** linguist-vendored\n",
+        )
+        .unwrap();
+        let root = root.path();
+        let excluded = excluded_paths(root, false);
+        assert!(!is_excluded(&excluded, root, &root.join("Cargo.lock")));
+        assert!(is_excluded(
+            &excluded,
+            root,
+            &root.join("vendor/lib/mod.rs")
+        ));
+    }
+}
@@ -1,256 +1,1871 @@
-use std::{collections::BTreeMap, error::Error};
+use std::{
+    collections::BTreeMap, error::Error, ffi::OsString, net::SocketAddr, path::PathBuf,
+    process::ExitCode, time::Duration,
+};
 
-use clap::Parser;
-use reqwest::{Client, IntoUrl, Response};
-use serde::Deserialize;
+use clap::{
+    builder::PossibleValuesParser, error::ErrorKind, parser::ValueSource, ArgMatches, Args as _,
+    CommandFactory, FromArgMatches,
+};
+use github_user_scraper::{
+    actions,
+    activity::RECENT_DAYS,
+    anonymize::{anonymize_report, pseudonym},
+    badge,
+    cache::Cache,
+    card::{self, CardLayout},
+    chart::render_bar_chart,
+    colors,
+    compare::{compare_reports, render_comparison},
+    config, csv,
+    diff::diff_reports,
+    filter::{glob_set, OrgFilter, RepoFilter, Visibility},
+    format::{self, NumberFormat, SortBy},
+    gists,
+    gitea::{self, Gitea},
+    github::{
+        collect_contributed_repos, collect_repos, enterprise_host, process_repos, DEFAULT_BASE_URL,
+    },
+    graphql,
+    heatmap::{parse_utc_offset, render_heatmap, WEEKDAYS},
+    history::History,
+    html,
+    http::ClientOptions,
+    loc::LocBackend,
+    markdown,
+    metrics::{
+        self, check_metrics, collect_metrics, enabled_collectors, supported_collectors,
+        MetricContext,
+    },
+    org::{self, rank_members, MemberRanking},
+    plan::{plan_repos, render_plan, requests_per_repo, Plan},
+    profile::ProfileSummary,
+    progress, prometheus,
+    rate_limit::{rate_limits, render_rate_limits},
+    readme,
+    report::{
+        group_languages, merge_accounts, summarize_languages, summarize_stars, LanguageRanking,
+        WeightMode,
+    },
+    retry::RetryPolicy,
+    server,
+    sponsors::dollars,
+    stars,
+    template::render_template,
+    theme::Theme,
+    token, tui,
+    wakatime::{self, WakaTimeRange},
+    GitHub, RepoInfo, Report,
+};
+use reqwest::Client;
+use serde_json::json;
+use tracing::{debug, info, warn, Level};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 #[derive(clap::Parser, Clone)]
 struct Args {
-    #[arg(long, short)]
-    user: String,
-    #[arg(long, short)]
+    /// TOML file to read default options from. Defaults to `lets-git-the-metrics.toml` in the
+    /// working directory or the user config directory
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// User to analyze. Pass more than once (or a comma separated list) to combine accounts
+    #[arg(long, short, value_delimiter = ',', global = true)]
+    user: Vec<String>,
+    /// Analyze every repo in an organization instead of a user's repos
+    #[arg(long, conflicts_with = "user", global = true)]
+    org: Option<String>,
+    /// What the `--org` member leaderboard is ranked by
+    #[arg(long, value_enum, default_value_t = MemberRanking::Contributions, global = true)]
+    rank_members_by: MemberRanking,
+    /// What the language percentages are shares of. `stars` answers which languages are behind
+    /// the most popular repos
+    #[arg(long, value_enum, default_value_t = LanguageRanking::Loc, global = true)]
+    rank_by: LanguageRanking,
+    /// Defaults to `GITHUB_TOKEN`, `GH_TOKEN`, the token saved with `--save-token` or
+    /// `gh auth token` for GitHub
+    #[arg(long, short, global = true)]
     token: Option<String>,
-    #[arg(long, short)]
+    /// Save `--token` in the OS keyring so later runs don't need it
+    #[arg(long, requires = "token", global = true)]
+    save_token: bool,
+    #[arg(long, short, global = true)]
     weighted: bool,
+    /// How `--weighted` attributes code to the user
+    #[arg(long, value_enum, default_value_t = WeightMode::Commits, global = true)]
+    weight_mode: WeightMode,
+    /// Also include repos owned by others that the user committed to in about the last year.
+    /// These would otherwise count in full, so this needs `--weighted`. Needs a token
+    #[arg(long, requires = "weighted", global = true)]
+    include_contributed: bool,
+    /// Also count the code in the user's public gists, each as a repo of its own
+    #[arg(long, global = true)]
+    include_gists: bool,
+    /// WakaTime API key. Adds the editor time spent per language next to the code
+    #[arg(long, global = true)]
+    wakatime_key: Option<String>,
+    /// Period of WakaTime data to use
+    #[arg(long, value_enum, default_value_t = WakaTimeRange::LastYear, global = true)]
+    wakatime_range: WakaTimeRange,
 
-    #[arg(long, short, default_value = "")]
+    #[arg(long, short, default_value = "", global = true)]
     excluded_langs: Vec<String>,
+    /// Count several languages as one, e.g. `Web=CSS,SCSS,HTML`. Can be given more than once, or
+    /// as a `[language_groups]` section in the config file
+    #[arg(long, value_name = "GROUP=LANGUAGES", value_parser = parse_language_group, global = true)]
+    language_groups: Vec<(String, Vec<String>)>,
+    /// Container images built from a repo, e.g. `user/app=ghcr.io/user/app-server`, for
+    /// `--metrics containers`. Repos with a `Dockerfile` and no mapping are assumed to publish
+    /// an image named after them. Can be given more than once, or as a `[container_images]`
+    /// section in the config file
+    #[arg(long, value_name = "REPO=IMAGES", value_parser = parse_container_images, global = true)]
+    container_images: Vec<(String, Vec<String>)>,
+
+    #[arg(long, short, value_enum, default_value_t = OutputFormat::Text, global = true)]
+    format: OutputFormat,
+
+    /// Analyze forked repos too. Their upstream code would otherwise count as the user's
+    #[arg(long, global = true)]
+    include_forks: bool,
+    /// Analyze archived repos too
+    #[arg(long, global = true)]
+    include_archived: bool,
+    /// Which repos to count. Private repos are only visible with a token for the same user
+    #[arg(long, value_enum, default_value_t = Visibility::All, global = true)]
+    visibility: Visibility,
+    /// Skip repos whose `owner/name` matches this glob, e.g. `user/legacy-*`
+    #[arg(long, global = true)]
+    exclude_repo: Vec<String>,
+    /// Only analyze repos whose `owner/name` matches this glob
+    #[arg(long, global = true)]
+    only_repo: Vec<String>,
+    /// Skip the repos of this organization without listing them, e.g. your employer's
+    #[arg(long, global = true)]
+    exclude_org: Vec<String>,
+    /// Only analyze the repos of this organization, besides the user's own
+    #[arg(long, global = true)]
+    only_org: Vec<String>,
+    /// Only analyze repos tagged with this topic
+    #[arg(long, global = true)]
+    include_topic: Vec<String>,
+    /// Skip repos tagged with this topic, e.g. `coursework`
+    #[arg(long, global = true)]
+    exclude_topic: Vec<String>,
+    /// Leave out repos where the user made less than this fraction of the commits, e.g. `0.05`
+    #[arg(long, default_value_t = 0.0, global = true)]
+    min_contribution: f32,
+    /// Leave out repos with fewer stars than this
+    #[arg(long, default_value_t = 0, global = true)]
+    min_stars: u32,
+    /// Leave out repos with fewer lines of code than this, e.g. dotfiles
+    #[arg(long, default_value_t = 0, global = true)]
+    min_loc: u32,
+
+    /// Which forge the user's repos are hosted on
+    #[arg(long, value_enum, default_value_t = Provider::Github, global = true)]
+    provider: Provider,
+    /// Root of the GitHub REST API, e.g. `https://github.mycorp.com/api/v3` for GitHub Enterprise
+    /// Server
+    #[arg(long, default_value = DEFAULT_BASE_URL, global = true)]
+    api_url: String,
+    /// Host of the Gitea/Forgejo instance
+    #[arg(long, default_value = "codeberg.org", global = true)]
+    host: String,
+
+    /// Which GitHub API to scrape with. GraphQL requires a token
+    #[arg(long, value_enum, default_value_t = Api::Rest, global = true)]
+    api: Api,
+
+    /// Where per-language line counts come from
+    #[arg(long, value_enum, default_value_t = LocBackend::Codetabs, global = true)]
+    loc_backend: LocBackend,
+    /// Backend to retry with when the primary one has no data for a repo
+    #[arg(long, value_enum, global = true)]
+    loc_fallback: Option<LocBackend>,
+    /// Count lockfiles, minified files, generated protobuf code, `target/` and `node_modules/`
+    /// with the local backend, which are left out by default
+    #[arg(long, global = true)]
+    no_default_excludes: bool,
+
+    /// How many repos to process at once
+    #[arg(long, default_value_t = 8, global = true)]
+    concurrency: usize,
+
+    /// Give up on a request (and retry it) if it hasn't finished after this long, e.g. `60s`
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "60s", global = true)]
+    request_timeout: Duration,
+    /// Give up on connecting to a host after this long
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "10s", global = true)]
+    connect_timeout: Duration,
+
+    /// Send every request through this proxy, e.g. `http://proxy.corp:3128`. Without it,
+    /// `HTTPS_PROXY`, `HTTP_PROXY` and `NO_PROXY` are honored
+    #[arg(long, global = true)]
+    proxy: Option<String>,
+    /// PEM file of extra certificates to trust, e.g. a TLS-intercepting proxy's
+    #[arg(long, global = true)]
+    ca_bundle: Option<PathBuf>,
+
+    /// How many times to retry requests that fail with a 5xx response or a network error
+    #[arg(long, default_value_t = 3, global = true)]
+    max_retries: u32,
+    /// Delay before the first retry, doubled for every retry after it, e.g. `500ms` or `2s`
+    #[arg(long, value_parser = humantime::parse_duration, default_value = "500ms", global = true)]
+    retry_base_delay: Duration,
+
+    /// Extra metrics to collect alongside the language stats
+    #[arg(long, value_delimiter = ',', value_parser = PossibleValuesParser::new(metrics::possible_values()), global = true)]
+    metrics: Vec<String>,
+    /// Time zone of `--metrics heatmap`, as an offset from UTC like `+02:00`
+    #[arg(long, default_value = "Z", value_parser = parse_utc_offset, allow_hyphen_values = true, global = true)]
+    utc_offset: i32,
+
+    /// Don't read or write the on-disk response cache
+    #[arg(long, global = true)]
+    no_cache: bool,
+    /// Don't touch the network: reuse the latest run in `--history`, or else build the report
+    /// from cached responses
+    #[arg(long, conflicts_with = "no_cache", global = true)]
+    offline: bool,
+
+    /// Record every run in this SQLite database
+    #[arg(long, global = true)]
+    history: Option<PathBuf>,
+
+    /// Decimal places of percentages
+    #[arg(long, default_value_t = 2, global = true)]
+    precision: usize,
+    /// Order languages are listed in
+    #[arg(long, value_enum, default_value_t = SortBy::Percent, global = true)]
+    sort_by: SortBy,
+    /// Digit group separator for line counts. Defaults to the locale's
+    #[arg(long, global = true)]
+    thousands_separator: Option<char>,
+
+    /// Replace repo and organization names with stable hashes in every output, so the stats can
+    /// be shared without revealing private repos
+    #[arg(long, global = true)]
+    anonymize: bool,
+
+    /// Log more details to stderr. Pass twice for everything
+    #[arg(long, short, action = clap::ArgAction::Count, conflicts_with = "quiet", global = true)]
+    verbose: u8,
+    /// Only log warnings and errors
+    #[arg(long, short, global = true)]
+    quiet: bool,
+    #[arg(long, value_enum, default_value_t = LogFormat::Text, global = true)]
+    log_format: LogFormat,
+    /// The client every connection shares, built from the network options by [`run`]
+    #[arg(skip)]
+    http_client: Client,
+
+    /// Options of the report, when run without a subcommand
+    #[command(flatten)]
+    report: ReportArgs,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
 
-struct GitHub {
-    client: Client,
-    user: String,
-    auth_code: Option<String>,
-    weighted: bool,
-    excluded_langs: Vec<String>,
+/// Options of the full report, which is also what runs without a subcommand.
+#[derive(clap::Args, Clone)]
+struct ReportArgs {
+    /// Only list the repos, then print which would be analyzed, why the others are skipped,
+    /// and roughly how many API requests analyzing them would take
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Keep running and export the report as Prometheus metrics on this address
+    #[arg(long)]
+    prometheus_listen: Option<SocketAddr>,
+    /// How often to recollect the exported metrics, e.g. `30m` or `6h`
+    #[arg(long, value_parser = parse_interval, default_value = "1h")]
+    prometheus_interval: Duration,
+
+    /// Write outputs like `top_language` to `$GITHUB_OUTPUT`, and the Markdown report to
+    /// `$GITHUB_STEP_SUMMARY`, for use in GitHub Actions workflows
+    #[arg(long)]
+    github_action: bool,
+
+    /// Keep running, collecting the report again every `--interval` and rewriting the outputs
+    #[arg(long)]
+    daemon: bool,
+    /// How often `--daemon` recollects, e.g. `30m` or `6h`. Also replaces
+    /// `--prometheus-interval` when both are given
+    #[arg(long, value_parser = parse_interval, default_value = "6h")]
+    interval: Duration,
+
+    /// Also render the report as an SVG stats card at this path
+    #[arg(long)]
+    output_svg: Option<PathBuf>,
+    /// Also render the card as a PNG at this path, for places that don't show SVGs
+    #[arg(long)]
+    output_png: Option<PathBuf>,
+
+    /// Directory `--format csv` writes its files to
+    #[arg(long, value_name = "DIR", default_value = ".")]
+    csv_out: PathBuf,
+
+    /// Also list the N repos with the most stars, and with the most stars weighted by
+    /// contribution. Markdown output always lists the top 10 unless this is given
+    #[arg(long, value_name = "N")]
+    top_repos: Option<usize>,
+
+    /// Also list every repo with its language mix, stars and the user's share of commits
+    #[arg(long)]
+    per_repo: bool,
+
+    /// Render the report with this Tera template instead of `--format`, with the fields of the
+    /// JSON output as variables. Also replaces the Markdown in `update-readme`
+    #[arg(long, value_name = "PATH")]
+    template: Option<PathBuf>,
+
+    /// Width in characters of a 100% bar in the text output's language chart
+    #[arg(long, default_value_t = 40)]
+    chart_width: usize,
+
+    #[command(flatten)]
+    card: CardArgs,
 }
-impl GitHub {
-    pub fn from_args(args: Args) -> Self {
-        Self {
-            client: Client::new(),
-            user: args.user,
-            auth_code: args.token,
-            weighted: args.weighted,
-            excluded_langs: args
-                .excluded_langs
-                .into_iter()
-                .map(|s| s.to_ascii_lowercase())
-                .collect(),
+
+/// How the SVG stats card looks.
+#[derive(clap::Args, Clone)]
+struct CardArgs {
+    /// How many pixels per SVG unit the PNG card gets
+    #[arg(long, default_value_t = 2.0)]
+    png_scale: f32,
+    #[arg(long, default_value = "Most Used Languages")]
+    card_title: String,
+    /// Colors of the card: one of default, light, dark, radical, dracula, gruvbox, tokyonight,
+    /// onedark and nord, or a TOML file with `background`, `title`, `text` and optionally
+    /// `border` and `bars` colors. `auto` is short for `--theme light --dark-theme dark`
+    #[arg(long, default_value = "default")]
+    theme: String,
+    /// How the card lays out the languages
+    #[arg(long, value_enum, default_value_t = CardLayout::Normal)]
+    layout: CardLayout,
+    /// Animate the SVG card: fill the bars, count up the stars and fade in the legend
+    #[arg(long)]
+    animate: bool,
+    /// Theme the SVG card switches to for viewers that prefer a dark color scheme, like GitHub
+    /// in dark mode
+    #[arg(long)]
+    dark_theme: Option<String>,
+    /// Replaces the theme's background color
+    #[arg(long)]
+    card_background: Option<String>,
+    /// Replaces the theme's title color
+    #[arg(long)]
+    card_title_color: Option<String>,
+    /// Replaces the theme's text color
+    #[arg(long)]
+    card_text_color: Option<String>,
+}
+
+impl CardArgs {
+    fn options(&self) -> Result<card::CardOptions, Box<dyn Error>> {
+        let (theme, dark_theme) = match self.theme.as_str() {
+            "auto" => ("light", Some(self.dark_theme.as_deref().unwrap_or("dark"))),
+            theme => (theme, self.dark_theme.as_deref()),
+        };
+        let mut theme = Theme::load(theme)?;
+        let overrides = [
+            (&mut theme.background, &self.card_background),
+            (&mut theme.title, &self.card_title_color),
+            (&mut theme.text, &self.card_text_color),
+        ];
+        for (color, replacement) in overrides {
+            if let Some(replacement) = replacement {
+                color.clone_from(replacement);
+            }
         }
+        Ok(card::CardOptions {
+            title: self.card_title.clone(),
+            theme,
+            dark_theme: dark_theme.map(Theme::load).transpose()?,
+            animate: self.animate,
+            layout: self.layout,
+        })
+    }
+}
+
+/// A duration like `6h` that timers can tick at, so not zero.
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let interval = humantime::parse_duration(value).map_err(|err| err.to_string())?;
+    if interval.is_zero() {
+        return Err("the interval must be longer than zero".to_string());
+    }
+    Ok(interval)
+}
+
+fn parse_language_group(value: &str) -> Result<(String, Vec<String>), String> {
+    let (group, languages) = value
+        .split_once('=')
+        .ok_or("expected GROUP=LANGUAGE,LANGUAGE")?;
+    let languages = languages
+        .split(',')
+        .map(|lang| lang.trim().to_string())
+        .filter(|lang| !lang.is_empty())
+        .collect();
+    Ok((group.trim().to_string(), languages))
+}
+
+fn parse_container_images(value: &str) -> Result<(String, Vec<String>), String> {
+    let (repo, images) = value
+        .split_once('=')
+        .ok_or("expected OWNER/REPO=IMAGE,IMAGE")?;
+    let images = images
+        .split(',')
+        .map(|image| image.trim().to_string())
+        .filter(|image| !image.is_empty())
+        .collect();
+    Ok((repo.trim().to_string(), images))
+}
+
+/// The command line parser, with the options from the config file as defaults.
+fn command_with_config(
+    options: &BTreeMap<String, Vec<String>>,
+) -> Result<clap::Command, Box<dyn Error>> {
+    let has_arg =
+        |command: &clap::Command, id: &str| command.get_arguments().any(|arg| arg.get_id() == id);
+    let mut command = Args::command();
+    for (id, values) in options {
+        let subcommands: Vec<String> = command
+            .get_subcommands()
+            .filter(|subcommand| has_arg(subcommand, id))
+            .map(|subcommand| subcommand.get_name().to_string())
+            .collect();
+        if id == "config" || (!has_arg(&command, id) && subcommands.is_empty()) {
+            return Err(format!("Unknown option `{id}` in the config file").into());
+        }
+        if has_arg(&command, id) {
+            command = command.mut_arg(id, |arg| arg.default_values(values));
+        }
+        for name in subcommands {
+            command = command.mut_subcommand(name, |subcommand| {
+                subcommand.mut_arg(id, |arg| arg.default_values(values))
+            });
+        }
+    }
+    Ok(command)
+}
+
+impl Args {
+    /// Moves the report options given to `report` or `update-readme` to `self.report`, and
+    /// rejects those given before a subcommand, which would ignore them.
+    fn take_report_args(&mut self, matches: &ArgMatches) {
+        let Some((name, _)) = matches.subcommand() else {
+            return;
+        };
+        let report_args = ReportArgs::augment_args(clap::Command::new("report"));
+        if let Some(arg) = report_args.get_arguments().find(|arg| {
+            matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+        }) {
+            Args::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!(
+                        "--{} doesn't apply to `{name}`, only to the report",
+                        arg.get_long().unwrap_or_default()
+                    ),
+                )
+                .exit();
+        }
+        match self.command.as_ref() {
+            Some(Command::Report(report) | Command::UpdateReadme { report, .. }) => {
+                self.report = report.clone();
+            }
+            Some(Command::Card { card, .. }) => self.report.card = card.clone(),
+            _ => {}
+        }
+    }
+
+    fn number_format(&self) -> NumberFormat {
+        NumberFormat {
+            precision: self.precision,
+            thousands_separator: self
+                .thousands_separator
+                .or_else(format::locale_thousands_separator),
+            sort_by: self.sort_by,
+        }
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.max_retries,
+            base_delay: self.retry_base_delay,
+        }
+    }
+
+    fn org_filter(&self) -> OrgFilter {
+        OrgFilter {
+            only: self.only_org.clone(),
+            exclude: self.exclude_org.clone(),
+        }
+    }
+
+    fn client_options(&self) -> ClientOptions {
+        ClientOptions {
+            request_timeout: self.request_timeout,
+            connect_timeout: self.connect_timeout,
+            max_idle_per_host: self.concurrency,
+            proxy: self.proxy.clone(),
+            ca_bundle: self.ca_bundle.clone(),
+        }
+    }
+}
+
+#[derive(clap::Subcommand, Clone)]
+enum Command {
+    /// Regenerate the stats between `<!-- metrics:start -->` and `<!-- metrics:end -->` in a
+    /// README instead of printing them
+    UpdateReadme {
+        #[arg(default_value = "README.md")]
+        path: PathBuf,
+        /// Commit the README if it changed
+        #[arg(long)]
+        commit: bool,
+        /// Push the commit, authenticating with the token on https remotes
+        #[arg(long, requires = "commit")]
+        push: bool,
+        #[command(flatten)]
+        report: ReportArgs,
+    },
+    /// Compare two reports: either two files written with `--format json`, or the two latest
+    /// runs in the `--history` database
+    Diff {
+        #[arg(requires = "new")]
+        old: Option<PathBuf>,
+        new: Option<PathBuf>,
+    },
+    /// Browse the report interactively, with live logs while repos are collected
+    Tui,
+    /// Collect reports for two users and show their languages, stars and contributions side by
+    /// side, highlighting the largest differences
+    Compare { first: String, second: String },
+    /// Show the token's remaining core, GraphQL and search quotas and when they reset. With
+    /// `--user` or `--org`, also estimate whether a full run fits in what's left
+    RateLimit,
+    /// The full report, like running without a subcommand
+    Report(ReportArgs),
+    /// Only the language breakdown
+    Langs {
+        /// Width in characters of a 100% bar
+        #[arg(long, default_value_t = 40)]
+        chart_width: usize,
+    },
+    /// Total stars, forks and watchers, and the most starred repos
+    Stars {
+        /// How many repos to list
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+    /// Every analyzed repo with its language mix, stars and the user's share of commits
+    Repos,
+    /// Render only the stats card, as a PNG if `output` ends in `.png` and as an SVG otherwise
+    Card {
+        #[arg(default_value = "card.svg")]
+        output: PathBuf,
+        #[command(flatten)]
+        card: CardArgs,
+    },
+    /// Write a small SVG badge like "Rust 43.2%" for each of the most used languages, named
+    /// after the language, e.g. `rust.svg`
+    Badges {
+        #[arg(default_value = "badges")]
+        out_dir: PathBuf,
+        /// How many languages get a badge
+        #[arg(long, default_value_t = 5)]
+        top: usize,
+    },
+    /// Show how many stars the most starred repos gained each month
+    StarHistory {
+        /// How many repos to show, most starred first
+        #[arg(long, default_value_t = 5)]
+        repos: usize,
+        #[arg(long, default_value_t = 12)]
+        months: i32,
+        #[arg(long, value_enum, default_value_t = StarHistoryStyle::Table)]
+        style: StarHistoryStyle,
+    },
+    /// Serve reports as JSON at `/stats/{user}`. With `--webhook-secret`, `POST /refresh`
+    /// recollects them right away, e.g. from a push webhook
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: SocketAddr,
+        /// Users whose stats are served, pass more than once (or a comma separated list) for
+        /// more. Defaults to the `--user`s
+        #[arg(long, value_delimiter = ',')]
+        allow_user: Vec<String>,
+        /// Also serve the private repos the token can see, which are left out by default
+        #[arg(long)]
+        include_private: bool,
+        /// How often to recollect the reports that have been requested, e.g. `30m` or `6h`
+        #[arg(long, value_parser = parse_interval, default_value = "1h")]
+        refresh_interval: Duration,
+        /// Enable `POST /refresh`, accepting only requests signed with this GitHub webhook secret
+        #[arg(long)]
+        webhook_secret: Option<String>,
+    },
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+    Markdown,
+    /// A standalone page with charts and every repo, for sharing or archiving
+    Html,
+    /// `repos.csv` and `languages.csv` in `--csv-out`, for spreadsheets
+    Csv,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum StarHistoryStyle {
+    /// New stars per month in columns
+    Table,
+    /// A sparkline of new stars per month for each repo
+    Sparkline,
+    /// The histories as JSON
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Provider {
+    Github,
+    Gitea,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Api {
+    Rest,
+    Graphql,
+}
+
+/// Sends log events to stderr so stdout only ever has the report on it.
+fn init_logging(args: &Args, logs: Option<tui::LogBuffer>) {
+    let level = match (args.quiet, args.verbose) {
+        (true, _) => Level::WARN,
+        (false, 0) => Level::INFO,
+        (false, 1) => Level::DEBUG,
+        (false, _) => Level::TRACE,
+    };
+    // The dashboard shows logs itself rather than having them written over it
+    let writer = match logs.clone() {
+        Some(logs) => BoxMakeWriter::new(logs),
+        None => BoxMakeWriter::new(std::io::stderr),
+    };
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(logs.is_none())
+        .with_max_level(level);
+    match args.log_format {
+        LogFormat::Text => subscriber.without_time().with_target(false).init(),
+        LogFormat::Json => subscriber.json().init(),
     }
+}
 
-    pub async fn user_data(&self) -> Result<UserData, Box<dyn Error>> {
-        let json = self
-            .get(format!("https://api.github.com/users/{}", self.user))
-            .await?
-            .text()
-            .await?;
-        let data: UserData = serde_json::from_str(&json).unwrap();
-        Ok(data)
+fn print_languages(report: &Report, chart_width: usize, format: &NumberFormat) {
+    match report.ranked_by {
+        LanguageRanking::Loc => println!("Most used languages:"),
+        LanguageRanking::Stars => println!("Languages behind the most stars:"),
     }
+    print!(
+        "{}",
+        render_bar_chart(report, chart_width, colors::stdout_supports_color(), format)
+    );
+    match report.unweighted_total_loc {
+        Some(unweighted) => println!(
+            "Total lines of code (weighted): {} of {}",
+            format.count(report.total_loc),
+            format.count(unweighted)
+        ),
+        None => println!("Total lines of code: {}", format.count(report.total_loc)),
+    }
+}
 
-    pub async fn get(&self, url: impl IntoUrl) -> reqwest::Result<Response> {
-        let mut builder = self
-            .client
-            .get(format!("{}?per_page=1000", url.as_str()))
-            .header("User-Agent", "GitHub user stats scraper (reqwest/hyper)");
-        if let Some(auth) = &self.auth_code {
-            builder = builder.header("Authorization", format!("Bearer {auth}"));
+fn print_top_repos(report: &Report, n: usize) {
+    for (title, weighted) in [("by stars", false), ("by weighted stars", true)] {
+        println!("Top repos {title}:");
+        println!(
+            "{:>3} {:<40} {:>7} {:>9} {:<16} {:>13} {:<10}",
+            "#", "Repository", "Stars", "Weighted", "Language", "Contribution", "Last push"
+        );
+        for (rank, repo) in report.top_repos(n, weighted).into_iter().enumerate() {
+            println!(
+                "{:>3} {:<40} {:>7} {:>9.1} {:<16} {:>12.2}% {:<10}",
+                rank + 1,
+                repo.full_name,
+                repo.stars,
+                repo.weighted_stars(),
+                repo.primary_language().unwrap_or("-"),
+                repo.ratio_of_commits_from_user * 100.0,
+                markdown::push_date(repo)
+            );
         }
-        builder.send().await
     }
 }
 
-async fn collect_repos(connection: &GitHub) -> Result<Vec<RepoData>, Box<dyn Error>> {
-    let user_data = connection.user_data().await?;
+fn print_repos(report: &Report) {
+    for repo in &report.repos {
+        println!(
+            "{:<40} {:>7} stars {:>7.2}% of commits",
+            repo.full_name,
+            repo.stars,
+            repo.ratio_of_commits_from_user * 100.0
+        );
+        println!("  {}", markdown::language_mix(repo));
+        if let Some((downloads, recent)) = report
+            .packages
+            .as_ref()
+            .and_then(|packages| packages.repo_downloads(&repo.full_name))
+        {
+            println!("  {downloads} package downloads, {recent} in the last 90 days");
+        }
+    }
+}
 
+fn print_totals(report: &Report) {
     println!(
-        "Successfully found user. scraping repos at `{}` and organizations at `{}`...",
-        user_data.repos_url, user_data.organizations_url
+        "Total stars (weighted depending on args): {}",
+        report.total_stars
     );
+    println!("Total forks: {}", report.total_forks);
+    println!("Total watchers: {}", report.total_watchers);
+}
 
-    let repos_data = connection.get(user_data.repos_url).await?.text().await?;
-    let mut repos: Vec<RepoData> = serde_json::from_str(&repos_data).unwrap();
-    println!("Found all {} user repos!", repos.len());
+fn print_text(
+    report: &Report,
+    top_repos: Option<usize>,
+    per_repo: bool,
+    chart_width: usize,
+    format: &NumberFormat,
+) {
+    for profile in &report.profiles {
+        match &profile.name {
+            Some(name) => println!("{name} ({})", profile.login),
+            None => println!("{}", profile.login),
+        }
+        println!(
+            "  {} followers, {} following, {} public repos",
+            profile.followers, profile.following, profile.public_repos
+        );
+        match profile.account_age_years() {
+            Some(years) => println!("  Joined {} ({years} years ago)", profile.created_date()),
+            None => println!("  Joined {}", profile.created_date()),
+        }
+    }
 
-    let orgs_data = connection
-        .get(user_data.organizations_url)
-        .await?
-        .text()
-        .await?;
-    let orgs_data: Vec<OrgData> = serde_json::from_str(&orgs_data).unwrap();
-    for org in orgs_data {
-        let repos_data = connection.get(org.repos_url).await?.text().await?;
-        let repos_data: Vec<RepoData> = serde_json::from_str(&repos_data).unwrap();
-        println!("Found {} organization repos!", repos_data.len());
-        repos.extend(repos_data)
+    print_languages(report, chart_width, format);
+
+    if let Some(stats) = &report.wakatime {
+        println!("Code and time spent ({}):", stats.range);
+        println!(
+            "{:<20} {:>9} {:>11} {:>8}",
+            "Language", "Code", "Time spent", "Hours"
+        );
+        for row in wakatime::combined_languages(report) {
+            println!(
+                "{:<20} {:>9} {:>11} {:>8.1}",
+                row.language,
+                format.percent(row.code_percent),
+                format.percent(row.time_percent),
+                row.hours
+            );
+        }
+    }
+
+    if let Some(n) = top_repos {
+        print_top_repos(report, n);
+    }
+
+    if per_repo {
+        println!("Repos:");
+        print_repos(report);
+    }
+
+    print_totals(report);
+
+    if !report.members.is_empty() {
+        println!("Member leaderboard:");
+        println!(
+            "{:>3} {:<30} {:>13} {:>6} {:>7} {:<16}",
+            "#", "Member", "Contributions", "Repos", "Stars", "Language"
+        );
+        for (rank, member) in report.members.iter().enumerate() {
+            println!(
+                "{:>3} {:<30} {:>13} {:>6} {:>7} {:<16}",
+                rank + 1,
+                member.login,
+                member.contributions,
+                member.repos,
+                member.stars,
+                member.primary_language.as_deref().unwrap_or("")
+            );
+        }
+    }
+
+    if let Some(pulls) = &report.pull_requests {
+        println!(
+            "Pull requests: {} opened, {} merged, {} closed unmerged ({:.2}% merge rate)",
+            pulls.opened,
+            pulls.merged,
+            pulls.closed,
+            pulls.merge_rate * 100.0
+        );
+    }
+
+    if let Some(issues) = &report.issues {
+        println!("Issues:");
+        println!("{:<14} {:>9} {:>9}", "", "Own repos", "Elsewhere");
+        for (kind, own, external) in [
+            ("Opened", issues.own_repos.opened, issues.external.opened),
+            ("Closed", issues.own_repos.closed, issues.external.closed),
+            (
+                "Commented on",
+                issues.own_repos.commented,
+                issues.external.commented,
+            ),
+        ] {
+            println!("{kind:<14} {own:>9} {external:>9}");
+        }
+    }
+
+    if let Some(ecosystems) = &report.ecosystems {
+        println!("Ecosystems:");
+        println!("{:<20} {:>6} {:>8}", "Ecosystem", "Repos", "Share");
+        for share in &ecosystems.ecosystems {
+            println!(
+                "{:<20} {:>6} {:>7.2}%",
+                share.ecosystem, share.repos, share.repo_percent
+            );
+        }
+        println!("{:<20} {:>6}", "No manifest", ecosystems.other_repos);
+    }
+
+    if let Some(reviews) = &report.reviews {
+        println!(
+            "Pull request reviews in the last year: {} ({} review comments)",
+            reviews.reviews, reviews.review_comments
+        );
+    }
+
+    if let Some(discussions) = &report.discussions {
+        println!(
+            "Discussions: {} started, {} answered",
+            discussions.created, discussions.answered
+        );
+        println!("{:<40} {:>8} {:>8}", "Repository", "Started", "Answered");
+        for repo in &discussions.repos {
+            println!(
+                "{:<40} {:>8} {:>8}",
+                repo.full_name, repo.created, repo.answered
+            );
+        }
+    }
+
+    if let Some(sponsorship) = &report.sponsorship {
+        println!(
+            "Sponsors: {} ({} a month)",
+            sponsorship.sponsors,
+            sponsorship.monthly_dollars()
+        );
+        println!(
+            "{:<30} {:>10} {:>8} {:>12}",
+            "Tier", "Price", "Sponsors", "Monthly"
+        );
+        for tier in &sponsorship.tiers {
+            println!(
+                "{:<30} {:>10} {:>8} {:>12}",
+                tier.name,
+                dollars(tier.monthly_cents),
+                tier.sponsors,
+                dollars(tier.total_cents())
+            );
+        }
+    }
+
+    if let Some(licenses) = &report.licenses {
+        println!("Licenses:");
+        println!("{:<20} {:>6} {:>8}", "License", "Repos", "LOC");
+        for share in &licenses.licenses {
+            println!(
+                "{:<20} {:>6} {:>7.2}%",
+                share.license, share.repos, share.loc_percent
+            );
+        }
+    }
+
+    if let Some(packages) = &report.packages {
+        println!(
+            "Package downloads: {} ({} in the last 90 days)",
+            packages.downloads, packages.recent_downloads
+        );
+        println!(
+            "{:<30} {:<10} {:<40} {:>12} {:>12}",
+            "Package", "Registry", "Repository", "Downloads", "Last 90 days"
+        );
+        for package in &packages.packages {
+            println!(
+                "{:<30} {:<10} {:<40} {:>12} {:>12}",
+                package.name,
+                package.registry,
+                package.full_name,
+                package.downloads,
+                package
+                    .recent_downloads
+                    .map_or_else(String::new, |recent| recent.to_string())
+            );
+        }
+    }
+
+    if let Some(containers) = &report.containers {
+        println!("Container pulls: {}", containers.pulls);
+        println!(
+            "{:<45} {:<10} {:<40} {:>12}",
+            "Image", "Registry", "Repository", "Pulls"
+        );
+        for image in &containers.images {
+            println!(
+                "{:<45} {:<10} {:<40} {:>12}",
+                image.image, image.registry, image.full_name, image.pulls
+            );
+        }
+    }
+
+    if let Some(traffic) = &report.traffic {
+        println!(
+            "Traffic in the last 14 days: {} views, {} clones",
+            traffic.views, traffic.clones
+        );
+        println!(
+            "{:<40} {:>8} {:>10} {:>8} {:>10}",
+            "Repository", "Views", "Visitors", "Clones", "Cloners"
+        );
+        for repo in &traffic.repos {
+            println!(
+                "{:<40} {:>8} {:>10} {:>8} {:>10}",
+                repo.full_name, repo.views, repo.unique_visitors, repo.clones, repo.unique_cloners
+            );
+        }
+    }
+
+    if let Some(releases) = &report.releases {
+        println!(
+            "Releases: {} published, {} asset downloads",
+            releases.releases, releases.downloads
+        );
+        println!(
+            "{:<40} {:>8} {:>10} {:<16}",
+            "Repository", "Releases", "Downloads", "Latest"
+        );
+        for repo in &releases.repos {
+            println!(
+                "{:<40} {:>8} {:>10} {:<16}",
+                repo.full_name,
+                repo.releases,
+                repo.downloads,
+                repo.latest_tag.as_deref().unwrap_or("")
+            );
+        }
+    }
+
+    if let Some(activity) = &report.activity {
+        println!("Activity:");
+        if let Some(oldest) = &activity.oldest_repo {
+            println!(
+                "Oldest repo: {} ({})",
+                oldest.full_name,
+                oldest.created_date()
+            );
+        }
+        if let Some(newest) = &activity.newest_repo {
+            println!(
+                "Newest repo: {} ({})",
+                newest.full_name,
+                newest.created_date()
+            );
+        }
+        if let Some(days) = activity.average_age_days {
+            println!("Average repo age: {days} days");
+        }
+        println!(
+            "Repos pushed to in the last {RECENT_DAYS} days: {}",
+            activity.recently_pushed
+        );
+    }
+
+    if let Some(contributions) = &report.contributions {
+        println!("Contributions in the last year: {}", contributions.total);
+        let max = contributions
+            .per_month
+            .values()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        for (month, count) in &contributions.per_month {
+            let bar = "#".repeat((count * 40 / max) as usize);
+            println!("{month}: {count:>5} {bar}");
+        }
+    }
+
+    if let Some(streak) = &report.streak {
+        println!(
+            "Contributions in the last year: {}",
+            streak.total_contributions
+        );
+        println!("Current streak: {} days", streak.current_streak);
+        println!("Longest streak: {} days", streak.longest_streak);
+    }
+
+    if let Some(commits) = &report.commits {
+        println!("Commits in the last year: {}", commits.total);
+        let max = commits
+            .per_month
+            .values()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        for (month, count) in &commits.per_month {
+            let bar = "#".repeat((count * 40 / max) as usize);
+            println!("{month}: {count:>5} {bar}");
+        }
     }
 
-    Ok(repos)
+    if let Some(messages) = &report.commit_messages {
+        println!("Commit messages: {}", messages.commits);
+        println!(
+            "Average length: {:.1} characters, {:.1} in the subject line",
+            messages.average_length, messages.average_subject_length
+        );
+        let mut types: Vec<_> = messages.types.iter().collect();
+        types.sort_by(|(_, a), (_, b)| b.cmp(a));
+        for (kind, count) in types {
+            println!("{kind:<12} {count:>6}");
+        }
+        let words: Vec<String> = messages
+            .top_words
+            .iter()
+            .map(|(word, count)| format!("{word} ({count})"))
+            .collect();
+        println!("Most common words: {}", words.join(", "));
+    }
+
+    if let Some(heatmap) = &report.heatmap {
+        let offset = heatmap.utc_offset_minutes;
+        println!(
+            "Commits by time of day (UTC{}{:02}:{:02}):",
+            if offset < 0 { '-' } else { '+' },
+            offset.abs() / 60,
+            offset.abs() % 60
+        );
+        print!("{}", render_heatmap(heatmap));
+        if let Some((day, hour)) = heatmap.busiest() {
+            println!("Busiest hour: {} {hour:02}:00", WEEKDAYS[day]);
+        }
+    }
 }
 
-struct RepoInfo {
-    language_loc_map: BTreeMap<String, u32>,
-    ratio_of_commits_from_user: f32,
-    stars: u32,
+/// The options that affect the numbers, for reports that record how they were made.
+fn settings(args: &Args) -> Vec<(&'static str, String)> {
+    let yes_no = |value: bool| if value { "yes" } else { "no" }.to_string();
+    vec![
+        (
+            "Subject",
+            match &args.org {
+                Some(org) if args.anonymize => pseudonym("org", org),
+                Some(org) => org.clone(),
+                None => args.user.join(", "),
+            },
+        ),
+        ("Provider", format!("{:?}", args.provider)),
+        ("API", format!("{:?}", args.api)),
+        ("Weighted by contribution", yes_no(args.weighted)),
+        (
+            "Repos contributed to included",
+            yes_no(args.include_contributed),
+        ),
+        ("Gists included", yes_no(args.include_gists)),
+        ("Weight mode", format!("{:?}", args.weight_mode)),
+        ("Lines of code from", format!("{:?}", args.loc_backend)),
+        (
+            "Generated files excluded",
+            yes_no(!args.no_default_excludes),
+        ),
+        ("Forks included", yes_no(args.include_forks)),
+        ("Archived repos included", yes_no(args.include_archived)),
+        (
+            "Minimum contribution",
+            format!("{:.0}%", args.min_contribution * 100.0),
+        ),
+        ("Only topics", args.include_topic.join(", ")),
+        ("Excluded topics", args.exclude_topic.join(", ")),
+        ("Minimum stars", args.min_stars.to_string()),
+        ("Minimum lines of code", args.min_loc.to_string()),
+        ("Visibility", format!("{:?}", args.visibility)),
+        (
+            "Excluded languages",
+            args.excluded_langs
+                .iter()
+                .filter(|lang| !lang.is_empty())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    ]
 }
 
-async fn handle_repo(
-    repo: RepoData,
-    connection: &GitHub,
-) -> Result<Option<RepoInfo>, Box<dyn Error>> {
-    // Get the ratio of all contributions to contributions from the user
-    let contributors_json = connection.get(&repo.contributors_url).await?.text().await?;
-    let Ok(contributors) = serde_json::from_str::<Vec<ContributorData>>(&contributors_json) else {
-        return Ok(None);
+fn github_connection(args: &Args, user: &str) -> GitHub {
+    GitHub::new(user, args.token.clone())
+        .with_weighted(args.weighted)
+        .with_weight_mode(args.weight_mode)
+        .with_excluded_langs(args.excluded_langs.clone())
+        .with_loc_backend(args.loc_backend)
+        .with_loc_fallback(args.loc_fallback)
+        .with_default_excludes(!args.no_default_excludes)
+        .with_retry(args.retry_policy())
+        .with_org_filter(args.org_filter())
+        .with_client(args.http_client.clone())
+        .with_cache(if args.no_cache { None } else { Cache::new() })
+        .with_offline(args.offline)
+        .with_base_url(&args.api_url)
+}
+
+async fn collect_user(
+    args: &Args,
+    user: &str,
+    filter: &RepoFilter,
+) -> Result<Vec<RepoInfo>, Box<dyn Error>> {
+    Ok(match args.provider {
+        Provider::Github => {
+            let connection = github_connection(args, user);
+            let mut repos = match args.api {
+                Api::Rest => {
+                    let mut repos = collect_repos(&connection).await?;
+                    if args.include_contributed {
+                        let mut contributed = collect_contributed_repos(&connection).await?;
+                        contributed.retain(|repo| {
+                            !repos.iter().any(|known| known.full_name == repo.full_name)
+                        });
+                        repos.extend(contributed);
+                    }
+                    let repos = filter.apply(repos);
+                    process_repos(&connection, repos, args.concurrency).await?
+                }
+                Api::Graphql if args.weighted && args.weight_mode != WeightMode::Commits => {
+                    return Err("--weight-mode is only supported with --api rest".into())
+                }
+                Api::Graphql => {
+                    graphql::collect_repo_infos(&connection, filter, args.include_contributed)
+                        .await?
+                }
+            };
+            if args.include_gists {
+                repos.extend(gists::collect_gist_infos(&connection).await?);
+            }
+            repos
+        }
+        Provider::Gitea if args.offline => {
+            return Err("--offline is only supported for GitHub".into())
+        }
+        Provider::Gitea if args.include_gists => {
+            return Err("--include-gists is only supported for GitHub".into())
+        }
+        Provider::Gitea if args.weighted && args.weight_mode != WeightMode::Commits => {
+            return Err("--weight-mode is only supported for GitHub".into())
+        }
+        Provider::Gitea => {
+            gitea::collect_repo_infos(&gitea_connection(args, user), filter, args.concurrency)
+                .await?
+        }
+    })
+}
+
+fn gitea_connection(args: &Args, user: &str) -> Gitea {
+    Gitea::new(&args.host, user, args.token.clone())
+        .with_excluded_langs(args.excluded_langs.clone())
+        .with_retry(args.retry_policy())
+        .with_org_filter(args.org_filter())
+        .with_client(args.http_client.clone())
+}
+
+/// Fetches the star history of the `count` most starred repos of `--org` or the `--user`s.
+async fn print_star_history(
+    args: &Args,
+    filter: &RepoFilter,
+    count: usize,
+    months: i32,
+    style: StarHistoryStyle,
+) -> Result<(), Box<dyn Error>> {
+    if args.provider != Provider::Github {
+        return Err("star-history is only supported for GitHub".into());
+    }
+    let mut repos = Vec::new();
+    let connection = match &args.org {
+        Some(org) => {
+            let connection = github_connection(args, org);
+            repos.extend(org::org_repos(&connection, org).await?);
+            connection
+        }
+        None => {
+            for user in &args.user {
+                repos.extend(collect_repos(&github_connection(args, user)).await?);
+            }
+            github_connection(args, &args.user[0])
+        }
     };
+    let mut repos = filter.apply(repos);
+    // Sorting by name too puts repos found for more than one user next to each other
+    repos.sort_by(|a, b| {
+        b.stargazers_count
+            .cmp(&a.stargazers_count)
+            .then_with(|| a.full_name.cmp(&b.full_name))
+    });
+    repos.dedup_by(|a, b| a.full_name == b.full_name);
+    repos.truncate(count);
 
-    let total_contributions = contributors
-        .iter()
-        .map(|data| data.contributions)
-        .sum::<u32>();
-    let Some(user_contributor) = contributors.iter().find(|contributor| {
-        contributor.login.to_ascii_lowercase() == connection.user.to_ascii_lowercase()
-    }) else {
-        return Ok(None);
+    let mut histories = Vec::new();
+    for repo in repos {
+        histories.push(stars::star_history(&connection, &repo.full_name, months).await?);
+    }
+    match style {
+        StarHistoryStyle::Table => print!("{}", stars::render_table(&histories)),
+        StarHistoryStyle::Sparkline => print!("{}", stars::render_sparklines(&histories)),
+        StarHistoryStyle::Json => println!("{}", serde_json::to_string_pretty(&histories)?),
+    }
+    Ok(())
+}
+
+/// Makes only the discovery calls of a run and prints which repos it would analyze.
+async fn print_plan(args: &Args, filter: &RepoFilter) -> Result<(), Box<dyn Error>> {
+    if args.provider != Provider::Github || args.api != Api::Rest {
+        return Err("--dry-run is only supported for GitHub with --api rest".into());
+    }
+    let plan = build_plan(args, filter).await?;
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&plan)?),
+        _ => print!("{}", render_plan(&plan)),
+    }
+    Ok(())
+}
+
+/// Lists the repos of `--org` or the `--user`s and estimates the requests analyzing them takes.
+async fn build_plan(args: &Args, filter: &RepoFilter) -> Result<Plan, Box<dyn Error>> {
+    let mut plan = Plan::default();
+    match &args.org {
+        Some(org) => {
+            let connection = github_connection(args, org);
+            let repos = org::org_repos(&connection, org).await?;
+            plan.repos = plan_repos(org, repos, filter);
+            plan.requests_per_repo = requests_per_repo(&connection, true);
+            // The member list
+            plan.requests_per_account = 1;
+            plan.accounts = 1;
+        }
+        None => {
+            for user in &args.user {
+                let connection = github_connection(args, user);
+                let mut repos = collect_repos(&connection).await?;
+                if args.include_contributed {
+                    let mut contributed = collect_contributed_repos(&connection).await?;
+                    contributed.retain(|repo| {
+                        !repos.iter().any(|known| known.full_name == repo.full_name)
+                    });
+                    repos.extend(contributed);
+                }
+                plan.repos.extend(plan_repos(user, repos, filter));
+                plan.requests_per_repo = requests_per_repo(&connection, false);
+            }
+            plan.accounts = args.user.len() as u32;
+            for collector in enabled_collectors(&args.metrics) {
+                plan.requests_per_repo += collector.requests_per_repo();
+                plan.requests_per_account += collector.requests_per_account();
+            }
+            // The profile
+            plan.requests_per_account += 1;
+        }
+    }
+    Ok(plan)
+}
+
+/// Prints the token's quotas, and whether a full run fits in them if there is anything to run.
+async fn print_rate_limits(args: &Args, filter: &RepoFilter) -> Result<(), Box<dyn Error>> {
+    if args.provider != Provider::Github {
+        return Err("rate-limit is only supported for GitHub".into());
+    }
+    let has_subject = !args.user.is_empty() || args.org.is_some();
+    let estimate = if has_subject && args.api == Api::Rest {
+        info!("Listing repos to estimate the size of a run...");
+        Some(build_plan(args, filter).await?.estimated_requests())
+    } else {
+        None
     };
+    // Asked for after listing the repos, so that those requests are already taken off
+    let limits = rate_limits(&github_connection(args, &subject(args))).await?;
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&limits)?),
+        _ => print!("{}", render_rate_limits(&limits, estimate)),
+    }
+    Ok(())
+}
 
-    let ratio_of_contributions = user_contributor.contributions as f32 / total_contributions as f32;
-
-    // Get the ratio of all languages in the repo
-    let langs_json = connection
-        .client
-        .get(format!(
-            "https://api.codetabs.com/v1/loc/?github={}",
-            &repo.full_name
-        ))
-        .send()
-        .await?
-        .text()
-        .await?;
-    let Ok(langs) = serde_json::from_str::<Vec<LOCData>>(&langs_json) else {
-        println!("Failed to get language data for repo: {}", repo.full_name);
-        return Ok(None);
+/// Builds the report for `--org` if given, and otherwise for every `--user`.
+async fn collect_report(args: &Args, filter: &RepoFilter) -> Result<Report, Box<dyn Error>> {
+    let Some(org) = &args.org else {
+        return user_report(args, &args.user, filter).await;
     };
+    if args.provider != Provider::Github {
+        return Err("--org is only supported for GitHub".into());
+    }
+    if !args.metrics.is_empty() || args.wakatime_key.is_some() {
+        return Err("--metrics and --wakatime-key only apply to users, not --org".into());
+    }
+    let connection = github_connection(args, org);
+    let (repos_info, mut members) =
+        org::collect_org(&connection, org, filter, args.concurrency).await?;
+    let mut repos_info = filter.apply_thresholds(repos_info);
+    rank_members(&mut members, args.rank_members_by);
+    group_languages(&mut repos_info, &args.language_groups);
+    let mut report = Report {
+        members,
+        ranked_by: args.rank_by,
+        ..Report::new(repos_info, false)
+    };
+    summarize_languages(&mut report);
+    summarize_stars(&mut report);
+    Ok(report)
+}
 
-    let language_loc_map: BTreeMap<String, u32> = langs
-        .into_iter()
-        .filter(|data| {
-            data.language != "Total"
-                && !connection
-                    .excluded_langs
-                    .contains(&data.language.to_ascii_lowercase())
-        })
-        .map(|data| (data.language, data.lines_of_code))
-        .collect();
+/// Collects and combines the repos of every user into one report.
+async fn user_report(
+    args: &Args,
+    users: &[String],
+    filter: &RepoFilter,
+) -> Result<Report, Box<dyn Error>> {
+    if args.metrics.iter().any(|metric| metric == "ecosystems") && args.api != Api::Rest {
+        return Err("--metrics ecosystems needs --api rest".into());
+    }
+    let collectors = enabled_collectors(&args.metrics);
+    // Capabilities are the same for every user of a forge
+    let user = users.first().map_or("", String::as_str);
+    let collectors = match args.provider {
+        Provider::Github => supported_collectors(collectors, &github_connection(args, user)),
+        Provider::Gitea => supported_collectors(collectors, &gitea_connection(args, user)),
+    };
+    let context = MetricContext {
+        accounts: match args.provider {
+            Provider::Github => users
+                .iter()
+                .map(|user| github_connection(args, user))
+                .collect(),
+            Provider::Gitea => Vec::new(),
+        },
+        concurrency: args.concurrency,
+        utc_offset_minutes: args.utc_offset,
+        container_images: args.container_images.clone(),
+    };
+    check_metrics(&collectors, &context)?;
+
+    let mut accounts = Vec::new();
+    for user in users {
+        info!("Collecting repos for {user}...");
+        accounts.push(collect_user(args, user, filter).await?);
+    }
+    let mut repos = filter.apply_thresholds(merge_accounts(accounts));
+    group_languages(&mut repos, &args.language_groups);
+    let mut report = Report::new(repos, args.weighted);
+    report.ranked_by = args.rank_by;
 
-    let stars = repo.stargazers_count;
+    if args.provider == Provider::Github {
+        for user in users {
+            let profile = github_connection(args, user).user_data().await?;
+            report.profiles.push(ProfileSummary::from(profile));
+        }
+    }
 
-    println!("Processed new repo: {}! {stars} stars found with {:.2}% of contributions being from selected user.", repo.full_name, ratio_of_contributions * 100.0);
-    Ok(Some(RepoInfo {
-        language_loc_map,
-        ratio_of_commits_from_user: ratio_of_contributions,
-        stars,
-    }))
+    collect_metrics(&collectors, &context, &mut report).await?;
+
+    if let Some(key) = &args.wakatime_key {
+        if args.offline {
+            return Err("--wakatime-key can't be used with --offline".into());
+        }
+        let known_languages: Vec<&str> = report.languages.keys().map(String::as_str).collect();
+        let mut stats = wakatime::wakatime_stats(
+            args.retry_policy(),
+            key,
+            args.wakatime_range,
+            &known_languages,
+        )
+        .await?;
+        stats.group_languages(&args.language_groups);
+        report.wakatime = Some(stats);
+    }
+    Ok(report)
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    let args = Args::parse();
-    let connection = GitHub::from_args(args);
+async fn main() -> ExitCode {
+    // Printed with `Display` rather than the `Debug` output returning the error would give
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    println!("Excluding languages: {:?}", connection.excluded_langs);
+async fn run() -> Result<(), Box<dyn Error>> {
+    let raw_args: Vec<OsString> = std::env::args_os().collect();
+    let config_path = config::explicit_config_path(&raw_args).or_else(config::default_config_path);
+    let options = match &config_path {
+        Some(path) => config::load_options(path)?,
+        None => BTreeMap::new(),
+    };
+    let matches = command_with_config(&options)?.get_matches_from(raw_args);
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    args.take_report_args(&matches);
+    args.http_client = args.client_options().build()?;
+    let logs = matches!(args.command, Some(Command::Tui)).then(tui::LogBuffer::default);
+    init_logging(&args, logs.clone());
+    if let Some(path) = config_path {
+        info!("Loaded options from {}", path.display());
+        if !config::in_config_dir(&path) {
+            for option in config::SENSITIVE_OPTIONS {
+                if options.contains_key(option) {
+                    warn!(
+                        "{} sets `{option}`, which decides where the token is sent. Check that \
+                         you trust it, or move it to your config directory",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+    let enterprise_host = enterprise_host(&args.api_url);
+    if let (true, Some(token)) = (args.save_token, &args.token) {
+        token::save_token(token, enterprise_host.as_deref())?;
+        info!("Saved the token in the keyring");
+    }
+    if args.token.is_none() && args.provider == Provider::Github {
+        args.token = token::discover_token(enterprise_host.as_deref());
+    }
+    let codetabs =
+        [Some(args.loc_backend), args.loc_fallback].contains(&Some(LocBackend::Codetabs));
+    if enterprise_host.is_some() && args.provider == Provider::Github && codetabs {
+        return Err(
+            "codetabs only counts github.com repos, use --loc-backend linguist or local with \
+             GitHub Enterprise Server"
+                .into(),
+        );
+    }
+    let command = args.command.clone();
+    let filter = RepoFilter {
+        include_forks: args.include_forks,
+        include_archived: args.include_archived,
+        visibility: args.visibility,
+        exclude_repos: glob_set(&args.exclude_repo)?,
+        only_repos: glob_set(&args.only_repo)?,
+        include_topics: args.include_topic.clone(),
+        exclude_topics: args.exclude_topic.clone(),
+        min_contribution: args.min_contribution,
+        min_stars: args.min_stars,
+        min_loc: args.min_loc,
+    };
 
-    let repos = collect_repos(&connection).await?;
+    debug!("Excluding languages: {:?}", args.excluded_langs);
 
-    // Get meaningful data from repos and filter
-    let mut repos_info = Vec::new();
-    for repo in repos {
-        let Some(info) = handle_repo(repo, &connection).await? else {
-            continue;
+    if let Some(Command::Serve {
+        listen,
+        allow_user,
+        include_private,
+        refresh_interval,
+        webhook_secret,
+    }) = command.clone()
+    {
+        let allowed_users = if allow_user.is_empty() {
+            &args.user
+        } else {
+            &allow_user
         };
-        repos_info.push(info);
+        if allowed_users.is_empty() {
+            return Err("serve needs --allow-user or --user to know whose stats to serve".into());
+        }
+        let mut filter = filter.clone();
+        if !include_private {
+            filter.visibility = Visibility::Public;
+        }
+        return server::serve(
+            listen,
+            allowed_users,
+            refresh_interval,
+            webhook_secret,
+            |user| {
+                let args = args.clone();
+                let filter = filter.clone();
+                async move {
+                    let mut report =
+                        user_report(&args, std::slice::from_ref(&user), &filter).await?;
+                    record_history(&args, &user, &report)?;
+                    if args.anonymize {
+                        anonymize_report(&mut report);
+                    }
+                    Ok(report)
+                }
+            },
+        )
+        .await;
     }
 
-    // Sum all language ratios into a new map
-    let mut langs_map: BTreeMap<String, f32> = BTreeMap::new();
-    for info in repos_info.iter() {
-        for (lang, val) in info.language_loc_map.clone() {
-            let val = if connection.weighted {
-                val as f32 * info.ratio_of_commits_from_user
-            } else {
-                val as f32
-            };
-            if let Some(old) = langs_map.get(&lang) {
-                let new = old + val;
-                langs_map.insert(lang, new);
-            } else {
-                langs_map.insert(lang, val);
+    if let Some(Command::Diff { old, new }) = &command {
+        let (old, new) = match (old, new, &args.history) {
+            (Some(old), Some(new), _) => (
+                serde_json::from_str(&std::fs::read_to_string(old)?)?,
+                serde_json::from_str(&std::fs::read_to_string(new)?)?,
+            ),
+            (None, None, Some(path)) => {
+                let history = History::open(path)?;
+                let subject = args
+                    .org
+                    .clone()
+                    .or_else(|| (!args.user.is_empty()).then(|| args.user.join(",")));
+                let runs = history.latest_runs(subject.as_deref(), 2)?;
+                let [new, old] = runs[..] else {
+                    return Err("The history database has fewer than two runs to compare".into());
+                };
+                (history.load_run(old)?, history.load_run(new)?)
             }
+            _ => return Err("diff needs either two report files or --history".into()),
+        };
+        print!("{}", diff_reports(&old, &new));
+        return Ok(());
+    }
+
+    if let Some(Command::Compare { first, second }) = &command {
+        let mut first_report = user_report(&args, std::slice::from_ref(first), &filter).await?;
+        let mut second_report = user_report(&args, std::slice::from_ref(second), &filter).await?;
+        if args.anonymize {
+            anonymize_report(&mut first_report);
+            anonymize_report(&mut second_report);
         }
+        let comparison = compare_reports(first, &first_report, second, &second_report);
+        match args.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&comparison)?),
+            _ => print!(
+                "{}",
+                render_comparison(&comparison, colors::stdout_supports_color())
+            ),
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::RateLimit) = &command {
+        return print_rate_limits(&args, &filter).await;
+    }
+
+    if args.user.is_empty() && args.org.is_none() {
+        Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "either --user or --org is required",
+            )
+            .exit();
     }
 
-    // Scale so that all values add to 100
-    let sum_of_components = langs_map.values().sum::<f32>();
-    let mut percent_map = BTreeMap::new();
-    for (lang, val) in langs_map {
-        let percent = (val / sum_of_components) * 100.0;
-        percent_map.insert(lang, percent);
+    if let Some(Command::StarHistory {
+        repos,
+        months,
+        style,
+    }) = command
+    {
+        return print_star_history(&args, &filter, repos, months, style).await;
     }
 
-    // Print most used languages
-    println!("Most used languages:");
-    let mut percents_sorted: Vec<_> = percent_map.into_iter().collect();
-    percents_sorted.sort_by_key(|(_, v)| (v * 1000.0) as u32);
-    percents_sorted.reverse();
-    for (lang, percent) in percents_sorted.into_iter() {
-        println!("{lang}: {percent}%");
+    if args.report.dry_run {
+        return print_plan(&args, &filter).await;
     }
 
-    // Print total stars
-    let total_stars: f32 = repos_info
-        .iter()
-        .map(|info| {
-            info.stars as f32
-                * if connection.weighted {
-                    info.ratio_of_commits_from_user
-                } else {
-                    1.0
+    if let Some(
+        command @ (Command::Langs { .. }
+        | Command::Stars { .. }
+        | Command::Repos
+        | Command::Card { .. }
+        | Command::Badges { .. }),
+    ) = &command
+    {
+        let report = current_report(&args, &filter).await?;
+        return print_section(&args, command, &report);
+    }
+
+    if let Some(addr) = args.report.prometheus_listen {
+        let interval = if args.report.daemon {
+            args.report.interval
+        } else {
+            args.report.prometheus_interval
+        };
+        return prometheus::export(addr, interval, || {
+            let (args, filter) = (&args, &filter);
+            async move {
+                let mut report = collect_report(args, filter).await?;
+                record_history(args, &subject(args), &report)?;
+                if args.anonymize {
+                    anonymize_report(&mut report);
                 }
+                Ok(report)
+            }
         })
-        .sum();
-    println!("Total stars (weighted depending on args): {total_stars}");
+        .await;
+    }
 
-    Ok(())
+    if let Some(logs) = logs {
+        progress::disable_bars();
+        return tui::run(collect_report(&args, &filter), logs).await;
+    }
+
+    if args.report.daemon {
+        let mut refresh = tokio::time::interval(args.report.interval);
+        loop {
+            refresh.tick().await;
+            if let Err(err) = refresh_report(&args, &filter).await {
+                warn!("Failed to refresh the report: {err}");
+            }
+            info!(
+                "Next refresh in {}",
+                humantime::format_duration(args.report.interval)
+            );
+        }
+    }
+
+    refresh_report(&args, &filter).await
 }
 
-#[derive(Deserialize, Debug)]
-struct LOCData {
-    language: String,
-    #[serde(rename(deserialize = "linesOfCode"))]
-    lines_of_code: u32,
+/// Collects the report once, records it in the history and writes every requested output.
+async fn refresh_report(args: &Args, filter: &RepoFilter) -> Result<(), Box<dyn Error>> {
+    let report = current_report(args, filter).await?;
+    write_report(args, &report)
 }
 
-#[derive(Deserialize, Debug)]
-struct UserData {
-    pub organizations_url: String,
-    pub repos_url: String,
+/// Collects the report, or reuses the latest recorded one with `--offline`, and records it in
+/// the history.
+async fn current_report(args: &Args, filter: &RepoFilter) -> Result<Report, Box<dyn Error>> {
+    let mut report = match latest_recorded(args)? {
+        Some(report) => report,
+        None => {
+            let report = collect_report(args, filter).await?;
+            // The history is local, so it keeps the real names
+            record_history(args, &subject(args), &report)?;
+            report
+        }
+    };
+    if args.anonymize {
+        anonymize_report(&mut report);
+    }
+    Ok(report)
 }
 
-#[derive(Deserialize, Debug)]
-struct RepoData {
-    pub stargazers_count: u32,
-    pub contributors_url: String,
-    pub full_name: String,
+/// `--org`, or the `--user`s joined with commas, as the history records them.
+fn subject(args: &Args) -> String {
+    args.org.clone().unwrap_or_else(|| args.user.join(","))
 }
 
-#[derive(Deserialize, Debug)]
-struct ContributorData {
-    login: String,
-    contributions: u32,
+/// With `--offline`, the most recent run of this subject in `--history`, if there is one.
+fn latest_recorded(args: &Args) -> Result<Option<Report>, Box<dyn Error>> {
+    let (true, Some(path)) = (args.offline, &args.history) else {
+        return Ok(None);
+    };
+    let history = History::open(path)?;
+    let Some(&run) = history.latest_runs(Some(&subject(args)), 1)?.first() else {
+        return Ok(None);
+    };
+    info!("Using the latest run recorded in {}", path.display());
+    Ok(Some(history.load_run(run)?))
 }
 
-#[derive(Deserialize, Debug)]
-struct OrgData {
-    repos_url: String,
+fn record_history(args: &Args, subject: &str, report: &Report) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = &args.history {
+        History::open(path)?.record(subject, report)?;
+        info!("Recorded run in {}", path.display());
+    }
+    Ok(())
+}
+
+/// Prints the part of the report a focused subcommand asks for.
+fn print_section(args: &Args, command: &Command, report: &Report) -> Result<(), Box<dyn Error>> {
+    let json = args.format == OutputFormat::Json;
+    match command {
+        Command::Langs { .. } if json => println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "languages": report.languages,
+                "language_loc": report.language_loc,
+                "total_loc": report.total_loc,
+                "unweighted_language_loc": report.unweighted_language_loc,
+                "unweighted_total_loc": report.unweighted_total_loc,
+            }))?
+        ),
+        Command::Langs { chart_width } => {
+            print_languages(report, *chart_width, &args.number_format())
+        }
+        Command::Stars { top } if json => println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "total_stars": report.total_stars,
+                "total_forks": report.total_forks,
+                "total_watchers": report.total_watchers,
+                "top_repos": report.top_repos(*top, false),
+            }))?
+        ),
+        Command::Stars { top } => {
+            print_totals(report);
+            print_top_repos(report, *top);
+        }
+        Command::Repos if json => println!("{}", serde_json::to_string_pretty(&report.repos)?),
+        Command::Repos => print_repos(report),
+        Command::Card { output, card } => {
+            let svg = card::render_svg(report, &card.options()?);
+            if output
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+            {
+                std::fs::write(output, card::render_png(&svg, card.png_scale)?)?;
+            } else {
+                std::fs::write(output, svg)?;
+            }
+            info!("Wrote stats card to {}", output.display());
+        }
+        Command::Badges { out_dir, top } => {
+            let paths = badge::write_badges(report, out_dir, *top)?;
+            info!("Wrote {} badges to {}", paths.len(), out_dir.display());
+        }
+        _ => unreachable!("not a report section"),
+    }
+    Ok(())
+}
+
+fn write_report(args: &Args, report: &Report) -> Result<(), Box<dyn Error>> {
+    let card_options = args.report.card.options()?;
+
+    let top_repos = args.report.top_repos.unwrap_or(markdown::DEFAULT_TOP_REPOS);
+    let number_format = args.number_format();
+    if args.report.github_action {
+        let summary =
+            markdown::render_markdown(report, top_repos, args.report.per_repo, &number_format);
+        actions::write_action_outputs(report, &summary)?;
+    }
+    if let Some(Command::UpdateReadme {
+        path, commit, push, ..
+    }) = &args.command
+    {
+        let block = match &args.report.template {
+            Some(template) => render_template(report, template)?,
+            None => {
+                markdown::render_markdown(report, top_repos, args.report.per_repo, &number_format)
+            }
+        };
+        if !readme::update_readme(path, &block)? {
+            info!("{} is already up to date", path.display());
+        } else {
+            info!("Updated metrics in {}", path.display());
+            if *commit {
+                readme::commit_readme(path, *push, args.token.as_deref())?;
+                info!("Committed {}", path.display());
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(template) = &args.report.template {
+        print!("{}", render_template(report, template)?);
+    } else {
+        match args.format {
+            OutputFormat::Text => print_text(
+                report,
+                args.report.top_repos,
+                args.report.per_repo,
+                args.report.chart_width,
+                &number_format,
+            ),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(report)?),
+            OutputFormat::Markdown => print!(
+                "{}",
+                markdown::render_markdown(report, top_repos, args.report.per_repo, &number_format)
+            ),
+            OutputFormat::Html => print!(
+                "{}",
+                html::render_html(
+                    report,
+                    &card::render_svg(report, &card_options),
+                    &settings(args),
+                    &number_format,
+                )
+            ),
+            OutputFormat::Csv => {
+                csv::write_csv(report, &args.report.csv_out)?;
+                info!(
+                    "Wrote repos.csv and languages.csv to {}",
+                    args.report.csv_out.display()
+                );
+            }
+        }
+    }
+
+    let svg = card::render_svg(report, &card_options);
+    if let Some(path) = &args.report.output_png {
+        std::fs::write(path, card::render_png(&svg, args.report.card.png_scale)?)?;
+        info!("Wrote PNG stats card to {}", path.display());
+    }
+    if let Some(path) = &args.report.output_svg {
+        std::fs::write(path, svg)?;
+        info!("Wrote stats card to {}", path.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_must_be_longer_than_zero() {
+        assert_eq!(parse_interval("6h"), Ok(Duration::from_secs(6 * 3600)));
+        assert_eq!(parse_interval("1m 30s"), Ok(Duration::from_secs(90)));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("often").is_err());
+    }
+
+    #[test]
+    fn language_groups_split_on_commas() {
+        assert_eq!(
+            parse_language_group(" Web = CSS, HTML,,SCSS "),
+            Ok((
+                "Web".to_string(),
+                vec!["CSS".to_string(), "HTML".to_string(), "SCSS".to_string()]
+            ))
+        );
+        assert!(parse_language_group("Web").is_err());
+    }
+
+    #[test]
+    fn container_images_belong_to_a_repo() {
+        assert_eq!(
+            parse_container_images("octocat/tools=ghcr.io/octocat/cli, ghcr.io/octocat/server"),
+            Ok((
+                "octocat/tools".to_string(),
+                vec![
+                    "ghcr.io/octocat/cli".to_string(),
+                    "ghcr.io/octocat/server".to_string()
+                ]
+            ))
+        );
+        assert!(parse_container_images("ghcr.io/octocat/cli").is_err());
+    }
 }
@@ -0,0 +1,480 @@
+use std::fmt::Write;
+
+use crate::{
+    activity::RECENT_DAYS, format::NumberFormat, sponsors::dollars, wakatime::combined_languages,
+    RepoInfo, Report,
+};
+
+/// How many repos are listed when `--top-repos` isn't given.
+pub const DEFAULT_TOP_REPOS: usize = 10;
+
+/// Renders the report as GitHub-flavored Markdown tables, listing the `top_repos` repos with
+/// the most stars, and every repo's language mix if `per_repo` is set.
+pub fn render_markdown(
+    report: &Report,
+    top_repos: usize,
+    per_repo: bool,
+    format: &NumberFormat,
+) -> String {
+    let mut md = String::new();
+
+    for profile in &report.profiles {
+        let name = profile.name.as_deref().unwrap_or(&profile.login);
+        write!(
+            md,
+            "**{}** · {} followers · {} following · {} public repos · joined {}",
+            escape(name),
+            profile.followers,
+            profile.following,
+            profile.public_repos,
+            profile.created_date()
+        )
+        .unwrap();
+        if let Some(years) = profile.account_age_years() {
+            write!(md, " ({years} years ago)").unwrap();
+        }
+        writeln!(md).unwrap();
+        writeln!(md).unwrap();
+    }
+
+    writeln!(md, "| Language | Percent | Lines of code |").unwrap();
+    writeln!(md, "| --- | ---: | ---: |").unwrap();
+    for (lang, percent) in format.sorted_languages(report) {
+        let loc = report
+            .language_loc
+            .get(lang)
+            .map(|loc| format.count(*loc))
+            .unwrap_or_default();
+        writeln!(
+            md,
+            "| {} | {} | {loc} |",
+            escape(lang),
+            format.percent(percent)
+        )
+        .unwrap();
+    }
+
+    if let Some(stats) = &report.wakatime {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "| Language | Code | Time spent | Hours ({}) |",
+            stats.range
+        )
+        .unwrap();
+        writeln!(md, "| --- | ---: | ---: | ---: |").unwrap();
+        for row in combined_languages(report) {
+            writeln!(
+                md,
+                "| {} | {} | {} | {:.1} |",
+                escape(row.language),
+                format.percent(row.code_percent),
+                format.percent(row.time_percent),
+                row.hours
+            )
+            .unwrap();
+        }
+    }
+
+    if !report.repos.is_empty() {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "| Repository | Stars | Language | Contribution | Last push |"
+        )
+        .unwrap();
+        writeln!(md, "| --- | ---: | --- | ---: | --- |").unwrap();
+        for repo in report.top_repos(top_repos, report.weighted) {
+            writeln!(
+                md,
+                "| {} | {} | {} | {:.2}% | {} |",
+                escape(&repo.full_name),
+                repo.stars,
+                escape(repo.primary_language().unwrap_or("")),
+                repo.ratio_of_commits_from_user * 100.0,
+                push_date(repo)
+            )
+            .unwrap();
+        }
+    }
+
+    if per_repo && !report.repos.is_empty() {
+        writeln!(md).unwrap();
+        match &report.packages {
+            Some(_) => {
+                writeln!(
+                    md,
+                    "| Repository | Stars | Contribution | Package downloads | Languages |"
+                )
+                .unwrap();
+                writeln!(md, "| --- | ---: | ---: | ---: | --- |").unwrap();
+            }
+            None => {
+                writeln!(md, "| Repository | Stars | Contribution | Languages |").unwrap();
+                writeln!(md, "| --- | ---: | ---: | --- |").unwrap();
+            }
+        }
+        for repo in &report.repos {
+            let downloads = match &report.packages {
+                Some(packages) => match packages.repo_downloads(&repo.full_name) {
+                    Some((downloads, _)) => format!(" {} |", format.count(downloads)),
+                    None => " |".to_string(),
+                },
+                None => String::new(),
+            };
+            writeln!(
+                md,
+                "| {} | {} | {:.2}% |{downloads} {} |",
+                escape(&repo.full_name),
+                repo.stars,
+                repo.ratio_of_commits_from_user * 100.0,
+                escape(&language_mix(repo))
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(md).unwrap();
+    writeln!(
+        md,
+        "**Total stars{}:** {:.0}",
+        if report.weighted { " (weighted)" } else { "" },
+        report.total_stars
+    )
+    .unwrap();
+    writeln!(md).unwrap();
+    writeln!(
+        md,
+        "**Total forks:** {:.0} · **Total watchers:** {:.0}",
+        report.total_forks, report.total_watchers
+    )
+    .unwrap();
+
+    if let Some(pulls) = &report.pull_requests {
+        writeln!(md).unwrap();
+        writeln!(md, "| Pull requests | Count |").unwrap();
+        writeln!(md, "| --- | ---: |").unwrap();
+        writeln!(md, "| Opened | {} |", pulls.opened).unwrap();
+        writeln!(md, "| Merged | {} |", pulls.merged).unwrap();
+        writeln!(md, "| Closed unmerged | {} |", pulls.closed).unwrap();
+        writeln!(md, "| Merge rate | {:.2}% |", pulls.merge_rate * 100.0).unwrap();
+    }
+
+    if let Some(issues) = &report.issues {
+        writeln!(md).unwrap();
+        writeln!(md, "| Issues | Own repos | Elsewhere |").unwrap();
+        writeln!(md, "| --- | ---: | ---: |").unwrap();
+        writeln!(
+            md,
+            "| Opened | {} | {} |",
+            issues.own_repos.opened, issues.external.opened
+        )
+        .unwrap();
+        writeln!(
+            md,
+            "| Closed | {} | {} |",
+            issues.own_repos.closed, issues.external.closed
+        )
+        .unwrap();
+        writeln!(
+            md,
+            "| Commented on | {} | {} |",
+            issues.own_repos.commented, issues.external.commented
+        )
+        .unwrap();
+    }
+
+    if let Some(reviews) = &report.reviews {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "**Pull request reviews in the last year:** {} ({} review comments)",
+            reviews.reviews, reviews.review_comments
+        )
+        .unwrap();
+    }
+
+    if let Some(discussions) = &report.discussions {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "**Discussions started:** {} · **Answered:** {}",
+            format.count(discussions.created as u64),
+            format.count(discussions.answered as u64)
+        )
+        .unwrap();
+        if !discussions.repos.is_empty() {
+            writeln!(md).unwrap();
+            writeln!(md, "| Repository | Started | Answered |").unwrap();
+            writeln!(md, "| --- | ---: | ---: |").unwrap();
+            for repo in &discussions.repos {
+                writeln!(
+                    md,
+                    "| {} | {} | {} |",
+                    escape(&repo.full_name),
+                    format.count(repo.created as u64),
+                    format.count(repo.answered as u64)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    if let Some(sponsorship) = &report.sponsorship {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "**Sponsors:** {} · **Monthly:** {}",
+            format.count(sponsorship.sponsors as u64),
+            sponsorship.monthly_dollars()
+        )
+        .unwrap();
+        if !sponsorship.tiers.is_empty() {
+            writeln!(md).unwrap();
+            writeln!(md, "| Tier | Price | Sponsors | Monthly |").unwrap();
+            writeln!(md, "| --- | ---: | ---: | ---: |").unwrap();
+            for tier in &sponsorship.tiers {
+                writeln!(
+                    md,
+                    "| {} | {} | {} | {} |",
+                    escape(&tier.name),
+                    dollars(tier.monthly_cents),
+                    format.count(tier.sponsors as u64),
+                    dollars(tier.total_cents())
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    if let Some(licenses) = &report.licenses {
+        writeln!(md).unwrap();
+        writeln!(md, "| License | Repos | Lines of code |").unwrap();
+        writeln!(md, "| --- | ---: | ---: |").unwrap();
+        for share in &licenses.licenses {
+            writeln!(
+                md,
+                "| {} | {} | {:.2}% |",
+                escape(&share.license),
+                share.repos,
+                share.loc_percent
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(packages) = &report.packages {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "**Package downloads:** {} · **Last 90 days:** {}",
+            format.count(packages.downloads),
+            format.count(packages.recent_downloads)
+        )
+        .unwrap();
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "| Package | Registry | Repository | Downloads | Last 90 days |"
+        )
+        .unwrap();
+        writeln!(md, "| --- | --- | --- | ---: | ---: |").unwrap();
+        for package in &packages.packages {
+            writeln!(
+                md,
+                "| {} | {} | {} | {} | {} |",
+                escape(&package.name),
+                package.registry,
+                escape(&package.full_name),
+                format.count(package.downloads),
+                package
+                    .recent_downloads
+                    .map_or_else(String::new, |recent| format.count(recent))
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(containers) = &report.containers {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "**Container pulls:** {}",
+            format.count(containers.pulls)
+        )
+        .unwrap();
+        writeln!(md).unwrap();
+        writeln!(md, "| Image | Registry | Repository | Pulls |").unwrap();
+        writeln!(md, "| --- | --- | --- | ---: |").unwrap();
+        for image in &containers.images {
+            writeln!(
+                md,
+                "| {} | {} | {} | {} |",
+                escape(&image.image),
+                image.registry,
+                escape(&image.full_name),
+                format.count(image.pulls)
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(traffic) = &report.traffic {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "**Views in the last 14 days:** {} · **Clones:** {}",
+            format.count(traffic.views),
+            format.count(traffic.clones)
+        )
+        .unwrap();
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "| Repository | Views | Unique visitors | Clones | Unique cloners |"
+        )
+        .unwrap();
+        writeln!(md, "| --- | ---: | ---: | ---: | ---: |").unwrap();
+        for repo in &traffic.repos {
+            writeln!(
+                md,
+                "| {} | {} | {} | {} | {} |",
+                escape(&repo.full_name),
+                format.count(repo.views),
+                format.count(repo.unique_visitors),
+                format.count(repo.clones),
+                format.count(repo.unique_cloners)
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(releases) = &report.releases {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "**Releases:** {} · **Asset downloads:** {}",
+            format.count(releases.releases as u64),
+            format.count(releases.downloads)
+        )
+        .unwrap();
+        writeln!(md).unwrap();
+        writeln!(md, "| Repository | Releases | Downloads | Latest |").unwrap();
+        writeln!(md, "| --- | ---: | ---: | --- |").unwrap();
+        for repo in &releases.repos {
+            writeln!(
+                md,
+                "| {} | {} | {} | {} |",
+                escape(&repo.full_name),
+                format.count(repo.releases as u64),
+                format.count(repo.downloads),
+                escape(repo.latest_tag.as_deref().unwrap_or(""))
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(activity) = &report.activity {
+        let mut parts = Vec::new();
+        if let Some(oldest) = &activity.oldest_repo {
+            parts.push(format!(
+                "**Oldest repo:** {} ({})",
+                escape(&oldest.full_name),
+                oldest.created_date()
+            ));
+        }
+        if let Some(newest) = &activity.newest_repo {
+            parts.push(format!(
+                "**Newest repo:** {} ({})",
+                escape(&newest.full_name),
+                newest.created_date()
+            ));
+        }
+        if let Some(days) = activity.average_age_days {
+            parts.push(format!("**Average repo age:** {days} days"));
+        }
+        parts.push(format!(
+            "**Pushed to in the last {RECENT_DAYS} days:** {} repos",
+            activity.recently_pushed
+        ));
+        writeln!(md).unwrap();
+        writeln!(md, "{}", parts.join(" · ")).unwrap();
+    }
+
+    if let Some(ecosystems) = &report.ecosystems {
+        writeln!(md).unwrap();
+        writeln!(md, "| Ecosystem | Repos | Share of repos |").unwrap();
+        writeln!(md, "| --- | ---: | ---: |").unwrap();
+        for share in &ecosystems.ecosystems {
+            writeln!(
+                md,
+                "| {} | {} | {:.2}% |",
+                escape(&share.ecosystem),
+                share.repos,
+                share.repo_percent
+            )
+            .unwrap();
+        }
+        writeln!(md, "| No manifest | {} | |", ecosystems.other_repos).unwrap();
+    }
+
+    if let Some(contributions) = &report.contributions {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "**Contributions in the last year:** {}",
+            contributions.total
+        )
+        .unwrap();
+        writeln!(md).unwrap();
+        writeln!(md, "| Month | Contributions |").unwrap();
+        writeln!(md, "| --- | ---: |").unwrap();
+        for (month, count) in &contributions.per_month {
+            writeln!(md, "| {month} | {count} |").unwrap();
+        }
+    }
+
+    if let Some(streak) = &report.streak {
+        writeln!(md).unwrap();
+        writeln!(
+            md,
+            "**Contributions in the last year:** {} · **Current streak:** {} days · **Longest streak:** {} days",
+            streak.total_contributions, streak.current_streak, streak.longest_streak
+        )
+        .unwrap();
+    }
+
+    if let Some(commits) = &report.commits {
+        writeln!(md).unwrap();
+        writeln!(md, "**Commits in the last year:** {}", commits.total).unwrap();
+        writeln!(md).unwrap();
+        writeln!(md, "| Month | Commits |").unwrap();
+        writeln!(md, "| --- | ---: |").unwrap();
+        for (month, count) in &commits.per_month {
+            writeln!(md, "| {month} | {count} |").unwrap();
+        }
+    }
+
+    md
+}
+
+/// The repo's languages with their percentages, e.g. `Rust 80.00%, Shell 20.00%`.
+pub fn language_mix(repo: &RepoInfo) -> String {
+    repo.language_percentages()
+        .into_iter()
+        .map(|(lang, percent)| format!("{lang} {percent:.2}%"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The day of the last push, without the time.
+pub fn push_date(repo: &RepoInfo) -> &str {
+    repo.pushed_at
+        .as_deref()
+        .map(|pushed_at| pushed_at.get(..10).unwrap_or(pushed_at))
+        .unwrap_or("")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|")
+}
@@ -0,0 +1,816 @@
+use std::{collections::BTreeMap, error::Error, time::SystemTime};
+
+use clap::builder::PossibleValue;
+use futures::future::LocalBoxFuture;
+use tracing::warn;
+
+use crate::{
+    activity::activity_stats,
+    commit_messages::{collect_commit_messages, message_stats},
+    commits::{collect_commits, CommitStats},
+    containers::{collect_containers, DOCKER_HUB_URL},
+    discussions::{collect_discussions, DiscussionStats},
+    ecosystems::ecosystem_stats,
+    forge::{Capability, ForgeProvider},
+    heatmap::{collect_heatmap, CommitHeatmap},
+    issues::{collect_issues, IssueStats},
+    licenses::license_stats,
+    loc::LocBackend,
+    packages::{collect_packages, RegistryUrls},
+    pulls::{collect_pull_requests, PullRequestStats},
+    releases::collect_releases,
+    report::{summarize_languages, summarize_stars},
+    reviews::{collect_reviews, ReviewStats},
+    sponsors::{collect_sponsors, SponsorStats},
+    streak::{contribution_calendar, contribution_stats, streak_stats},
+    traffic::collect_traffic,
+    GitHub, Report,
+};
+
+/// What collectors get to work with besides the report.
+pub struct MetricContext {
+    /// One connection per analyzed account on GitHub. Empty on other forges, where only metrics
+    /// that work from the report alone are supported
+    pub accounts: Vec<GitHub>,
+    /// How many repos to process at once
+    pub concurrency: usize,
+    /// Offset from UTC to bucket commit times in, in minutes
+    pub utc_offset_minutes: i32,
+    /// Container images built from each repo, for repos whose image isn't named after them
+    pub container_images: Vec<(String, Vec<String>)>,
+}
+
+/// Something collected into the report for every analyzed account, like the commit heatmap.
+/// Collectors are found by [`MetricCollector::name`], which is what `--metrics` takes.
+pub trait MetricCollector {
+    /// Kebab case, e.g. `pull-requests`
+    fn name(&self) -> &'static str;
+
+    /// One line for `--help`.
+    fn description(&self) -> &'static str;
+
+    /// Other names `--metrics` accepts.
+    fn aliases(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Collected even when not asked for, e.g. the languages.
+    fn always_on(&self) -> bool {
+        false
+    }
+
+    /// Scopes the token needs. Metrics that need any token at all, like those from the GraphQL
+    /// API, list at least `read:user`.
+    fn required_scopes(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// What the forge has to supply for the metric to be collected.
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[]
+    }
+
+    /// GitHub API requests the metric takes per analyzed repo and account, for `--dry-run`.
+    fn requests_per_repo(&self) -> u32 {
+        0
+    }
+
+    /// GitHub API requests the metric takes per account regardless of its repos.
+    fn requests_per_account(&self) -> u32 {
+        0
+    }
+
+    /// Whether the metric can be collected with these connections, before anything is.
+    fn check(&self, context: &MetricContext) -> Result<(), String> {
+        let missing_token = context
+            .accounts
+            .iter()
+            .any(|account| account.auth_code.is_none());
+        if !self.required_scopes().is_empty() && missing_token {
+            return Err(format!(
+                "--metrics {} needs a token with the {} scopes",
+                self.name(),
+                self.required_scopes().join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    /// Collects the metric over the report's repos and stores it in the report.
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>>;
+}
+
+/// Every built-in collector, in the order they run.
+pub fn collectors() -> Vec<Box<dyn MetricCollector>> {
+    vec![
+        Box::new(Languages),
+        Box::new(Stars),
+        Box::new(Commits),
+        Box::new(CommitMessages),
+        Box::new(Heatmap),
+        Box::new(Streak),
+        Box::new(Contributions),
+        Box::new(PullRequests),
+        Box::new(Issues),
+        Box::new(Reviews),
+        Box::new(Discussions),
+        Box::new(Sponsorship),
+        Box::new(Licenses),
+        Box::new(Ecosystems),
+        Box::new(Activity),
+        Box::new(Releases),
+        Box::new(Packages),
+        Box::new(Containers),
+        Box::new(Traffic),
+    ]
+}
+
+/// The collectors to run for `--metrics names`, including those that always run.
+pub fn enabled_collectors(names: &[String]) -> Vec<Box<dyn MetricCollector>> {
+    collectors()
+        .into_iter()
+        .filter(|collector| {
+            collector.always_on() || names.iter().any(|name| name == collector.name())
+        })
+        .collect()
+}
+
+/// Leaves out the collectors needing something `forge` can't supply, warning about each.
+pub fn supported_collectors(
+    collectors: Vec<Box<dyn MetricCollector>>,
+    forge: &impl ForgeProvider,
+) -> Vec<Box<dyn MetricCollector>> {
+    collectors
+        .into_iter()
+        .filter(|collector| {
+            let missing = collector
+                .required_capabilities()
+                .iter()
+                .find(|capability| !forge.supports(**capability));
+            if let Some(capability) = missing {
+                warn!(
+                    "Skipping --metrics {}: {} can't supply {}",
+                    collector.name(),
+                    forge.name(),
+                    capability.description()
+                );
+            }
+            missing.is_none()
+        })
+        .collect()
+}
+
+/// The values `--metrics` accepts, with their descriptions.
+pub fn possible_values() -> Vec<PossibleValue> {
+    collectors()
+        .iter()
+        .filter(|collector| !collector.always_on())
+        .map(|collector| {
+            PossibleValue::new(collector.name())
+                .help(collector.description())
+                .aliases(collector.aliases().iter().copied())
+        })
+        .collect()
+}
+
+/// Checks every collector up front, so a run doesn't fail after collecting the repos.
+pub fn check_metrics(
+    collectors: &[Box<dyn MetricCollector>],
+    context: &MetricContext,
+) -> Result<(), String> {
+    collectors
+        .iter()
+        .try_for_each(|collector| collector.check(context))
+}
+
+/// Runs `collectors` one after another over the report.
+pub async fn collect_metrics(
+    collectors: &[Box<dyn MetricCollector>],
+    context: &MetricContext,
+    report: &mut Report,
+) -> Result<(), Box<dyn Error>> {
+    for collector in collectors {
+        collector.collect(context, report).await?;
+    }
+    Ok(())
+}
+
+struct Languages;
+impl MetricCollector for Languages {
+    fn name(&self) -> &'static str {
+        "languages"
+    }
+    fn description(&self) -> &'static str {
+        "Share of each language across the repos"
+    }
+    fn always_on(&self) -> bool {
+        true
+    }
+    fn collect<'a>(
+        &'a self,
+        _context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            summarize_languages(report);
+            Ok(())
+        })
+    }
+}
+
+struct Stars;
+impl MetricCollector for Stars {
+    fn name(&self) -> &'static str {
+        "stars"
+    }
+    fn description(&self) -> &'static str {
+        "Total stars, forks and watchers"
+    }
+    fn always_on(&self) -> bool {
+        true
+    }
+    fn collect<'a>(
+        &'a self,
+        _context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            summarize_stars(report);
+            Ok(())
+        })
+    }
+}
+
+struct Commits;
+impl MetricCollector for Commits {
+    fn name(&self) -> &'static str {
+        "commits"
+    }
+    fn description(&self) -> &'static str {
+        "Commits over the last year, with a histogram per month"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::CommitsByAuthor]
+    }
+    fn requests_per_repo(&self) -> u32 {
+        1
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let mut commits = CommitStats::default();
+            for account in &context.accounts {
+                commits.merge(collect_commits(account, &report.repos, context.concurrency).await?);
+            }
+            report.commits = Some(commits);
+            Ok(())
+        })
+    }
+}
+
+struct CommitMessages;
+impl MetricCollector for CommitMessages {
+    fn name(&self) -> &'static str {
+        "commit-messages"
+    }
+    fn description(&self) -> &'static str {
+        "Average message length, conventional commit types and most common words of the user's \
+         commits. Needs `--loc-backend local`"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::CommitsByAuthor, Capability::LocalClones]
+    }
+    fn requests_per_repo(&self) -> u32 {
+        1
+    }
+    fn check(&self, context: &MetricContext) -> Result<(), String> {
+        if context
+            .accounts
+            .iter()
+            .any(|account| account.loc_backend != LocBackend::Local)
+        {
+            return Err("--metrics commit-messages needs --loc-backend local".to_string());
+        }
+        Ok(())
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let mut messages = Vec::new();
+            for account in &context.accounts {
+                messages.extend(
+                    collect_commit_messages(account, &report.repos, context.concurrency).await?,
+                );
+            }
+            report.commit_messages = Some(message_stats(&messages));
+            Ok(())
+        })
+    }
+}
+
+struct Heatmap;
+impl MetricCollector for Heatmap {
+    fn name(&self) -> &'static str {
+        "heatmap"
+    }
+    fn description(&self) -> &'static str {
+        "Commits over the last year by weekday and hour of the day, in `--utc-offset` time"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::CommitsByAuthor]
+    }
+    fn requests_per_repo(&self) -> u32 {
+        1
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let mut heatmap = CommitHeatmap::default();
+            for account in &context.accounts {
+                heatmap.merge(
+                    collect_heatmap(
+                        account,
+                        &report.repos,
+                        context.concurrency,
+                        context.utc_offset_minutes,
+                    )
+                    .await?,
+                );
+            }
+            heatmap.utc_offset_minutes = context.utc_offset_minutes;
+            report.heatmap = Some(heatmap);
+            Ok(())
+        })
+    }
+}
+
+/// Every account's contribution calendar merged, with the total contributions.
+async fn merged_calendar(
+    context: &MetricContext,
+) -> Result<(u32, BTreeMap<String, u32>), Box<dyn Error>> {
+    let mut total = 0;
+    let mut days: BTreeMap<String, u32> = BTreeMap::new();
+    for account in &context.accounts {
+        let (account_total, account_days) = contribution_calendar(account).await?;
+        total += account_total;
+        for (day, count) in account_days {
+            *days.entry(day).or_default() += count;
+        }
+    }
+    Ok((total, days))
+}
+
+struct Streak;
+impl MetricCollector for Streak {
+    fn name(&self) -> &'static str {
+        "streak"
+    }
+    fn description(&self) -> &'static str {
+        "Current and longest contribution streaks from the contribution calendar. Needs a token"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::ContributionGraph]
+    }
+    fn required_scopes(&self) -> &'static [&'static str] {
+        &["read:user"]
+    }
+    fn requests_per_account(&self) -> u32 {
+        1
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            // Merge every account's calendar before looking for streaks
+            let (_, days) = merged_calendar(context).await?;
+            report.streak = Some(streak_stats(&days));
+            Ok(())
+        })
+    }
+}
+
+struct Contributions;
+impl MetricCollector for Contributions {
+    fn name(&self) -> &'static str {
+        "contributions"
+    }
+    fn description(&self) -> &'static str {
+        "Total contributions over the last year, as on the profile page, and per month. Needs a \
+         token"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::ContributionGraph]
+    }
+    fn required_scopes(&self) -> &'static [&'static str] {
+        &["read:user"]
+    }
+    fn requests_per_account(&self) -> u32 {
+        1
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let (total, days) = merged_calendar(context).await?;
+            report.contributions = Some(contribution_stats(total, &days));
+            Ok(())
+        })
+    }
+}
+
+struct PullRequests;
+impl MetricCollector for PullRequests {
+    fn name(&self) -> &'static str {
+        "pull-requests"
+    }
+    fn description(&self) -> &'static str {
+        "Pull requests opened, merged, and closed, and the merge rate"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::Search]
+    }
+    fn aliases(&self) -> &'static [&'static str] {
+        &["prs"]
+    }
+    fn requests_per_account(&self) -> u32 {
+        3
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let mut pulls = PullRequestStats::default();
+            for account in &context.accounts {
+                pulls.merge(collect_pull_requests(account).await?);
+            }
+            report.pull_requests = Some(pulls);
+            Ok(())
+        })
+    }
+}
+
+struct Issues;
+impl MetricCollector for Issues {
+    fn name(&self) -> &'static str {
+        "issues"
+    }
+    fn description(&self) -> &'static str {
+        "Issues opened, closed, and commented on, in the user's own repos and elsewhere"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::Search]
+    }
+    fn requests_per_account(&self) -> u32 {
+        3
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let mut issues = IssueStats::default();
+            for account in &context.accounts {
+                issues.merge(collect_issues(account).await?);
+            }
+            report.issues = Some(issues);
+            Ok(())
+        })
+    }
+}
+
+struct Reviews;
+impl MetricCollector for Reviews {
+    fn name(&self) -> &'static str {
+        "reviews"
+    }
+    fn description(&self) -> &'static str {
+        "Pull request reviews and review comments over the last year. Needs a token"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::ContributionGraph]
+    }
+    fn required_scopes(&self) -> &'static [&'static str] {
+        &["read:user"]
+    }
+    fn requests_per_account(&self) -> u32 {
+        1
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let mut reviews = ReviewStats::default();
+            for account in &context.accounts {
+                reviews.merge(collect_reviews(account).await?);
+            }
+            report.reviews = Some(reviews);
+            Ok(())
+        })
+    }
+}
+
+struct Discussions;
+impl MetricCollector for Discussions {
+    fn name(&self) -> &'static str {
+        "discussions"
+    }
+    fn description(&self) -> &'static str {
+        "Discussions started and answered in the analyzed repos. Needs a token"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::Discussions]
+    }
+    fn required_scopes(&self) -> &'static [&'static str] {
+        &["read:user"]
+    }
+    fn requests_per_account(&self) -> u32 {
+        2
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let mut discussions = DiscussionStats::default();
+            for account in &context.accounts {
+                discussions.merge(collect_discussions(account, &report.repos).await?);
+            }
+            report.discussions = Some(discussions);
+            Ok(())
+        })
+    }
+}
+
+struct Sponsorship;
+impl MetricCollector for Sponsorship {
+    fn name(&self) -> &'static str {
+        "sponsorship"
+    }
+    fn description(&self) -> &'static str {
+        "Active GitHub Sponsors and what their monthly tiers add up to. Needs a token, and private \
+         sponsorships only show up with the user's own"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::Sponsors]
+    }
+    fn required_scopes(&self) -> &'static [&'static str] {
+        &["read:user"]
+    }
+    fn requests_per_account(&self) -> u32 {
+        1
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let mut sponsorship = SponsorStats::default();
+            for account in &context.accounts {
+                sponsorship.merge(collect_sponsors(account).await?);
+            }
+            report.sponsorship = Some(sponsorship);
+            Ok(())
+        })
+    }
+}
+
+struct Licenses;
+impl MetricCollector for Licenses {
+    fn name(&self) -> &'static str {
+        "licenses"
+    }
+    fn description(&self) -> &'static str {
+        "Repos and share of lines of code per license"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::Licenses]
+    }
+    fn collect<'a>(
+        &'a self,
+        _context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            report.licenses = Some(license_stats(&report.repos, report.weighted));
+            Ok(())
+        })
+    }
+}
+
+struct Ecosystems;
+impl MetricCollector for Ecosystems {
+    fn name(&self) -> &'static str {
+        "ecosystems"
+    }
+    fn description(&self) -> &'static str {
+        "Repos per package ecosystem, from manifests like `Cargo.toml` or `package.json`. Needs \
+         `--loc-backend local`"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::LocalClones]
+    }
+    fn check(&self, context: &MetricContext) -> Result<(), String> {
+        if context
+            .accounts
+            .iter()
+            .any(|account| account.loc_backend != LocBackend::Local)
+        {
+            return Err("--metrics ecosystems needs --loc-backend local".to_string());
+        }
+        Ok(())
+    }
+    fn collect<'a>(
+        &'a self,
+        _context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            report.ecosystems = Some(ecosystem_stats(&report.repos));
+            Ok(())
+        })
+    }
+}
+
+struct Activity;
+impl MetricCollector for Activity {
+    fn name(&self) -> &'static str {
+        "activity"
+    }
+    fn description(&self) -> &'static str {
+        "Oldest and newest repo, average repo age and repos pushed to in the last 90 days"
+    }
+    fn collect<'a>(
+        &'a self,
+        _context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            report.activity = Some(activity_stats(&report.repos, SystemTime::now()));
+            Ok(())
+        })
+    }
+}
+
+struct Releases;
+impl MetricCollector for Releases {
+    fn name(&self) -> &'static str {
+        "releases"
+    }
+    fn description(&self) -> &'static str {
+        "Published releases and downloads of their assets, per repo and in total"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::Releases]
+    }
+    fn requests_per_repo(&self) -> u32 {
+        1
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let Some(account) = context.accounts.first() else {
+                return Ok(());
+            };
+            report.releases =
+                Some(collect_releases(account, &report.repos, context.concurrency).await?);
+            Ok(())
+        })
+    }
+}
+
+struct Packages;
+impl MetricCollector for Packages {
+    fn name(&self) -> &'static str {
+        "packages"
+    }
+    fn description(&self) -> &'static str {
+        "Downloads of the packages published from the repos to crates.io, npm and PyPI"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::FileContents]
+    }
+    fn requests_per_repo(&self) -> u32 {
+        1
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let Some(account) = context.accounts.first() else {
+                return Ok(());
+            };
+            report.packages = Some(
+                collect_packages(
+                    account,
+                    &report.repos,
+                    context.concurrency,
+                    &RegistryUrls::default(),
+                )
+                .await?,
+            );
+            Ok(())
+        })
+    }
+}
+
+struct Containers;
+impl MetricCollector for Containers {
+    fn name(&self) -> &'static str {
+        "containers"
+    }
+    fn description(&self) -> &'static str {
+        "Pulls of the container images built from the repos, from Docker Hub and GHCR. Images \
+         are named after the repo unless mapped with `--container-images`"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::FileContents]
+    }
+    fn requests_per_repo(&self) -> u32 {
+        1
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let Some(account) = context.accounts.first() else {
+                return Ok(());
+            };
+            report.containers = Some(
+                collect_containers(
+                    account,
+                    &report.repos,
+                    &context.container_images,
+                    context.concurrency,
+                    DOCKER_HUB_URL,
+                )
+                .await?,
+            );
+            Ok(())
+        })
+    }
+}
+
+struct Traffic;
+impl MetricCollector for Traffic {
+    fn name(&self) -> &'static str {
+        "traffic"
+    }
+    fn description(&self) -> &'static str {
+        "Views and clones over the last 14 days of the repos the token can push to. Recorded \
+         per day with `--history`, so it adds up past the 14 days GitHub keeps"
+    }
+    fn required_capabilities(&self) -> &'static [Capability] {
+        &[Capability::Traffic]
+    }
+    fn required_scopes(&self) -> &'static [&'static str] {
+        &["repo"]
+    }
+    fn requests_per_repo(&self) -> u32 {
+        2
+    }
+    fn collect<'a>(
+        &'a self,
+        context: &'a MetricContext,
+        report: &'a mut Report,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        Box::pin(async move {
+            let Some(account) = context.accounts.first() else {
+                return Ok(());
+            };
+            report.traffic =
+                Some(collect_traffic(account, &report.repos, context.concurrency).await?);
+            Ok(())
+        })
+    }
+}
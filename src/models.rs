@@ -0,0 +1,205 @@
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug)]
+pub struct LOCData {
+    pub language: String,
+    #[serde(rename(deserialize = "linesOfCode"))]
+    pub lines_of_code: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UserData {
+    pub login: String,
+    pub name: Option<String>,
+    pub organizations_url: String,
+    pub repos_url: String,
+    pub followers: u32,
+    pub following: u32,
+    pub public_repos: u32,
+    pub created_at: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AuthenticatedUserData {
+    pub login: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct RepoData {
+    pub stargazers_count: u32,
+    pub forks_count: u32,
+    pub pushed_at: Option<String>,
+    pub created_at: Option<String>,
+    pub contributors_url: String,
+    pub full_name: String,
+    pub owner: OwnerData,
+    pub clone_url: String,
+    pub languages_url: String,
+    pub fork: bool,
+    pub archived: bool,
+    pub disabled: bool,
+    pub private: bool,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    pub license: Option<LicenseData>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OwnerData {
+    pub login: String,
+    /// `User` or `Organization`
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+impl OwnerData {
+    pub fn is_organization(&self) -> bool {
+        self.kind == "Organization"
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LicenseData {
+    pub spdx_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ContributorData {
+    pub login: String,
+    pub contributions: u32,
+}
+
+/// One contributor's entry in `stats/contributors`.
+#[derive(Deserialize, Debug)]
+pub struct ContributorStatsData {
+    /// Missing for commits by deleted accounts
+    pub author: Option<ContributorStatsAuthor>,
+    pub weeks: Vec<WeeklyStatsData>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ContributorStatsAuthor {
+    pub login: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WeeklyStatsData {
+    /// Lines added
+    pub a: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct MemberData {
+    pub login: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct OrgData {
+    pub login: String,
+    pub repos_url: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommitData {
+    pub sha: String,
+    pub commit: CommitDetails,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommitDetails {
+    pub author: Option<GitAuthorData>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GitAuthorData {
+    pub date: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchCountData {
+    pub total_count: u32,
+}
+
+/// Fields only returned when fetching a single repo, not in repo lists.
+#[derive(Deserialize, Debug)]
+pub struct RepoDetailsData {
+    pub subscribers_count: u32,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ReleaseData {
+    pub tag_name: String,
+    /// Drafts are only listed for tokens that can push to the repo
+    #[serde(default)]
+    pub draft: bool,
+    /// Missing for drafts
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAssetData>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ReleaseAssetData {
+    pub download_count: u64,
+}
+
+/// An entry of a directory listed by the contents API.
+#[derive(Deserialize, Debug)]
+pub struct ContentEntryData {
+    pub path: String,
+    /// `file`, `dir`, `symlink` or `submodule`
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CratesIoData {
+    #[serde(rename = "crate")]
+    pub krate: CratesIoCrate,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NpmDownloadsData {
+    pub downloads: u64,
+}
+
+/// Daily downloads from pypistats, which keeps the last 180 days.
+#[derive(Deserialize, Debug)]
+pub struct PypiStatsData {
+    pub data: Vec<PypiStatsDay>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct PypiStatsDay {
+    /// `YYYY-MM-DD`
+    pub date: String,
+    pub downloads: u64,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CratesIoCrate {
+    pub downloads: u64,
+    /// Over the last 90 days
+    pub recent_downloads: Option<u64>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DockerHubRepoData {
+    pub pull_count: u64,
+}
+
+/// Either of the repo's `traffic/views` or `traffic/clones`, over the last 14 days.
+#[derive(Deserialize, Debug)]
+pub struct TrafficData {
+    pub count: u64,
+    pub uniques: u64,
+    #[serde(alias = "views", alias = "clones", default)]
+    pub days: Vec<TrafficDayData>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TrafficDayData {
+    /// RFC 3339 timestamp of the start of the day, in UTC
+    pub timestamp: String,
+    pub count: u64,
+    pub uniques: u64,
+}
@@ -0,0 +1,202 @@
+use std::{collections::BTreeMap, error::Error};
+
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::{info, info_span, Instrument};
+
+use crate::{
+    error::MetricsError,
+    filter::RepoFilter,
+    github::warn_skipped,
+    models::{ContributorData, MemberData, RepoData},
+    progress::Progress,
+    report::RepoInfo,
+    GitHub,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MemberContribution {
+    pub login: String,
+    pub contributions: u32,
+    /// How many of the organization's repos the member contributed to
+    pub repos: u32,
+    /// Stars on the repos the member is the top contributor of
+    #[serde(default)]
+    pub stars: u32,
+    /// The language with the most lines of code, counting each repo by the member's share of
+    /// its contributions
+    #[serde(default)]
+    pub primary_language: Option<String>,
+}
+
+/// What the member leaderboard is ranked by.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MemberRanking {
+    #[default]
+    Contributions,
+    /// Stars on the repos each member leads
+    Stars,
+    Repos,
+}
+
+/// Sorts members best first, breaking ties by contributions and then by login.
+pub fn rank_members(members: &mut [MemberContribution], ranking: MemberRanking) {
+    members.sort_by(|a, b| {
+        let key = |member: &MemberContribution| match ranking {
+            MemberRanking::Contributions => member.contributions,
+            MemberRanking::Stars => member.stars,
+            MemberRanking::Repos => member.repos,
+        };
+        key(b)
+            .cmp(&key(a))
+            .then(b.contributions.cmp(&a.contributions))
+            .then(a.login.cmp(&b.login))
+    });
+}
+
+/// Every repo in the organization the token can see.
+pub async fn org_repos(connection: &GitHub, org: &str) -> Result<Vec<RepoData>, MetricsError> {
+    let repos: Vec<RepoData> = connection
+        .get_all(connection.api_url(&format!("/orgs/{org}/repos?type=all")))
+        .await?;
+    info!("Found {} organization repos!", repos.len());
+    Ok(repos)
+}
+
+/// Analyzes every repo in an organization. Every repo counts in full, and the commits to them
+/// are tallied up per organization member.
+pub async fn collect_org(
+    connection: &GitHub,
+    org: &str,
+    filter: &RepoFilter,
+    concurrency: usize,
+) -> Result<(Vec<RepoInfo>, Vec<MemberContribution>), Box<dyn Error>> {
+    let repos = filter.apply(org_repos(connection, org).await?);
+
+    let progress = Progress::new(repos.len());
+    let results: Vec<(RepoInfo, Vec<ContributorData>)> = futures::stream::iter(repos)
+        .map(|repo| {
+            let progress = &progress;
+            let span = info_span!("repo", name = %repo.full_name);
+            async move {
+                progress.start(&repo.full_name);
+                let result = handle_org_repo(repo, connection).await?;
+                progress.set_api_budget(connection.rate_limit_remaining());
+                progress.finish_repo(result.as_ref().map(|(info, _)| {
+                    format!(
+                        "Processed new repo: {}! {} stars found.",
+                        info.full_name, info.stars
+                    )
+                }));
+                Ok::<_, Box<dyn Error>>(result)
+            }
+            .instrument(span)
+        })
+        .buffered(concurrency.max(1))
+        .try_filter_map(|result| async move { Ok(result) })
+        .try_collect()
+        .await?;
+    warn_skipped(connection);
+
+    // Without a token only public members are listed
+    let members: Vec<MemberData> = connection
+        .get_all(connection.api_url(&format!("/orgs/{org}/members")))
+        .await?;
+
+    let mut by_login: BTreeMap<String, MemberContribution> = BTreeMap::new();
+    let mut languages: BTreeMap<String, BTreeMap<&str, f32>> = BTreeMap::new();
+    for (info, contributors) in &results {
+        let total = contributors
+            .iter()
+            .map(|contributor| contributor.contributions)
+            .sum::<u32>()
+            .max(1);
+        let lead = contributors
+            .iter()
+            .max_by_key(|contributor| contributor.contributions);
+        for contributor in contributors {
+            if !members
+                .iter()
+                .any(|member| member.login.eq_ignore_ascii_case(&contributor.login))
+            {
+                continue;
+            }
+            let entry = by_login
+                .entry(contributor.login.clone())
+                .or_insert_with(|| MemberContribution {
+                    login: contributor.login.clone(),
+                    contributions: 0,
+                    repos: 0,
+                    stars: 0,
+                    primary_language: None,
+                });
+            entry.contributions += contributor.contributions;
+            entry.repos += 1;
+            if lead.is_some_and(|lead| lead.login == contributor.login) {
+                entry.stars += info.stars;
+            }
+
+            let share = contributor.contributions as f32 / total as f32;
+            let member_languages = languages.entry(contributor.login.clone()).or_default();
+            for (lang, loc) in &info.language_loc_map {
+                *member_languages.entry(lang).or_default() += *loc as f32 * share;
+            }
+        }
+    }
+    for (login, member_languages) in languages {
+        if let Some(member) = by_login.get_mut(&login) {
+            member.primary_language = member_languages
+                .into_iter()
+                .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|(lang, _)| lang.to_string());
+        }
+    }
+    let mut members: Vec<MemberContribution> = by_login.into_values().collect();
+    rank_members(&mut members, MemberRanking::default());
+
+    let repos_info = results.into_iter().map(|(info, _)| info).collect();
+    Ok((repos_info, members))
+}
+
+async fn handle_org_repo(
+    repo: RepoData,
+    connection: &GitHub,
+) -> Result<Option<(RepoInfo, Vec<ContributorData>)>, Box<dyn Error>> {
+    let contributors = match connection
+        .get_all::<ContributorData>(&repo.contributors_url)
+        .await
+    {
+        Ok(contributors) => contributors,
+        Err(err) => {
+            connection.skip_repo(
+                &repo.full_name,
+                format!("couldn't list contributors: {err}"),
+            );
+            return Ok(None);
+        }
+    };
+    let Some((language_loc_map, ecosystems)) = connection.language_loc_map(&repo).await? else {
+        connection.skip_repo(&repo.full_name, "no language data");
+        return Ok(None);
+    };
+
+    let watchers = connection.watchers(&repo.full_name).await;
+
+    Ok(Some((
+        RepoInfo {
+            full_name: repo.full_name,
+            language_loc_map,
+            ratio_of_commits_from_user: 1.0,
+            stars: repo.stargazers_count,
+            forks: repo.forks_count,
+            watchers,
+            pushed_at: repo.pushed_at,
+            created_at: repo.created_at,
+            license: repo.license.and_then(|license| license.spdx_id),
+            authored_lines: None,
+            ecosystems,
+            collaborator: false,
+        },
+        contributors,
+    )))
+}
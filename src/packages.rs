@@ -0,0 +1,455 @@
+use std::{
+    error::Error,
+    time::{Duration, SystemTime},
+};
+
+use futures::{StreamExt, TryStreamExt};
+use reqwest::{IntoUrl, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use toml::{Table, Value};
+use tracing::debug;
+
+use crate::{
+    activity::RECENT_DAYS,
+    error::{parse_json, parse_url, MetricsError},
+    http::USER_AGENT,
+    models::{ContentEntryData, CratesIoData, NpmDownloadsData, PypiStatsData},
+    GitHub, RepoInfo,
+};
+
+pub const CRATES_IO_URL: &str = "https://crates.io/api/v1";
+pub const NPM_URL: &str = "https://api.npmjs.org";
+pub const PYPISTATS_URL: &str = "https://pypistats.org/api";
+
+/// Where each registry's download counts are looked up.
+#[derive(Clone, Debug)]
+pub struct RegistryUrls {
+    pub crates_io: String,
+    pub npm: String,
+    pub pypistats: String,
+}
+impl Default for RegistryUrls {
+    fn default() -> Self {
+        Self {
+            crates_io: CRATES_IO_URL.to_string(),
+            npm: NPM_URL.to_string(),
+            pypistats: PYPISTATS_URL.to_string(),
+        }
+    }
+}
+
+/// A package registry, along with the manifest packages for it are named in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Registry {
+    CratesIo,
+    Npm,
+    PyPI,
+}
+impl Registry {
+    pub const ALL: [Registry; 3] = [Registry::CratesIo, Registry::Npm, Registry::PyPI];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Registry::CratesIo => "crates.io",
+            Registry::Npm => "npm",
+            Registry::PyPI => "PyPI",
+        }
+    }
+
+    /// Whether the repo may publish to the registry, from its ecosystems or failing that its
+    /// languages, which is all that's known without `--loc-backend local`.
+    fn applies_to(self, repo: &RepoInfo) -> bool {
+        let (ecosystem, languages): (&str, &[&str]) = match self {
+            Registry::CratesIo => ("Cargo", &["Rust"]),
+            Registry::Npm => ("npm", &["JavaScript", "TypeScript"]),
+            Registry::PyPI => ("PyPI", &["Python"]),
+        };
+        repo.ecosystems.iter().any(|known| known == ecosystem)
+            || languages
+                .iter()
+                .any(|language| repo.language_loc_map.contains_key(*language))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PackageStats {
+    /// Sum of the packages' `downloads`
+    pub downloads: u64,
+    /// Downloads over the last 90 days
+    pub recent_downloads: u64,
+    /// Most downloaded first
+    pub packages: Vec<PackageDownloads>,
+}
+impl PackageStats {
+    /// Total and recent downloads of the packages published from the repo, or `None` if it
+    /// publishes none.
+    pub fn repo_downloads(&self, full_name: &str) -> Option<(u64, u64)> {
+        self.packages
+            .iter()
+            .filter(|package| package.full_name == full_name)
+            .fold(None, |sum, package| {
+                let (downloads, recent) = sum.unwrap_or_default();
+                Some((
+                    downloads + package.downloads,
+                    recent + package.recent_downloads.unwrap_or(0),
+                ))
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PackageDownloads {
+    /// e.g. `crates.io`, see [`Registry::name`]
+    pub registry: String,
+    pub name: String,
+    /// The repo the package is published from
+    pub full_name: String,
+    /// As far back as the registry keeps count: ever on crates.io, the last year on npm and the
+    /// last 180 days on PyPI
+    pub downloads: u64,
+    /// Over the last 90 days
+    pub recent_downloads: Option<u64>,
+}
+
+/// Looks up the packages published from each repo on their registries. Packages are linked to
+/// repos by the names in the repos' manifests, so only packages whose manifest is in the repo
+/// are found.
+pub async fn collect_packages(
+    connection: &GitHub,
+    repos: &[RepoInfo],
+    concurrency: usize,
+    registries: &RegistryUrls,
+) -> Result<PackageStats, Box<dyn Error>> {
+    if connection.offline {
+        return Err(MetricsError::Offline("--metrics packages").into());
+    }
+    let per_repo: Vec<Vec<PackageDownloads>> = futures::stream::iter(repos)
+        .map(|repo| repo_packages(connection, repo, registries))
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    let mut packages: Vec<PackageDownloads> = per_repo.into_iter().flatten().collect();
+    packages.sort_by_key(|package| std::cmp::Reverse(package.downloads));
+    Ok(PackageStats {
+        downloads: packages.iter().map(|package| package.downloads).sum(),
+        recent_downloads: packages
+            .iter()
+            .filter_map(|package| package.recent_downloads)
+            .sum(),
+        packages,
+    })
+}
+
+async fn repo_packages(
+    connection: &GitHub,
+    repo: &RepoInfo,
+    registries: &RegistryUrls,
+) -> Result<Vec<PackageDownloads>, Box<dyn Error>> {
+    let full_name = &repo.full_name;
+    let mut packages = Vec::new();
+    for registry in Registry::ALL {
+        if !registry.applies_to(repo) {
+            continue;
+        }
+        let names = match registry {
+            Registry::CratesIo => crate_names(connection, full_name).await?,
+            Registry::Npm => npm_names(connection, full_name).await?,
+            Registry::PyPI => pypi_names(connection, full_name).await?,
+        };
+        for name in names {
+            let Some((downloads, recent_downloads)) =
+                package_downloads(connection, registries, registry, &name).await?
+            else {
+                debug!("{name} from {full_name} isn't on {}", registry.name());
+                continue;
+            };
+            packages.push(PackageDownloads {
+                registry: registry.name().to_string(),
+                name,
+                full_name: full_name.clone(),
+                downloads,
+                recent_downloads,
+            });
+        }
+    }
+    Ok(packages)
+}
+
+/// Names of the publishable packages in the repo's root `Cargo.toml` and the workspace members
+/// it lists.
+async fn crate_names(connection: &GitHub, full_name: &str) -> Result<Vec<String>, MetricsError> {
+    let Some(root) = toml_manifest(connection, full_name, "Cargo.toml").await? else {
+        return Ok(Vec::new());
+    };
+    let mut names: Vec<String> = crate_name(&root).into_iter().collect();
+
+    let members: Vec<&str> = root
+        .get("workspace")
+        .and_then(|workspace| workspace.get("members"))
+        .and_then(Value::as_array)
+        .map(|members| members.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    for dir in member_dirs(connection, full_name, &members).await? {
+        let path = format!("{dir}/Cargo.toml");
+        if let Some(manifest) = toml_manifest(connection, full_name, &path).await? {
+            names.extend(crate_name(&manifest));
+        }
+    }
+
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// The package's name, unless it isn't published to crates.io.
+fn crate_name(manifest: &Table) -> Option<String> {
+    let package = manifest.get("package")?;
+    match package.get("publish") {
+        Some(Value::Boolean(false)) => return None,
+        Some(Value::Array(registries))
+            if !registries
+                .iter()
+                .any(|registry| registry.as_str() == Some("crates-io")) =>
+        {
+            return None
+        }
+        _ => {}
+    }
+    package.get("name")?.as_str().map(str::to_string)
+}
+
+/// Names of the public packages in the repo's root `package.json` and the workspaces it lists.
+async fn npm_names(connection: &GitHub, full_name: &str) -> Result<Vec<String>, MetricsError> {
+    let Some(root) = json_manifest(connection, full_name, "package.json").await? else {
+        return Ok(Vec::new());
+    };
+    let mut names: Vec<String> = npm_name(&root).into_iter().collect();
+
+    // Either a list of paths, or an object with them under `packages` for Yarn
+    let workspaces = root.get("workspaces");
+    let members: Vec<&str> = workspaces
+        .and_then(|workspaces| workspaces.get("packages"))
+        .or(workspaces)
+        .and_then(serde_json::Value::as_array)
+        .map(|members| {
+            members
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .collect()
+        })
+        .unwrap_or_default();
+    for dir in member_dirs(connection, full_name, &members).await? {
+        let path = format!("{dir}/package.json");
+        if let Some(manifest) = json_manifest(connection, full_name, &path).await? {
+            names.extend(npm_name(&manifest));
+        }
+    }
+
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// The package's name, unless it's marked private.
+fn npm_name(manifest: &serde_json::Value) -> Option<String> {
+    if manifest.get("private").and_then(serde_json::Value::as_bool) == Some(true) {
+        return None;
+    }
+    manifest.get("name")?.as_str().map(str::to_string)
+}
+
+/// The name in the repo's root `pyproject.toml`, from the standard `[project]` table or from
+/// Poetry's. Projects only described by `setup.py` can't be read without running it.
+async fn pypi_names(connection: &GitHub, full_name: &str) -> Result<Vec<String>, MetricsError> {
+    let Some(manifest) = toml_manifest(connection, full_name, "pyproject.toml").await? else {
+        return Ok(Vec::new());
+    };
+    let name = manifest
+        .get("project")
+        .or_else(|| manifest.get("tool").and_then(|tool| tool.get("poetry")))
+        .and_then(|project| project.get("name"))
+        .and_then(Value::as_str);
+    Ok(name.map(normalize_pypi_name).into_iter().collect())
+}
+
+/// PyPI treats runs of `-`, `_` and `.` alike and ignores case, see PEP 503.
+fn normalize_pypi_name(name: &str) -> String {
+    let mut normalized = String::new();
+    for c in name.to_ascii_lowercase().chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c);
+        }
+    }
+    normalized
+}
+
+/// The directories of workspace `members`. Members given as `dir/*` are found by listing `dir`,
+/// other globs are skipped.
+async fn member_dirs(
+    connection: &GitHub,
+    full_name: &str,
+    members: &[&str],
+) -> Result<Vec<String>, MetricsError> {
+    let mut dirs = Vec::new();
+    for member in members {
+        let member = member.trim_start_matches("./").trim_end_matches('/');
+        match member.strip_suffix("/*") {
+            Some(parent) if !is_glob(parent) => {
+                dirs.extend(subdirectories(connection, full_name, parent).await?)
+            }
+            _ if is_glob(member) => {
+                debug!("Skipping workspace members {member} of {full_name}")
+            }
+            _ => dirs.push(member.to_string()),
+        }
+    }
+    Ok(dirs)
+}
+
+fn is_glob(path: &str) -> bool {
+    path.contains(['*', '?', '[', '{'])
+}
+
+async fn subdirectories(
+    connection: &GitHub,
+    full_name: &str,
+    dir: &str,
+) -> Result<Vec<String>, MetricsError> {
+    let url = parse_url(&connection.api_url(&format!("/repos/{full_name}/contents/{dir}")))?;
+    let page = match connection.get_page(url.clone()).await {
+        Ok(page) => page,
+        Err(MetricsError::NotFound { .. }) => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let entries: Vec<ContentEntryData> = parse_json(&url, &page.body)?;
+    Ok(entries
+        .into_iter()
+        .filter(|entry| entry.kind == "dir")
+        .map(|entry| entry.path)
+        .collect())
+}
+
+async fn toml_manifest(
+    connection: &GitHub,
+    full_name: &str,
+    path: &str,
+) -> Result<Option<Table>, MetricsError> {
+    let Some(contents) = connection.file_contents(full_name, path).await? else {
+        return Ok(None);
+    };
+    match contents.parse::<Table>() {
+        Ok(manifest) => Ok(Some(manifest)),
+        Err(err) => {
+            debug!("Invalid {path} in {full_name}: {err}");
+            Ok(None)
+        }
+    }
+}
+
+async fn json_manifest(
+    connection: &GitHub,
+    full_name: &str,
+    path: &str,
+) -> Result<Option<serde_json::Value>, MetricsError> {
+    let Some(contents) = connection.file_contents(full_name, path).await? else {
+        return Ok(None);
+    };
+    match serde_json::from_str(&contents) {
+        Ok(manifest) => Ok(Some(manifest)),
+        Err(err) => {
+            debug!("Invalid {path} in {full_name}: {err}");
+            Ok(None)
+        }
+    }
+}
+
+/// Total and recent downloads of the package, or `None` if the registry doesn't know it.
+async fn package_downloads(
+    connection: &GitHub,
+    registries: &RegistryUrls,
+    registry: Registry,
+    name: &str,
+) -> Result<Option<(u64, Option<u64>)>, MetricsError> {
+    match registry {
+        Registry::CratesIo => {
+            let url = format!(
+                "{}/crates/{name}",
+                registries.crates_io.trim_end_matches('/')
+            );
+            let data: Option<CratesIoData> = get_json(connection, url).await?;
+            Ok(data.map(|data| (data.krate.downloads, data.krate.recent_downloads)))
+        }
+        Registry::Npm => {
+            let base = registries.npm.trim_end_matches('/');
+            let total: Option<NpmDownloadsData> = get_json(
+                connection,
+                format!("{base}/downloads/point/last-year/{name}"),
+            )
+            .await?;
+            let Some(total) = total else {
+                return Ok(None);
+            };
+            let range = format!("{}:{}", days_ago(RECENT_DAYS), days_ago(0));
+            let recent: Option<NpmDownloadsData> =
+                get_json(connection, format!("{base}/downloads/point/{range}/{name}")).await?;
+            Ok(Some((
+                total.downloads,
+                recent.map(|recent| recent.downloads),
+            )))
+        }
+        Registry::PyPI => {
+            let url = format!(
+                "{}/packages/{name}/overall?mirrors=false",
+                registries.pypistats.trim_end_matches('/')
+            );
+            let data: Option<PypiStatsData> = get_json(connection, url).await?;
+            let Some(data) = data else {
+                return Ok(None);
+            };
+            let since = days_ago(RECENT_DAYS);
+            let recent = data
+                .data
+                .iter()
+                .filter(|day| day.date >= since)
+                .map(|day| day.downloads)
+                .sum();
+            let total = data.data.iter().map(|day| day.downloads).sum();
+            Ok(Some((total, Some(recent))))
+        }
+    }
+}
+
+/// The date `days` days ago as `YYYY-MM-DD`, in UTC.
+fn days_ago(days: u64) -> String {
+    let time = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+    humantime::format_rfc3339_seconds(time).to_string()[..10].to_string()
+}
+
+/// Fetches JSON from a registry, `None` if it responds with 404 Not Found.
+async fn get_json<T: DeserializeOwned>(
+    connection: &GitHub,
+    url: impl IntoUrl,
+) -> Result<Option<T>, MetricsError> {
+    let url = url.into_url()?;
+    // Registries turn away requests without a user agent
+    let response = connection
+        .client
+        .get(url.clone())
+        .header("User-Agent", USER_AGENT)
+        .send()
+        .await?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(MetricsError::from_response(&url, status, &body));
+    }
+    parse_json(&url, &body).map(Some)
+}
@@ -0,0 +1,104 @@
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::{filter::RepoFilter, loc::LocBackend, models::RepoData, report::WeightMode, GitHub};
+
+/// A discovered repo and whether it would be analyzed.
+#[derive(Serialize, Debug, Clone)]
+pub struct PlannedRepo {
+    /// The user or organization the repo was found for
+    pub account: String,
+    pub full_name: String,
+    pub stars: u32,
+    /// Why the repo would be skipped, or `None` if it would be analyzed
+    pub skip_reason: Option<String>,
+}
+
+/// What a run would do, from the discovery calls alone.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct Plan {
+    pub repos: Vec<PlannedRepo>,
+    /// GitHub API requests analyzing one repo takes
+    pub requests_per_repo: u32,
+    /// Requests made once per account, like the profile and search based metrics
+    pub requests_per_account: u32,
+    pub accounts: u32,
+}
+impl Plan {
+    pub fn analyzed(&self) -> impl Iterator<Item = &PlannedRepo> {
+        self.repos.iter().filter(|repo| repo.skip_reason.is_none())
+    }
+
+    /// A lower bound, since paginated listings like contributors can take more than one
+    /// request and repos GitHub is still computing statistics for are retried.
+    pub fn estimated_requests(&self) -> u32 {
+        self.analyzed().count() as u32 * self.requests_per_repo
+            + self.accounts * self.requests_per_account
+    }
+}
+
+/// Checks every discovered repo against the filters. `--min-stars` is known from the listing,
+/// but the other thresholds need the analysis, so they aren't reported here.
+pub fn plan_repos(account: &str, repos: Vec<RepoData>, filter: &RepoFilter) -> Vec<PlannedRepo> {
+    repos
+        .into_iter()
+        .map(|repo| {
+            let skip_reason = filter.skip_reason(&repo).or_else(|| {
+                (repo.stargazers_count < filter.min_stars).then(|| "below --min-stars".to_string())
+            });
+            PlannedRepo {
+                account: account.to_string(),
+                full_name: repo.full_name,
+                stars: repo.stargazers_count,
+                skip_reason,
+            }
+        })
+        .collect()
+}
+
+/// GitHub API requests [`crate::github::handle_repo`], or the organization equivalent, makes
+/// per repo with the connection's settings. Codetabs and local clones aren't counted, since
+/// they don't use the API's rate limit.
+pub fn requests_per_repo(connection: &GitHub, org: bool) -> u32 {
+    // The contributors and the repo details for the watcher count
+    let mut requests = 2;
+    if connection.loc_backend == LocBackend::Linguist {
+        requests += 1;
+    }
+    if connection.weighted && !org {
+        requests += match connection.weight_mode {
+            WeightMode::Commits => 0,
+            // `stats/contributors`, or the user's commits to blame
+            WeightMode::Lines | WeightMode::Blame => 1,
+        };
+    }
+    requests
+}
+
+pub fn render_plan(plan: &Plan) -> String {
+    let mut out = String::new();
+    let mut account = None;
+    for repo in &plan.repos {
+        if account != Some(&repo.account) {
+            writeln!(out, "{}:", repo.account).unwrap();
+            account = Some(&repo.account);
+        }
+        match &repo.skip_reason {
+            None => writeln!(out, "  + {} ({} stars)", repo.full_name, repo.stars).unwrap(),
+            Some(reason) => writeln!(out, "  - {} ({reason})", repo.full_name).unwrap(),
+        }
+    }
+    let analyzed = plan.analyzed().count();
+    writeln!(
+        out,
+        "\n{analyzed} of {} repos would be analyzed, taking at least {} API requests \
+         ({} per repo, {} per account).",
+        plan.repos.len(),
+        plan.estimated_requests(),
+        plan.requests_per_repo,
+        plan.requests_per_account
+    )
+    .unwrap();
+    out
+}
@@ -0,0 +1,45 @@
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::UserData;
+
+const SECONDS_PER_YEAR: u64 = 31_557_600;
+
+/// Who the report is about, shown above the language stats.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProfileSummary {
+    pub login: String,
+    pub name: Option<String>,
+    pub followers: u32,
+    pub following: u32,
+    pub public_repos: u32,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+}
+impl ProfileSummary {
+    /// Whole years since the account was created, if the creation date could be parsed.
+    pub fn account_age_years(&self) -> Option<u64> {
+        let created = humantime::parse_rfc3339(&self.created_at).ok()?;
+        let age = SystemTime::now().duration_since(created).ok()?;
+        Some(age.as_secs() / SECONDS_PER_YEAR)
+    }
+
+    /// The creation date without the time of day.
+    pub fn created_date(&self) -> &str {
+        self.created_at.get(..10).unwrap_or(&self.created_at)
+    }
+}
+
+impl From<UserData> for ProfileSummary {
+    fn from(data: UserData) -> Self {
+        Self {
+            login: data.login,
+            name: data.name,
+            followers: data.followers,
+            following: data.following,
+            public_repos: data.public_repos,
+            created_at: data.created_at,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    io::{self, IsTerminal},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use tracing::{info, Level};
+
+use crate::RepoInfo;
+
+static BARS_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Stops progress bars from being drawn, for when something else owns the terminal.
+pub fn disable_bars() {
+    BARS_ENABLED.store(false, Ordering::Relaxed);
+}
+
+/// Shows how far along repo processing is. On a terminal this draws a bar with the repos
+/// processed so far and a line with the repo being worked on, otherwise it prints a line per
+/// repo like before.
+pub struct Progress {
+    bars: Option<Bars>,
+}
+
+struct Bars {
+    multi: MultiProgress,
+    repos: ProgressBar,
+    current: ProgressBar,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Self {
+        // Progress is logged at info level, so `--quiet` hides the bars too
+        if !BARS_ENABLED.load(Ordering::Relaxed)
+            || !io::stderr().is_terminal()
+            || !tracing::enabled!(Level::INFO)
+        {
+            return Self { bars: None };
+        }
+
+        let multi = MultiProgress::with_draw_target(ProgressDrawTarget::stderr());
+        let repos = multi.add(ProgressBar::new(total as u64));
+        repos.set_style(
+            ProgressStyle::with_template("{bar:40.cyan/blue} {pos}/{len} repos {msg}")
+                .unwrap()
+                .progress_chars("=> "),
+        );
+        let current = multi.add(ProgressBar::new_spinner());
+        current.set_style(ProgressStyle::with_template("{spinner} {msg}").unwrap());
+        current.enable_steady_tick(Duration::from_millis(100));
+
+        Self {
+            bars: Some(Bars {
+                multi,
+                repos,
+                current,
+            }),
+        }
+    }
+
+    /// Adds repos to the total, for when they're found a page at a time.
+    pub fn add_to_total(&self, count: usize) {
+        if let Some(bars) = &self.bars {
+            bars.repos.inc_length(count as u64);
+        }
+    }
+
+    /// Marks `name` as the repo currently being worked on.
+    pub fn start(&self, name: &str) {
+        if let Some(bars) = &self.bars {
+            bars.current.set_message(format!("Processing {name}..."));
+        }
+    }
+
+    /// Counts a repo as done, logging `message` about it if there is one.
+    pub fn finish_repo(&self, message: Option<String>) {
+        let Some(bars) = &self.bars else {
+            if let Some(message) = message {
+                info!("{message}");
+            }
+            return;
+        };
+        if let Some(message) = message {
+            let _ = bars.multi.println(message);
+        }
+        bars.repos.inc(1);
+    }
+
+    /// Shows how many API requests are left before hitting the rate limit.
+    pub fn set_api_budget(&self, remaining: Option<u32>) {
+        if let (Some(bars), Some(remaining)) = (&self.bars, remaining) {
+            bars.repos
+                .set_message(format!("({remaining} API requests left)"));
+        }
+    }
+}
+
+impl Drop for Progress {
+    fn drop(&mut self) {
+        if let Some(bars) = &self.bars {
+            bars.current.finish_and_clear();
+            bars.repos.finish();
+        }
+    }
+}
+
+/// The line logged for a repo the user has contributed to.
+pub fn processed_message(info: &RepoInfo) -> String {
+    format!(
+        "Processed new repo: {}! {} stars found with {:.2}% of contributions being from selected user.",
+        info.full_name,
+        info.stars,
+        info.ratio_of_commits_from_user * 100.0
+    )
+}
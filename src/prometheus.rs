@@ -0,0 +1,366 @@
+use std::{
+    error::Error,
+    fmt::Write,
+    future::Future,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{extract::State, http::header, response::IntoResponse, routing::get, Router};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::{activity::RECENT_DAYS, Report};
+
+/// Renders the report in the Prometheus text exposition format.
+pub fn render_metrics(report: &Report) -> String {
+    let mut out = String::new();
+
+    writeln!(
+        out,
+        "# HELP ghmetrics_language_percent Share of analyzed code written in each language."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE ghmetrics_language_percent gauge").unwrap();
+    for (lang, percent) in &report.languages {
+        writeln!(
+            out,
+            "ghmetrics_language_percent{{lang=\"{}\"}} {percent}",
+            escape_label(lang)
+        )
+        .unwrap();
+    }
+
+    writeln!(
+        out,
+        "# HELP ghmetrics_stars_total Stars across analyzed repos, weighted if enabled."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE ghmetrics_stars_total gauge").unwrap();
+    writeln!(out, "ghmetrics_stars_total {}", report.total_stars).unwrap();
+
+    writeln!(
+        out,
+        "# HELP ghmetrics_forks_total Forks across analyzed repos, weighted if enabled."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE ghmetrics_forks_total gauge").unwrap();
+    writeln!(out, "ghmetrics_forks_total {}", report.total_forks).unwrap();
+
+    writeln!(
+        out,
+        "# HELP ghmetrics_watchers_total Watchers across analyzed repos, weighted if enabled."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE ghmetrics_watchers_total gauge").unwrap();
+    writeln!(out, "ghmetrics_watchers_total {}", report.total_watchers).unwrap();
+
+    writeln!(
+        out,
+        "# HELP ghmetrics_repos_analyzed Number of repos that were analyzed."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE ghmetrics_repos_analyzed gauge").unwrap();
+    writeln!(out, "ghmetrics_repos_analyzed {}", report.repos.len()).unwrap();
+
+    if let Some(pulls) = &report.pull_requests {
+        writeln!(
+            out,
+            "# HELP ghmetrics_pull_requests Pull requests authored by the user, by outcome."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_pull_requests gauge").unwrap();
+        for (state, count) in [
+            ("opened", pulls.opened),
+            ("merged", pulls.merged),
+            ("closed", pulls.closed),
+        ] {
+            writeln!(out, "ghmetrics_pull_requests{{state=\"{state}\"}} {count}").unwrap();
+        }
+    }
+
+    if let Some(issues) = &report.issues {
+        writeln!(
+            out,
+            "# HELP ghmetrics_issues Issues the user opened, had closed, or commented on."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_issues gauge").unwrap();
+        for (scope, counts) in [("own", issues.own_repos), ("external", issues.external)] {
+            for (kind, count) in [
+                ("opened", counts.opened),
+                ("closed", counts.closed),
+                ("commented", counts.commented),
+            ] {
+                writeln!(
+                    out,
+                    "ghmetrics_issues{{scope=\"{scope}\",kind=\"{kind}\"}} {count}"
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    if let Some(reviews) = &report.reviews {
+        writeln!(
+            out,
+            "# HELP ghmetrics_reviews Pull request reviews submitted over the last year."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_reviews gauge").unwrap();
+        writeln!(out, "ghmetrics_reviews {}", reviews.reviews).unwrap();
+        writeln!(
+            out,
+            "# HELP ghmetrics_review_comments Comments left in those reviews."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_review_comments gauge").unwrap();
+        writeln!(out, "ghmetrics_review_comments {}", reviews.review_comments).unwrap();
+    }
+
+    if let Some(discussions) = &report.discussions {
+        writeln!(
+            out,
+            "# HELP ghmetrics_discussions_started Discussions started in the analyzed repos."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_discussions_started gauge").unwrap();
+        writeln!(out, "ghmetrics_discussions_started {}", discussions.created).unwrap();
+        writeln!(
+            out,
+            "# HELP ghmetrics_discussions_answered Discussions answered in the analyzed repos."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_discussions_answered gauge").unwrap();
+        writeln!(
+            out,
+            "ghmetrics_discussions_answered {}",
+            discussions.answered
+        )
+        .unwrap();
+    }
+
+    if let Some(sponsorship) = &report.sponsorship {
+        writeln!(out, "# HELP ghmetrics_sponsors Active GitHub Sponsors.").unwrap();
+        writeln!(out, "# TYPE ghmetrics_sponsors gauge").unwrap();
+        writeln!(out, "ghmetrics_sponsors {}", sponsorship.sponsors).unwrap();
+        writeln!(
+            out,
+            "# HELP ghmetrics_sponsorship_monthly_cents What the monthly sponsorship tiers add up to, in US cents."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_sponsorship_monthly_cents gauge").unwrap();
+        writeln!(
+            out,
+            "ghmetrics_sponsorship_monthly_cents {}",
+            sponsorship.monthly_cents
+        )
+        .unwrap();
+    }
+
+    if let Some(packages) = &report.packages {
+        writeln!(
+            out,
+            "# HELP ghmetrics_package_downloads Downloads of each package published from the analyzed repos."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_package_downloads gauge").unwrap();
+        for package in &packages.packages {
+            writeln!(
+                out,
+                "ghmetrics_package_downloads{{registry=\"{}\",package=\"{}\"}} {}",
+                escape_label(&package.registry),
+                escape_label(&package.name),
+                package.downloads
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(containers) = &report.containers {
+        writeln!(
+            out,
+            "# HELP ghmetrics_container_pulls Pulls of each container image built from the analyzed repos."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_container_pulls gauge").unwrap();
+        for image in &containers.images {
+            writeln!(
+                out,
+                "ghmetrics_container_pulls{{registry=\"{}\",image=\"{}\"}} {}",
+                escape_label(&image.registry),
+                escape_label(&image.image),
+                image.pulls
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(traffic) = &report.traffic {
+        writeln!(
+            out,
+            "# HELP ghmetrics_repo_views Views of each analyzed repo over the last 14 days."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_repo_views gauge").unwrap();
+        for repo in &traffic.repos {
+            writeln!(
+                out,
+                "ghmetrics_repo_views{{repo=\"{}\"}} {}",
+                escape_label(&repo.full_name),
+                repo.views
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "# HELP ghmetrics_repo_clones Clones of each analyzed repo over the last 14 days."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_repo_clones gauge").unwrap();
+        for repo in &traffic.repos {
+            writeln!(
+                out,
+                "ghmetrics_repo_clones{{repo=\"{}\"}} {}",
+                escape_label(&repo.full_name),
+                repo.clones
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(releases) = &report.releases {
+        writeln!(
+            out,
+            "# HELP ghmetrics_releases Published releases of each analyzed repo."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_releases gauge").unwrap();
+        for repo in &releases.repos {
+            writeln!(
+                out,
+                "ghmetrics_releases{{repo=\"{}\"}} {}",
+                escape_label(&repo.full_name),
+                repo.releases
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "# HELP ghmetrics_release_downloads Downloads of the release assets of each analyzed repo."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_release_downloads gauge").unwrap();
+        for repo in &releases.repos {
+            writeln!(
+                out,
+                "ghmetrics_release_downloads{{repo=\"{}\"}} {}",
+                escape_label(&repo.full_name),
+                repo.downloads
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(activity) = &report.activity {
+        writeln!(
+            out,
+            "# HELP ghmetrics_recently_pushed_repos Repos pushed to in the last {RECENT_DAYS} days."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_recently_pushed_repos gauge").unwrap();
+        writeln!(
+            out,
+            "ghmetrics_recently_pushed_repos {}",
+            activity.recently_pushed
+        )
+        .unwrap();
+        if let Some(days) = activity.average_age_days {
+            writeln!(
+                out,
+                "# HELP ghmetrics_average_repo_age_days Mean age of the analyzed repos."
+            )
+            .unwrap();
+            writeln!(out, "# TYPE ghmetrics_average_repo_age_days gauge").unwrap();
+            writeln!(out, "ghmetrics_average_repo_age_days {days}").unwrap();
+        }
+    }
+
+    if let Some(licenses) = &report.licenses {
+        writeln!(
+            out,
+            "# HELP ghmetrics_license_repos Repos analyzed under each license."
+        )
+        .unwrap();
+        writeln!(out, "# TYPE ghmetrics_license_repos gauge").unwrap();
+        for share in &licenses.licenses {
+            writeln!(
+                out,
+                "ghmetrics_license_repos{{license=\"{}\"}} {}",
+                escape_label(&share.license),
+                share.repos
+            )
+            .unwrap();
+        }
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    writeln!(
+        out,
+        "# HELP ghmetrics_last_refresh_timestamp_seconds When the metrics were last collected."
+    )
+    .unwrap();
+    writeln!(out, "# TYPE ghmetrics_last_refresh_timestamp_seconds gauge").unwrap();
+    writeln!(out, "ghmetrics_last_refresh_timestamp_seconds {now}").unwrap();
+
+    out
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serves `GET /metrics` for Prometheus, recollecting the report every `interval`.
+///
+/// Collection runs on the calling task, so `collect` doesn't need to be `Send`. If a refresh
+/// fails the previous metrics keep being served.
+pub async fn export<F, Fut>(
+    addr: SocketAddr,
+    interval: Duration,
+    collect: F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Report, Box<dyn Error>>>,
+{
+    let metrics: Arc<RwLock<String>> = Arc::default();
+    let app = Router::new()
+        .route("/metrics", get(serve_metrics))
+        .with_state(metrics.clone());
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Exporting Prometheus metrics on http://{addr}/metrics");
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let mut refresh = tokio::time::interval(interval);
+    loop {
+        refresh.tick().await;
+        match collect().await {
+            Ok(report) => *metrics.write().await = render_metrics(&report),
+            Err(err) => warn!("Failed to refresh metrics: {err}"),
+        }
+    }
+}
+
+async fn serve_metrics(State(metrics): State<Arc<RwLock<String>>>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics.read().await.clone(),
+    )
+}
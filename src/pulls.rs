@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{error::MetricsError, GitHub};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct PullRequestStats {
+    pub opened: u32,
+    pub merged: u32,
+    /// Closed without being merged
+    pub closed: u32,
+    /// Share of decided (merged or closed) pull requests that were merged
+    pub merge_rate: f32,
+}
+impl PullRequestStats {
+    fn new(opened: u32, merged: u32, closed: u32) -> Self {
+        let decided = merged + closed;
+        Self {
+            opened,
+            merged,
+            closed,
+            merge_rate: if decided == 0 {
+                0.0
+            } else {
+                merged as f32 / decided as f32
+            },
+        }
+    }
+
+    /// Adds another account's pull requests to these.
+    pub fn merge(&mut self, other: PullRequestStats) {
+        *self = Self::new(
+            self.opened + other.opened,
+            self.merged + other.merged,
+            self.closed + other.closed,
+        );
+    }
+}
+
+/// Counts the user's pull requests across all of GitHub with the search API.
+pub async fn collect_pull_requests(connection: &GitHub) -> Result<PullRequestStats, MetricsError> {
+    let author = format!("type:pr author:{}", connection.user);
+    Ok(PullRequestStats::new(
+        connection.search_count(&author).await?,
+        connection
+            .search_count(&format!("{author} is:merged"))
+            .await?,
+        connection
+            .search_count(&format!("{author} is:closed is:unmerged"))
+            .await?,
+    ))
+}
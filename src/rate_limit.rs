@@ -0,0 +1,106 @@
+use std::{
+    fmt::Write,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{parse_json, parse_url, MetricsError},
+    GitHub,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Quota {
+    pub limit: u32,
+    pub used: u32,
+    pub remaining: u32,
+    /// Unix timestamp at which the quota resets
+    pub reset: u64,
+}
+
+/// The quotas relevant to a run, as reported by `/rate_limit`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RateLimits {
+    /// REST requests other than searches
+    pub core: Quota,
+    /// Missing when unauthenticated, since the GraphQL API needs a token
+    #[serde(default)]
+    pub graphql: Option<Quota>,
+    pub search: Quota,
+}
+
+#[derive(Deserialize)]
+struct RateLimitData {
+    resources: RateLimits,
+}
+
+/// The token's current quotas. Asking doesn't count against any of them, so this skips the
+/// response cache.
+pub async fn rate_limits(connection: &GitHub) -> Result<RateLimits, MetricsError> {
+    if connection.offline {
+        return Err(MetricsError::Offline("rate-limit"));
+    }
+    let url = parse_url(&connection.api_url("/rate_limit"))?;
+    let response = connection.send(url.clone(), None, None).await?;
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(MetricsError::from_response(&url, status, &body));
+    }
+    Ok(parse_json::<RateLimitData>(&url, &body)?.resources)
+}
+
+/// One line per quota, followed by whether `estimated_requests` REST requests fit in what's
+/// left of the core quota, if an estimate is given.
+pub fn render_rate_limits(limits: &RateLimits, estimated_requests: Option<u32>) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let resets_in = |quota: &Quota| {
+        humantime::format_duration(Duration::from_secs(quota.reset.saturating_sub(now)))
+    };
+
+    let mut out = String::new();
+    let quotas = [
+        ("Core", Some(&limits.core)),
+        ("GraphQL", limits.graphql.as_ref()),
+        ("Search", Some(&limits.search)),
+    ];
+    for (name, quota) in quotas {
+        match quota {
+            Some(quota) => writeln!(
+                out,
+                "{name:<8} {:>6} of {:>6} left, resets in {}",
+                quota.remaining,
+                quota.limit,
+                resets_in(quota)
+            )
+            .unwrap(),
+            None => writeln!(out, "{name:<8} not available without a token").unwrap(),
+        }
+    }
+
+    if let Some(estimate) = estimated_requests {
+        let core = &limits.core;
+        if estimate <= core.remaining {
+            writeln!(
+                out,
+                "\nA full run takes at least {estimate} requests, which fits in the {} left.",
+                core.remaining
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                out,
+                "\nA full run takes at least {estimate} requests, more than the {} left. It \
+                 would wait {} for the quota to reset partway through.",
+                core.remaining,
+                resets_in(core)
+            )
+            .unwrap();
+        }
+    }
+    out
+}
@@ -0,0 +1,105 @@
+use std::{error::Error, fs, path::Path, process::Command};
+
+use crate::http::git_auth_env;
+
+pub const START_MARKER: &str = "<!-- metrics:start -->";
+pub const END_MARKER: &str = "<!-- metrics:end -->";
+
+/// Replaces everything between the metrics markers with `block`.
+pub fn replace_block(contents: &str, block: &str) -> Result<String, Box<dyn Error>> {
+    let start = contents
+        .find(START_MARKER)
+        .ok_or_else(|| format!("Missing `{START_MARKER}` marker"))?
+        + START_MARKER.len();
+    let end = contents[start..]
+        .find(END_MARKER)
+        .ok_or_else(|| format!("Missing `{END_MARKER}` marker after `{START_MARKER}`"))?
+        + start;
+
+    Ok(format!(
+        "{}\n{}\n{}",
+        &contents[..start],
+        block.trim_end(),
+        &contents[end..]
+    ))
+}
+
+/// Regenerates the metrics block in the README at `path`. Returns whether the file changed.
+pub fn update_readme(path: &Path, block: &str) -> Result<bool, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    let updated = replace_block(&contents, block)?;
+    if updated == contents {
+        return Ok(false);
+    }
+    fs::write(path, updated)?;
+    Ok(true)
+}
+
+/// Commits the README in whatever repo it lives in, optionally pushing with `token` for
+/// authentication on https remotes.
+pub fn commit_readme(path: &Path, push: bool, token: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    // The token goes to git through the environment, so it's in neither the command line nor
+    // the error messages
+    let git_with_env = |args: &[&str], env: &[(&str, String)]| -> Result<String, Box<dyn Error>> {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .envs(env.iter().cloned())
+            .args(args)
+            .output()?;
+        if !output.status.success() {
+            return Err(format!(
+                "`git {}` failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .into());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let git = |args: &[&str]| git_with_env(args, &[]);
+
+    let file_name = path
+        .file_name()
+        .ok_or("README path has no file name")?
+        .to_string_lossy();
+    git(&["add", file_name.as_ref()])?;
+    git(&["commit", "-m", "Update metrics"])?;
+
+    if push {
+        let remote = git(&["remote", "get-url", "origin"])?;
+        let env = match token {
+            Some(token) if remote.starts_with("https://") => git_auth_env(&remote, token),
+            _ => Vec::new(),
+        };
+        git_with_env(&["push", "origin", "HEAD"], &env)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_block_between_the_markers_is_replaced() {
+        let contents =
+            format!("# Hi\n{START_MARKER}\nold stats\n{END_MARKER}\nBye {START_MARKER}\n");
+        let updated = replace_block(&contents, "new stats\n\n").unwrap();
+        assert_eq!(
+            updated,
+            format!("# Hi\n{START_MARKER}\nnew stats\n{END_MARKER}\nBye {START_MARKER}\n")
+        );
+        // Replacing again is a no-op
+        assert_eq!(replace_block(&updated, "new stats").unwrap(), updated);
+    }
+
+    #[test]
+    fn missing_markers_are_errors() {
+        assert!(replace_block("no markers", "stats").is_err());
+        assert!(replace_block(&format!("{END_MARKER}\n{START_MARKER}"), "stats").is_err());
+    }
+}
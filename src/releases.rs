@@ -0,0 +1,88 @@
+use std::error::Error;
+
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{error::MetricsError, models::ReleaseData, GitHub, RepoInfo};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ReleaseStats {
+    /// Published releases across every repo, drafts left out
+    pub releases: u32,
+    /// Downloads of every release asset
+    pub downloads: u64,
+    /// Repos with at least one release, most downloads first
+    pub repos: Vec<RepoReleases>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RepoReleases {
+    pub full_name: String,
+    pub releases: u32,
+    pub downloads: u64,
+    /// Tag of the most recently published release
+    pub latest_tag: Option<String>,
+}
+
+/// Counts the published releases of each repo and the downloads of their assets. Releases
+/// belong to the repo rather than to whoever made them, so one account's connection is enough.
+pub async fn collect_releases(
+    connection: &GitHub,
+    repos: &[RepoInfo],
+    concurrency: usize,
+) -> Result<ReleaseStats, Box<dyn Error>> {
+    let per_repo: Vec<Option<RepoReleases>> = futures::stream::iter(repos)
+        .map(|repo| repo_releases(connection, &repo.full_name))
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    let mut repos: Vec<RepoReleases> = per_repo.into_iter().flatten().collect();
+    repos.sort_by(|a, b| {
+        b.downloads
+            .cmp(&a.downloads)
+            .then(b.releases.cmp(&a.releases))
+    });
+    Ok(ReleaseStats {
+        releases: repos.iter().map(|repo| repo.releases).sum(),
+        downloads: repos.iter().map(|repo| repo.downloads).sum(),
+        repos,
+    })
+}
+
+/// `None` if the repo has no published releases.
+async fn repo_releases(
+    connection: &GitHub,
+    full_name: &str,
+) -> Result<Option<RepoReleases>, Box<dyn Error>> {
+    let url = connection.api_url(&format!("/repos/{full_name}/releases"));
+    let releases: Vec<ReleaseData> = match connection.get_all(url).await {
+        Ok(releases) => releases,
+        Err(MetricsError::NotFound { .. }) => {
+            debug!("No releases for {full_name}, it wasn't found");
+            return Ok(None);
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let mut published: Vec<ReleaseData> = releases
+        .into_iter()
+        .filter(|release| !release.draft)
+        .collect();
+    if published.is_empty() {
+        return Ok(None);
+    }
+    // RFC 3339 timestamps in UTC sort chronologically as strings
+    published.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+
+    Ok(Some(RepoReleases {
+        full_name: full_name.to_string(),
+        releases: published.len() as u32,
+        downloads: published
+            .iter()
+            .flat_map(|release| &release.assets)
+            .map(|asset| asset.download_count)
+            .sum(),
+        latest_tag: published.first().map(|release| release.tag_name.clone()),
+    }))
+}
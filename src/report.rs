@@ -0,0 +1,386 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    activity::ActivityStats,
+    commit_messages::CommitMessageStats,
+    commits::CommitStats,
+    containers::ContainerStats,
+    discussions::DiscussionStats,
+    ecosystems::EcosystemStats,
+    heatmap::CommitHeatmap,
+    issues::IssueStats,
+    licenses::LicenseStats,
+    org::MemberContribution,
+    packages::PackageStats,
+    profile::ProfileSummary,
+    pulls::PullRequestStats,
+    releases::ReleaseStats,
+    reviews::ReviewStats,
+    sponsors::SponsorStats,
+    streak::{ContributionStats, StreakStats},
+    traffic::TrafficStats,
+    wakatime::WakaTimeStats,
+};
+
+/// What the language percentages are shares of.
+#[derive(clap::ValueEnum, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LanguageRanking {
+    /// Lines of code
+    #[default]
+    Loc,
+    /// Stars, each repo's split between its languages by lines of code, to show which
+    /// languages are behind the most popular work
+    Stars,
+}
+
+/// How `--weighted` decides how much of a repo's code counts towards the user's languages.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeightMode {
+    /// Scale every language by the user's share of the repo's commits.
+    #[default]
+    Commits,
+    /// Count only the lines `git blame` attributes to the user's commits. Clones each repo
+    /// with its full history.
+    Blame,
+    /// Scale every language by the user's share of the lines added to the repo, from
+    /// `stats/contributors`.
+    Lines,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RepoInfo {
+    pub full_name: String,
+    pub language_loc_map: BTreeMap<String, u32>,
+    /// The user's share of the repo's commits, or of its added lines with `--weight-mode lines`
+    pub ratio_of_commits_from_user: f32,
+    pub stars: u32,
+    #[serde(default)]
+    pub forks: u32,
+    /// People watching the repo for notifications, not stargazers
+    #[serde(default)]
+    pub watchers: u32,
+    /// When the repo was last pushed to, as an RFC 3339 timestamp
+    #[serde(default)]
+    pub pushed_at: Option<String>,
+    /// When the repo was created, as an RFC 3339 timestamp
+    #[serde(default)]
+    pub created_at: Option<String>,
+    /// SPDX identifier of the repo's license, if it has one
+    #[serde(default)]
+    pub license: Option<String>,
+    /// Lines per language last changed by the user, with `--weight-mode blame`. Used instead of
+    /// scaling `language_loc_map` when weighting.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authored_lines: Option<BTreeMap<String, u32>>,
+    /// Package ecosystems the repo's manifests belong to, e.g. `Cargo` or `npm`. Only found with
+    /// `--loc-backend local`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ecosystems: Vec<String>,
+    /// Owned by another user who made the user an outside collaborator. Always weighted by the
+    /// user's share, since counting someone else's whole repo would overstate the user's work.
+    #[serde(default)]
+    pub collaborator: bool,
+}
+impl RepoInfo {
+    /// The language with the most lines of code.
+    pub fn primary_language(&self) -> Option<&str> {
+        self.language_loc_map
+            .iter()
+            .max_by_key(|(_, loc)| **loc)
+            .map(|(lang, _)| lang.as_str())
+    }
+
+    /// What the repo's languages and stars are scaled by in a report that is `weighted` or not.
+    pub fn weight(&self, weighted: bool) -> f32 {
+        if weighted || self.collaborator {
+            self.ratio_of_commits_from_user
+        } else {
+            1.0
+        }
+    }
+
+    pub fn weighted_stars(&self) -> f32 {
+        self.stars as f32 * self.ratio_of_commits_from_user
+    }
+
+    /// Each language's share of the repo's lines of code in percent, largest first.
+    pub fn language_percentages(&self) -> Vec<(&str, f32)> {
+        let total = self.language_loc_map.values().sum::<u32>().max(1) as f32;
+        let mut percents: Vec<_> = self
+            .language_loc_map
+            .iter()
+            .map(|(lang, loc)| (lang.as_str(), *loc as f32 / total * 100.0))
+            .collect();
+        percents.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        percents
+    }
+}
+
+/// The name of the group `lang` belongs to, or `lang` itself if it isn't in any.
+pub fn language_group(lang: String, groups: &[(String, Vec<String>)]) -> String {
+    groups
+        .iter()
+        .find(|(_, members)| {
+            members
+                .iter()
+                .any(|member| member.eq_ignore_ascii_case(&lang))
+        })
+        .map(|(group, _)| group.clone())
+        .unwrap_or(lang)
+}
+
+/// Folds languages into groups before aggregation, e.g. `("Web", ["CSS", "HTML"])`. Languages
+/// are matched case-insensitively, and the lines of every language in a group are added up.
+pub fn group_languages(repos: &mut [RepoInfo], groups: &[(String, Vec<String>)]) {
+    if groups.is_empty() {
+        return;
+    }
+    let regroup = |map: &mut BTreeMap<String, u32>| {
+        let mut grouped = BTreeMap::new();
+        for (lang, loc) in std::mem::take(map) {
+            *grouped.entry(language_group(lang, groups)).or_default() += loc;
+        }
+        *map = grouped;
+    };
+    for repo in repos {
+        regroup(&mut repo.language_loc_map);
+        if let Some(authored) = &mut repo.authored_lines {
+            regroup(authored);
+        }
+    }
+}
+
+/// Combines the repos found for several accounts into one list. A repo that more than one of
+/// the accounts contributed to is kept once, with their shares of its commits added together.
+pub fn merge_accounts(accounts: impl IntoIterator<Item = Vec<RepoInfo>>) -> Vec<RepoInfo> {
+    let mut merged: Vec<RepoInfo> = Vec::new();
+    for info in accounts.into_iter().flatten() {
+        if let Some(existing) = merged
+            .iter_mut()
+            .find(|existing| existing.full_name == info.full_name)
+        {
+            existing.ratio_of_commits_from_user =
+                (existing.ratio_of_commits_from_user + info.ratio_of_commits_from_user).min(1.0);
+            if let (Some(existing), Some(authored)) =
+                (&mut existing.authored_lines, info.authored_lines)
+            {
+                for (lang, lines) in authored {
+                    *existing.entry(lang).or_default() += lines;
+                }
+            }
+        } else {
+            merged.push(info);
+        }
+    }
+    merged
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Report {
+    /// One per analyzed GitHub account
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<ProfileSummary>,
+    pub weighted: bool,
+    /// What `languages` are shares of
+    #[serde(default)]
+    pub ranked_by: LanguageRanking,
+    pub languages: BTreeMap<String, f32>,
+    /// Lines of code per language, weighted like `languages`
+    #[serde(default)]
+    pub language_loc: BTreeMap<String, u64>,
+    /// Sum of `language_loc`
+    #[serde(default)]
+    pub total_loc: u64,
+    /// Lines of code per language before weighting, only filled in for weighted reports
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub unweighted_language_loc: BTreeMap<String, u64>,
+    /// Sum of `unweighted_language_loc`, only filled in for weighted reports
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unweighted_total_loc: Option<u64>,
+    pub total_stars: f32,
+    #[serde(default)]
+    pub total_forks: f32,
+    #[serde(default)]
+    pub total_watchers: f32,
+    pub repos: Vec<RepoInfo>,
+    /// Commits per member, only filled in for organization reports
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<MemberContribution>,
+    /// Only filled in with `--metrics commits`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commits: Option<CommitStats>,
+    /// Only filled in with `--metrics commit-messages`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_messages: Option<CommitMessageStats>,
+    /// Only filled in with `--metrics heatmap`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heatmap: Option<CommitHeatmap>,
+    /// Only filled in with `--metrics contributions`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contributions: Option<ContributionStats>,
+    /// Only filled in with `--metrics streak`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub streak: Option<StreakStats>,
+    /// Only filled in with `--metrics pull-requests`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull_requests: Option<PullRequestStats>,
+    /// Only filled in with `--metrics issues`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub issues: Option<IssueStats>,
+    /// Only filled in with `--metrics reviews`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reviews: Option<ReviewStats>,
+    /// Only filled in with `--metrics discussions`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub discussions: Option<DiscussionStats>,
+    /// Only filled in with `--metrics sponsorship`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sponsorship: Option<SponsorStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub licenses: Option<LicenseStats>,
+    /// Only filled in with `--metrics ecosystems`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ecosystems: Option<EcosystemStats>,
+    /// Only filled in with `--metrics activity`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<ActivityStats>,
+    /// Only filled in with `--metrics releases`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub releases: Option<ReleaseStats>,
+    /// Only filled in with `--metrics packages`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packages: Option<PackageStats>,
+    /// Only filled in with `--metrics containers`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub containers: Option<ContainerStats>,
+    /// Only filled in with `--metrics traffic`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traffic: Option<TrafficStats>,
+    /// Only filled in with `--wakatime-key`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wakatime: Option<WakaTimeStats>,
+}
+
+impl Report {
+    /// A report of `repos` with nothing summarized yet.
+    pub fn new(repos: Vec<RepoInfo>, weighted: bool) -> Self {
+        Report {
+            weighted,
+            repos,
+            ..Default::default()
+        }
+    }
+
+    /// The `n` repos with the most stars, or the most stars weighted by the user's share of
+    /// commits.
+    pub fn top_repos(&self, n: usize, weighted: bool) -> Vec<&RepoInfo> {
+        let mut repos: Vec<&RepoInfo> = self.repos.iter().collect();
+        let stars = |repo: &RepoInfo| repo.stars as f32 * repo.weight(weighted);
+        repos.sort_by(|a, b| stars(b).total_cmp(&stars(a)));
+        repos.truncate(n);
+        repos
+    }
+}
+
+pub fn build_report(repos_info: Vec<RepoInfo>, weighted: bool) -> Report {
+    let mut report = Report::new(repos_info, weighted);
+    summarize_languages(&mut report);
+    summarize_stars(&mut report);
+    report
+}
+
+/// Sums every repo's languages, weighted by the user's share of it if the report is weighted,
+/// into `languages`, `language_loc` and `total_loc`, and also unweighted if it is. With
+/// [`LanguageRanking::Stars`], `languages` are shares of the stars instead.
+pub fn summarize_languages(report: &mut Report) {
+    let weighted = report.weighted;
+    // Sum all language ratios into a new map
+    let mut langs_map: BTreeMap<String, f32> = BTreeMap::new();
+    for info in report.repos.iter() {
+        let (loc_map, ratio) = match &info.authored_lines {
+            Some(authored) if weighted => (authored, 1.0),
+            _ => (&info.language_loc_map, info.weight(weighted)),
+        };
+        for (lang, val) in loc_map.clone() {
+            let val = val as f32 * ratio;
+            if let Some(old) = langs_map.get(&lang) {
+                let new = old + val;
+                langs_map.insert(lang, new);
+            } else {
+                langs_map.insert(lang, val);
+            }
+        }
+    }
+
+    report.language_loc = langs_map
+        .iter()
+        .map(|(lang, loc)| (lang.clone(), loc.round() as u64))
+        .collect();
+    report.total_loc = report.language_loc.values().sum();
+
+    if weighted {
+        let mut unweighted: BTreeMap<String, u64> = BTreeMap::new();
+        for info in &report.repos {
+            for (lang, loc) in &info.language_loc_map {
+                *unweighted.entry(lang.clone()).or_default() += *loc as u64;
+            }
+        }
+        report.unweighted_total_loc = Some(unweighted.values().sum());
+        report.unweighted_language_loc = unweighted;
+    }
+
+    if report.ranked_by == LanguageRanking::Stars {
+        let star_map = language_stars(report);
+        // Without a single star there is nothing to rank by
+        if star_map.values().sum::<f32>() > 0.0 {
+            langs_map = star_map;
+        }
+    }
+
+    // Scale so that all values add to 100, unless there are no lines at all to scale
+    let sum_of_components = langs_map.values().sum::<f32>();
+    if sum_of_components <= 0.0 {
+        langs_map.clear();
+    }
+    let mut percent_map = BTreeMap::new();
+    for (lang, val) in langs_map {
+        let percent = (val / sum_of_components) * 100.0;
+        percent_map.insert(lang, percent);
+    }
+    report.languages = percent_map;
+}
+
+/// Each repo's stars, weighted like the languages, split between its languages by their share
+/// of its lines of code.
+fn language_stars(report: &Report) -> BTreeMap<String, f32> {
+    let mut stars: BTreeMap<String, f32> = BTreeMap::new();
+    for info in &report.repos {
+        let repo_loc = info.language_loc_map.values().sum::<u32>();
+        if repo_loc == 0 {
+            continue;
+        }
+        let repo_stars = info.stars as f32 * info.weight(report.weighted);
+        for (lang, loc) in &info.language_loc_map {
+            *stars.entry(lang.clone()).or_default() += repo_stars * *loc as f32 / repo_loc as f32;
+        }
+    }
+    stars
+}
+
+/// Totals the stars, forks and watchers of every repo, weighted like the languages.
+pub fn summarize_stars(report: &mut Report) {
+    let weighted = report.weighted;
+    let repos_info = &report.repos;
+    let weighted_total = |count: fn(&RepoInfo) -> u32| -> f32 {
+        repos_info
+            .iter()
+            .map(|info| count(info) as f32 * info.weight(weighted))
+            .sum()
+    };
+    report.total_stars = weighted_total(|info| info.stars);
+    report.total_forks = weighted_total(|info| info.forks);
+    report.total_watchers = weighted_total(|info| info.watchers);
+}
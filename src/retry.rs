@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::{RequestBuilder, Response};
+use tracing::warn;
+
+/// How often and how patiently to retry requests that failed for transient reasons: connection
+/// errors, timeouts, and 5xx responses.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// Delay before the first retry. Doubles with every attempt after that.
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter, so concurrent requests don't all retry at once.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        backoff.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+
+    /// Sends the request, retrying it if it fails transiently. The last response or error is
+    /// returned once retries run out.
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            // Requests without streaming bodies can always be cloned
+            let Some(retry) = request.try_clone().filter(|_| attempt < self.max_retries) else {
+                return request.send().await;
+            };
+            let reason = match retry.send().await {
+                Ok(response) if response.status().is_server_error() => {
+                    response.status().to_string()
+                }
+                Err(err) if err.is_timeout() || err.is_connect() || err.is_request() => {
+                    err.to_string()
+                }
+                result => return result,
+            };
+
+            let delay = self.delay(attempt);
+            attempt += 1;
+            warn!(
+                "Request failed ({reason}), retrying in {delay:?} (attempt {attempt}/{})...",
+                self.max_retries
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
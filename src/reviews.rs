@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{error::MetricsError, GitHub};
+
+const REVIEWS_QUERY: &str = r#"
+query($login: String!, $cursor: String) {
+  user(login: $login) {
+    contributionsCollection {
+      pullRequestReviewContributions(first: 100, after: $cursor) {
+        totalCount
+        pageInfo { hasNextPage endCursor }
+        nodes { pullRequestReview { comments { totalCount } } }
+      }
+    }
+  }
+}
+"#;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct ReviewStats {
+    /// Pull request reviews submitted over the last year
+    pub reviews: u32,
+    /// Comments left as part of those reviews
+    pub review_comments: u32,
+}
+impl ReviewStats {
+    /// Adds another account's reviews to these.
+    pub fn merge(&mut self, other: ReviewStats) {
+        self.reviews += other.reviews;
+        self.review_comments += other.review_comments;
+    }
+}
+
+/// Counts the user's pull request reviews from their contributions over the last year. Requires
+/// a token like the rest of the GraphQL API.
+pub async fn collect_reviews(connection: &GitHub) -> Result<ReviewStats, MetricsError> {
+    let mut stats = ReviewStats::default();
+    let mut cursor: Option<String> = None;
+    loop {
+        let data: ReviewsData = connection
+            .graphql(
+                REVIEWS_QUERY,
+                json!({ "login": connection.user, "cursor": cursor }),
+            )
+            .await?;
+        let contributions = data
+            .user
+            .contributions_collection
+            .pull_request_review_contributions;
+        stats.reviews = contributions.total_count;
+        stats.review_comments += contributions
+            .nodes
+            .iter()
+            .map(|node| node.pull_request_review.comments.total_count)
+            .sum::<u32>();
+
+        if !contributions.page_info.has_next_page {
+            break;
+        }
+        cursor = contributions.page_info.end_cursor;
+    }
+    Ok(stats)
+}
+
+#[derive(Deserialize)]
+struct ReviewsData {
+    user: ReviewsUser,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewsUser {
+    contributions_collection: ReviewsCollection,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewsCollection {
+    pull_request_review_contributions: ReviewContributions,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewContributions {
+    total_count: u32,
+    page_info: PageInfo,
+    nodes: Vec<ReviewContribution>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReviewContribution {
+    pull_request_review: Review,
+}
+
+#[derive(Deserialize)]
+struct Review {
+    comments: TotalCount,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TotalCount {
+    total_count: u32,
+}
@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap, error::Error, future::Future, net::SocketAddr, sync::Arc, time::Duration,
+};
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use futures::{stream::FuturesUnordered, StreamExt};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tokio::sync::{mpsc, oneshot, Notify, RwLock};
+use tracing::{info, warn};
+
+use crate::Report;
+
+type CollectResult = Result<String, String>;
+type CollectRequest = (String, oneshot::Sender<CollectResult>);
+
+#[derive(Clone)]
+struct AppState {
+    /// Lowercased names of the users whose stats are served
+    allowed_users: Arc<[String]>,
+    /// Serialized reports by lowercased user name
+    reports: Arc<RwLock<HashMap<String, String>>>,
+    requests: mpsc::Sender<CollectRequest>,
+    refresh_now: Arc<Notify>,
+    webhook_secret: Option<Arc<str>>,
+}
+
+/// Serves `GET /stats/{user}` with the JSON report for that user, if they're one of
+/// `allowed_users`. Others get 404 Not Found, so at most one report per allowed user is kept.
+///
+/// Reports are collected with `collect` the first time a user is requested, kept in memory, and
+/// recollected every `refresh_interval` or when `POST /refresh` is called, e.g. by a GitHub
+/// webhook. `POST /refresh` is only there with a `webhook_secret`, and must be signed with it
+/// like GitHub signs webhook deliveries. Collections run concurrently on the calling task, so `collect` doesn't need to be
+/// `Send`, and cached reports are served while they're refreshed.
+pub async fn serve<F, Fut>(
+    addr: SocketAddr,
+    allowed_users: &[String],
+    refresh_interval: Duration,
+    webhook_secret: Option<String>,
+    collect: F,
+) -> Result<(), Box<dyn Error>>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Report, Box<dyn Error>>>,
+{
+    let (requests, mut pending) = mpsc::channel::<CollectRequest>(64);
+    let state = AppState {
+        allowed_users: allowed_users
+            .iter()
+            .map(|user| user.to_ascii_lowercase())
+            .collect(),
+        reports: Arc::default(),
+        requests,
+        refresh_now: Arc::default(),
+        webhook_secret: webhook_secret.map(Arc::from),
+    };
+    let reports = state.reports.clone();
+    let refresh_now = state.refresh_now.clone();
+
+    let mut app = Router::new().route("/stats/:user", get(stats));
+    // Refreshing recollects every report, so anyone who can call it can use up the API quota
+    if state.webhook_secret.is_some() {
+        app = app.route("/refresh", post(refresh));
+    }
+    let app = app.with_state(state);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!("Serving stats on http://{addr}/stats/{{user}}");
+    let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let mut refresh = tokio::time::interval(refresh_interval);
+    // The first tick completes immediately and there's nothing to refresh yet
+    refresh.tick().await;
+    let mut running = FuturesUnordered::new();
+    // Who is waiting for each running collection, by user
+    let mut waiting: HashMap<String, Vec<oneshot::Sender<CollectResult>>> = HashMap::new();
+    loop {
+        let refreshing = tokio::select! {
+            request = pending.recv() => {
+                let Some((user, reply)) = request else {
+                    break;
+                };
+                let replies = waiting.entry(user.clone()).or_insert_with(|| {
+                    running.push(collect_json(&collect, user));
+                    Vec::new()
+                });
+                replies.push(reply);
+                continue;
+            }
+            Some((user, result)) = running.next() => {
+                match &result {
+                    Ok(json) => {
+                        reports.write().await.insert(user.clone(), json.clone());
+                    }
+                    Err(err) => warn!("Failed to collect stats for {user}: {err}"),
+                }
+                for reply in waiting.remove(&user).unwrap_or_default() {
+                    let _ = reply.send(result.clone());
+                }
+                continue;
+            }
+            _ = refresh.tick() => reports.read().await.keys().cloned().collect::<Vec<_>>(),
+            _ = refresh_now.notified() => {
+                // Wait a full interval before the next scheduled refresh
+                refresh.reset();
+                reports.read().await.keys().cloned().collect()
+            }
+        };
+        for user in refreshing {
+            // Users already being collected get the newer report anyway
+            if !waiting.contains_key(&user) {
+                info!("Refreshing stats for {user}...");
+                waiting.insert(user.clone(), Vec::new());
+                running.push(collect_json(&collect, user));
+            }
+        }
+    }
+
+    server.await??;
+    Ok(())
+}
+
+async fn collect_json<F, Fut>(collect: &F, user: String) -> (String, CollectResult)
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Report, Box<dyn Error>>>,
+{
+    let result = match collect(user.clone()).await {
+        Ok(report) => serde_json::to_string(&report).map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+    (user, result)
+}
+
+async fn stats(State(state): State<AppState>, Path(user): Path<String>) -> Response {
+    let user = user.to_ascii_lowercase();
+    if !state.allowed_users.contains(&user) {
+        return (StatusCode::NOT_FOUND, "Not serving stats for this user").into_response();
+    }
+    if let Some(json) = state.reports.read().await.get(&user) {
+        return json_response(json.clone());
+    }
+
+    let (reply, response) = oneshot::channel();
+    if state.requests.send((user, reply)).await.is_err() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "Server is shutting down").into_response();
+    }
+    match response.await {
+        Ok(Ok(json)) => json_response(json),
+        Ok(Err(err)) => (StatusCode::BAD_GATEWAY, err).into_response(),
+        Err(_) => (StatusCode::SERVICE_UNAVAILABLE, "Server is shutting down").into_response(),
+    }
+}
+
+async fn refresh(State(state): State<AppState>, headers: HeaderMap, body: Bytes) -> Response {
+    let signed = state.webhook_secret.as_ref().is_some_and(|secret| {
+        headers
+            .get("X-Hub-Signature-256")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|signature| verify_signature(secret, &body, signature))
+    });
+    if !signed {
+        return (StatusCode::UNAUTHORIZED, "Invalid webhook signature").into_response();
+    }
+    // Refreshes requested while one is running are coalesced into a single one after it
+    state.refresh_now.notify_one();
+    (StatusCode::ACCEPTED, "Refresh scheduled").into_response()
+}
+
+/// Checks a `sha256=<hex HMAC of the body>` signature, as sent by GitHub webhooks.
+fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(digest) = signature
+        .strip_prefix("sha256=")
+        .and_then(|digest| hex::decode(digest).ok())
+    else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&digest).is_ok()
+}
+
+fn json_response(json: String) -> Response {
+    ([(header::CONTENT_TYPE, "application/json")], json).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // From GitHub's webhook documentation
+    const SECRET: &str = "It's a Secret to Everybody";
+    const BODY: &[u8] = b"Hello, World!";
+    const SIGNATURE: &str =
+        "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+    #[test]
+    fn valid_signatures_are_accepted() {
+        assert!(verify_signature(SECRET, BODY, SIGNATURE));
+    }
+
+    #[test]
+    fn invalid_signatures_are_rejected() {
+        assert!(!verify_signature("another secret", BODY, SIGNATURE));
+        assert!(!verify_signature(SECRET, b"Hello, World?", SIGNATURE));
+        assert!(!verify_signature(SECRET, BODY, &SIGNATURE[7..]));
+        assert!(!verify_signature(SECRET, BODY, "sha256=not hex"));
+        assert!(!verify_signature(SECRET, BODY, ""));
+    }
+}
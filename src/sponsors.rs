@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{error::MetricsError, GitHub};
+
+const SPONSORS_QUERY: &str = r#"
+query($login: String!, $cursor: String) {
+  user(login: $login) {
+    sponsorshipsAsMaintainer(first: 100, after: $cursor, activeOnly: true) {
+      totalCount
+      pageInfo { hasNextPage endCursor }
+      nodes { isOneTimePayment tier { name monthlyPriceInCents } }
+    }
+  }
+}
+"#;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct SponsorStats {
+    /// Active sponsors, one-time ones included
+    pub sponsors: u32,
+    /// What the monthly sponsorships add up to each month, in US cents
+    pub monthly_cents: u64,
+    /// Monthly tiers with at least one sponsor, most earning first
+    pub tiers: Vec<SponsorTier>,
+}
+impl SponsorStats {
+    /// Adds another account's sponsors to these.
+    pub fn merge(&mut self, other: SponsorStats) {
+        self.sponsors += other.sponsors;
+        self.monthly_cents += other.monthly_cents;
+        for tier in other.tiers {
+            self.add_to_tier(&tier.name, tier.monthly_cents, tier.sponsors);
+        }
+        self.sort_tiers();
+    }
+
+    /// The monthly total in dollars, e.g. `$125.00`.
+    pub fn monthly_dollars(&self) -> String {
+        dollars(self.monthly_cents)
+    }
+
+    fn add_to_tier(&mut self, name: &str, monthly_cents: u64, sponsors: u32) {
+        match self
+            .tiers
+            .iter_mut()
+            .find(|tier| tier.name == name && tier.monthly_cents == monthly_cents)
+        {
+            Some(tier) => tier.sponsors += sponsors,
+            None => self.tiers.push(SponsorTier {
+                name: name.to_string(),
+                monthly_cents,
+                sponsors,
+            }),
+        }
+    }
+
+    fn sort_tiers(&mut self) {
+        self.tiers
+            .sort_by_key(|tier| std::cmp::Reverse(tier.total_cents()));
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SponsorTier {
+    /// e.g. `$5 a month`
+    pub name: String,
+    /// Price of the tier, in US cents
+    pub monthly_cents: u64,
+    pub sponsors: u32,
+}
+impl SponsorTier {
+    /// What the tier's sponsors add up to each month, in US cents.
+    pub fn total_cents(&self) -> u64 {
+        self.monthly_cents * self.sponsors as u64
+    }
+}
+
+/// Formats US cents as dollars, e.g. `$5.00`.
+pub fn dollars(cents: u64) -> String {
+    format!("${}.{:02}", cents / 100, cents % 100)
+}
+
+/// Counts the user's active sponsors and what their monthly tiers add up to. Tiers of
+/// sponsorships the token can't see, like private ones when it isn't the user's own, are left
+/// out of the totals but the sponsors still count.
+pub async fn collect_sponsors(connection: &GitHub) -> Result<SponsorStats, MetricsError> {
+    let mut stats = SponsorStats::default();
+    let mut cursor: Option<String> = None;
+    loop {
+        let data: SponsorsData = connection
+            .graphql(
+                SPONSORS_QUERY,
+                json!({ "login": connection.user, "cursor": cursor }),
+            )
+            .await?;
+        let sponsorships = data.user.sponsorships_as_maintainer;
+        stats.sponsors = sponsorships.total_count;
+        for sponsorship in sponsorships.nodes {
+            let Some(tier) = sponsorship
+                .tier
+                .filter(|_| !sponsorship.is_one_time_payment)
+            else {
+                continue;
+            };
+            stats.monthly_cents += tier.monthly_price_in_cents;
+            stats.add_to_tier(&tier.name, tier.monthly_price_in_cents, 1);
+        }
+
+        if !sponsorships.page_info.has_next_page {
+            break;
+        }
+        cursor = sponsorships.page_info.end_cursor;
+    }
+    stats.sort_tiers();
+    Ok(stats)
+}
+
+#[derive(Deserialize)]
+struct SponsorsData {
+    user: SponsorsUser,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SponsorsUser {
+    sponsorships_as_maintainer: Sponsorships,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Sponsorships {
+    total_count: u32,
+    page_info: PageInfo,
+    nodes: Vec<Sponsorship>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Sponsorship {
+    is_one_time_payment: bool,
+    tier: Option<Tier>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Tier {
+    name: String,
+    monthly_price_in_cents: u64,
+}
@@ -0,0 +1,110 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{commits::recent_months, error::MetricsError, GitHub};
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StarHistory {
+    pub full_name: String,
+    /// Stars from before the first month shown
+    pub earlier: u32,
+    /// New stars per `YYYY-MM` month, including months without any
+    pub per_month: BTreeMap<String, u32>,
+}
+impl StarHistory {
+    pub fn total(&self) -> u32 {
+        self.earlier + self.per_month.values().sum::<u32>()
+    }
+}
+
+#[derive(Deserialize)]
+struct StargazerData {
+    starred_at: String,
+}
+
+/// New stars per month for the last `months` months, from when each stargazer starred the repo.
+pub async fn star_history(
+    connection: &GitHub,
+    full_name: &str,
+    months: i32,
+) -> Result<StarHistory, MetricsError> {
+    let stargazers: Vec<StargazerData> = connection
+        .get_all_as(
+            connection.api_url(&format!("/repos/{full_name}/stargazers")),
+            Some("application/vnd.github.star+json"),
+        )
+        .await?;
+    debug!("Found {} stargazers for {full_name}", stargazers.len());
+
+    let mut history = StarHistory {
+        full_name: full_name.to_string(),
+        earlier: 0,
+        per_month: recent_months(months)
+            .into_iter()
+            .map(|month| (month, 0))
+            .collect(),
+    };
+    for stargazer in stargazers {
+        let month = stargazer.starred_at.get(..7).unwrap_or_default();
+        match history.per_month.get_mut(month) {
+            Some(count) => *count += 1,
+            None => history.earlier += 1,
+        }
+    }
+    Ok(history)
+}
+
+/// One row per repo with its new stars in each month.
+pub fn render_table(histories: &[StarHistory]) -> String {
+    let mut out = String::new();
+    let Some(first) = histories.first() else {
+        return out;
+    };
+    write!(out, "{:<40} {:>7}", "Repository", "Total").unwrap();
+    for month in first.per_month.keys() {
+        // `YY-MM` keeps the columns narrow
+        write!(out, " {:>6}", month.get(2..).unwrap_or(month)).unwrap();
+    }
+    writeln!(out).unwrap();
+    for history in histories {
+        write!(out, "{:<40} {:>7}", history.full_name, history.total()).unwrap();
+        for count in history.per_month.values() {
+            write!(out, " {count:>6}").unwrap();
+        }
+        writeln!(out).unwrap();
+    }
+    out
+}
+
+/// One sparkline of new stars per month for each repo, from the first month to the last.
+pub fn render_sparklines(histories: &[StarHistory]) -> String {
+    let mut out = String::new();
+    for history in histories {
+        let counts: Vec<u32> = history.per_month.values().copied().collect();
+        writeln!(
+            out,
+            "{:<40} {} {:>6} new, {} total",
+            history.full_name,
+            sparkline(&counts),
+            counts.iter().sum::<u32>(),
+            history.total()
+        )
+        .unwrap();
+    }
+    out
+}
+
+fn sparkline(values: &[u32]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0).max(1) as f32;
+    values
+        .iter()
+        .map(|&value| {
+            let level = (value as f32 / max * (SPARK_LEVELS.len() - 1) as f32).round() as usize;
+            SPARK_LEVELS[level]
+        })
+        .collect()
+}
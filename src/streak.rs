@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{error::MetricsError, GitHub};
+
+const CALENDAR_QUERY: &str = r#"
+query($login: String!) {
+  user(login: $login) {
+    contributionsCollection {
+      contributionCalendar {
+        totalContributions
+        weeks { contributionDays { date contributionCount } }
+      }
+    }
+  }
+}
+"#;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct StreakStats {
+    /// Contributions over the last year
+    pub total_contributions: u32,
+    /// Days in a row with contributions up to today. Today doesn't break the streak until it's
+    /// over.
+    pub current_streak: u32,
+    pub longest_streak: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ContributionStats {
+    /// Contributions over the last year, as shown on the profile page
+    pub total: u32,
+    /// Contributions per `YYYY-MM` month. The first month is usually only partly covered
+    pub per_month: BTreeMap<String, u32>,
+}
+impl ContributionStats {
+    /// Adds another account's contributions to these.
+    pub fn merge(&mut self, other: ContributionStats) {
+        self.total += other.total;
+        for (month, count) in other.per_month {
+            *self.per_month.entry(month).or_default() += count;
+        }
+    }
+}
+
+/// The total contributions over the last year, and the contributions per `YYYY-MM-DD` day,
+/// from the profile contribution calendar. Requires a token like the rest of the GraphQL API.
+pub async fn contribution_calendar(
+    connection: &GitHub,
+) -> Result<(u32, BTreeMap<String, u32>), MetricsError> {
+    let data: CalendarData = connection
+        .graphql(CALENDAR_QUERY, json!({ "login": connection.user }))
+        .await?;
+    let calendar = data.user.contributions_collection.contribution_calendar;
+    let days = calendar
+        .weeks
+        .into_iter()
+        .flat_map(|week| week.contribution_days)
+        .map(|day| (day.date, day.contribution_count))
+        .collect();
+    Ok((calendar.total_contributions, days))
+}
+
+/// Sums a calendar from [`contribution_calendar`] up per month.
+pub fn contribution_stats(total: u32, days: &BTreeMap<String, u32>) -> ContributionStats {
+    let mut per_month: BTreeMap<String, u32> = BTreeMap::new();
+    for (day, count) in days {
+        *per_month
+            .entry(day.get(..7).unwrap_or_default().to_string())
+            .or_default() += count;
+    }
+    ContributionStats { total, per_month }
+}
+
+/// Computes streaks from a calendar of every day in order, as returned by
+/// [`contribution_calendar`].
+pub fn streak_stats(days: &BTreeMap<String, u32>) -> StreakStats {
+    let counts: Vec<u32> = days.values().copied().collect();
+
+    let mut longest_streak = 0;
+    let mut streak = 0;
+    for &count in &counts {
+        streak = if count > 0 { streak + 1 } else { 0 };
+        longest_streak = longest_streak.max(streak);
+    }
+
+    let mut recent = counts.iter().rev().peekable();
+    // Today may just not have any contributions yet
+    recent.next_if(|&&count| count == 0);
+    let current_streak = recent.take_while(|&&count| count > 0).count() as u32;
+
+    StreakStats {
+        total_contributions: counts.iter().sum(),
+        current_streak,
+        longest_streak,
+    }
+}
+
+#[derive(Deserialize)]
+struct CalendarData {
+    user: CalendarUser,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CalendarUser {
+    contributions_collection: ContributionsCollection,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionsCollection {
+    contribution_calendar: ContributionCalendar,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionCalendar {
+    total_contributions: u32,
+    weeks: Vec<ContributionWeek>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionWeek {
+    contribution_days: Vec<ContributionDay>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ContributionDay {
+    date: String,
+    contribution_count: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calendar(counts: &[u32]) -> BTreeMap<String, u32> {
+        counts
+            .iter()
+            .enumerate()
+            .map(|(day, count)| (format!("2024-01-{:02}", day + 1), *count))
+            .collect()
+    }
+
+    #[test]
+    fn streaks_count_days_in_a_row() {
+        let stats = streak_stats(&calendar(&[1, 2, 3, 0, 1, 1, 4]));
+        assert_eq!(
+            stats,
+            StreakStats {
+                total_contributions: 12,
+                current_streak: 3,
+                longest_streak: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn today_without_contributions_keeps_the_streak() {
+        assert_eq!(streak_stats(&calendar(&[1, 1, 0])).current_streak, 2);
+        assert_eq!(streak_stats(&calendar(&[1, 0, 0])).current_streak, 0);
+        assert_eq!(streak_stats(&BTreeMap::new()), StreakStats::default());
+    }
+
+    #[test]
+    fn contributions_are_summed_per_month() {
+        let days = BTreeMap::from([
+            ("2024-01-30".to_string(), 2),
+            ("2024-01-31".to_string(), 3),
+            ("2024-02-01".to_string(), 4),
+        ]);
+        let mut stats = contribution_stats(9, &days);
+        assert_eq!(
+            stats.per_month,
+            BTreeMap::from([("2024-01".to_string(), 5), ("2024-02".to_string(), 4)])
+        );
+
+        let other = BTreeMap::from([("2024-02-02".to_string(), 4)]);
+        stats.merge(contribution_stats(4, &other));
+        assert_eq!(stats.total, 13);
+        assert_eq!(stats.per_month["2024-02"], 8);
+    }
+}
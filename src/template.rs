@@ -0,0 +1,27 @@
+use std::{error::Error, fs, path::Path};
+
+use tera::{Context, Tera};
+
+use crate::Report;
+
+/// Renders the user's Tera template at `path` with every field of the report as a variable, e.g.
+/// `{{ total_stars }}` or `{% for repo in repos %}`. Templates ending in `.html` are escaped
+/// like Tera does by default.
+pub fn render_template(report: &Report, path: &Path) -> Result<String, Box<dyn Error>> {
+    let template = fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read template {}: {err}", path.display()))?;
+    let context = Context::from_serialize(report)?;
+    let escape = path
+        .extension()
+        .is_some_and(|extension| extension == "html" || extension == "htm");
+    Tera::one_off(&template, &context, escape).map_err(|err| {
+        // Tera's own message only names the template, the cause is further down the chain
+        let mut message = format!("Invalid template {}", path.display());
+        let mut source: Option<&dyn Error> = err.source();
+        while let Some(err) = source {
+            message.push_str(&format!(": {err}"));
+            source = err.source();
+        }
+        message.into()
+    })
+}
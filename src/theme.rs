@@ -0,0 +1,80 @@
+use std::{error::Error, fs, path::Path};
+
+use serde::Deserialize;
+
+/// Colors of the stats card. Custom themes are TOML files with these keys, e.g.
+/// `background = "#282a36"`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Theme {
+    pub background: String,
+    pub title: String,
+    pub text: String,
+    /// Outline of the card, the background color if not given
+    #[serde(default)]
+    pub border: Option<String>,
+    /// Colors for the languages, in order of use, instead of their GitHub colors
+    #[serde(default)]
+    pub bars: Vec<String>,
+}
+
+/// Built-in themes as `(name, [background, title, text, border])`, with the colors of
+/// github-readme-stats' themes of the same name.
+const THEMES: &[(&str, [&str; 4])] = &[
+    ("default", ["#fffefe", "#2f80ed", "#434d58", "#e4e2e2"]),
+    ("light", ["#fffefe", "#2f80ed", "#434d58", "#e4e2e2"]),
+    ("dark", ["#151515", "#ffffff", "#9f9f9f", "#e4e2e2"]),
+    ("radical", ["#141321", "#fe428e", "#a9fef7", "#141321"]),
+    ("dracula", ["#282a36", "#ff6e96", "#f8f8f2", "#282a36"]),
+    ("gruvbox", ["#282828", "#fabd2f", "#8ec07c", "#282828"]),
+    ("tokyonight", ["#1a1b27", "#70a5fd", "#38bdae", "#1a1b27"]),
+    ("onedark", ["#282c34", "#e4bf7a", "#df6d74", "#282c34"]),
+    ("nord", ["#2e3440", "#81a1c1", "#d8dee9", "#2e3440"]),
+];
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::named("default").unwrap()
+    }
+}
+
+impl Theme {
+    pub fn named(name: &str) -> Option<Self> {
+        let (_, [background, title, text, border]) = THEMES
+            .iter()
+            .find(|(theme, _)| theme.eq_ignore_ascii_case(name))?;
+        Some(Self {
+            background: background.to_string(),
+            title: title.to_string(),
+            text: text.to_string(),
+            border: Some(border.to_string()),
+            bars: Vec::new(),
+        })
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        THEMES.iter().map(|(name, _)| *name)
+    }
+
+    /// A built-in theme by name, or else a custom theme file at `theme`.
+    pub fn load(theme: &str) -> Result<Self, Box<dyn Error>> {
+        if let Some(named) = Self::named(theme) {
+            return Ok(named);
+        }
+        let path = Path::new(theme);
+        if !path.is_file() {
+            return Err(format!(
+                "Unknown theme `{theme}`, expected a theme file or one of {}",
+                Self::names().collect::<Vec<_>>().join(", ")
+            )
+            .into());
+        }
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read theme {}: {err}", path.display()))?;
+        Ok(toml::from_str(&contents)
+            .map_err(|err| format!("Invalid theme {}: {err}", path.display()))?)
+    }
+
+    pub fn border(&self) -> &str {
+        self.border.as_deref().unwrap_or(&self.background)
+    }
+}
@@ -0,0 +1,73 @@
+use std::{env, process::Command};
+
+use keyring::Entry;
+use tracing::{debug, warn};
+
+const KEYRING_SERVICE: &str = "github-user-scraper";
+const KEYRING_USER: &str = "github-token";
+
+/// Finds a GitHub token when `--token` isn't given: from `GITHUB_TOKEN`, then `GH_TOKEN`, then
+/// the OS keyring, then the GitHub CLI's `gh auth token`.
+///
+/// For a GitHub Enterprise Server `host`, `GH_ENTERPRISE_TOKEN` and `GITHUB_ENTERPRISE_TOKEN`
+/// are read instead, like `gh` does, and `gh` is asked for that host's token.
+pub fn discover_token(host: Option<&str>) -> Option<String> {
+    let vars = match host {
+        Some(_) => ["GH_ENTERPRISE_TOKEN", "GITHUB_ENTERPRISE_TOKEN"],
+        None => ["GITHUB_TOKEN", "GH_TOKEN"],
+    };
+    for var in vars {
+        if let Some(token) = env::var(var).ok().filter(|token| !token.trim().is_empty()) {
+            debug!("Using the token from {var}");
+            return Some(token.trim().to_string());
+        }
+    }
+
+    if let Some(token) = keyring_token(host) {
+        debug!("Using the token from the keyring");
+        return Some(token);
+    }
+
+    // Missing or logged out `gh` just means there's no token
+    let mut gh = Command::new("gh");
+    gh.args(["auth", "token"]);
+    if let Some(host) = host {
+        gh.args(["--hostname", host]);
+    }
+    let output = gh.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let token = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    if token.is_empty() {
+        return None;
+    }
+    debug!("Using the token from `gh auth token`");
+    Some(token)
+}
+
+/// Stores the token in the OS keyring, where [`discover_token`] finds it on later runs. Tokens
+/// of Enterprise Server hosts are kept apart from the github.com one.
+pub fn save_token(token: &str, host: Option<&str>) -> keyring::Result<()> {
+    Entry::new(KEYRING_SERVICE, &keyring_user(host))?.set_password(token)
+}
+
+fn keyring_user(host: Option<&str>) -> String {
+    match host {
+        Some(host) => format!("{KEYRING_USER}@{host}"),
+        None => KEYRING_USER.to_string(),
+    }
+}
+
+fn keyring_token(host: Option<&str>) -> Option<String> {
+    let entry = Entry::new(KEYRING_SERVICE, &keyring_user(host)).ok()?;
+    match entry.get_password() {
+        Ok(token) => Some(token),
+        Err(keyring::Error::NoEntry) => None,
+        // E.g. no secret service running on a headless Linux machine
+        Err(err) => {
+            warn!("Couldn't read the token from the keyring: {err}");
+            None
+        }
+    }
+}
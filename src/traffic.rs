@@ -0,0 +1,108 @@
+use std::{collections::BTreeMap, error::Error};
+
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::{models::TrafficData, GitHub, RepoInfo};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct TrafficStats {
+    /// Views of every repo over the last 14 days
+    pub views: u64,
+    pub clones: u64,
+    /// Repos the token can see the traffic of, most viewed first
+    pub repos: Vec<RepoTraffic>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RepoTraffic {
+    pub full_name: String,
+    pub views: u64,
+    pub unique_visitors: u64,
+    pub clones: u64,
+    pub unique_cloners: u64,
+    /// Days with any views or clones, oldest first, so runs can be stitched together past the
+    /// 14 days GitHub keeps
+    pub days: Vec<TrafficDay>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrafficDay {
+    /// e.g. `2024-05-01`
+    pub date: String,
+    pub views: u64,
+    pub unique_visitors: u64,
+    pub clones: u64,
+    pub unique_cloners: u64,
+}
+
+/// Collects the last 14 days of views and clones of each repo. GitHub only shows traffic to
+/// those who can push to the repo, so the others are left out.
+pub async fn collect_traffic(
+    connection: &GitHub,
+    repos: &[RepoInfo],
+    concurrency: usize,
+) -> Result<TrafficStats, Box<dyn Error>> {
+    let per_repo: Vec<Option<RepoTraffic>> = futures::stream::iter(repos)
+        .map(|repo| repo_traffic(connection, &repo.full_name))
+        .buffered(concurrency.max(1))
+        .try_collect()
+        .await?;
+
+    let mut repos: Vec<RepoTraffic> = per_repo.into_iter().flatten().collect();
+    repos.sort_by(|a, b| b.views.cmp(&a.views).then(b.clones.cmp(&a.clones)));
+    Ok(TrafficStats {
+        views: repos.iter().map(|repo| repo.views).sum(),
+        clones: repos.iter().map(|repo| repo.clones).sum(),
+        repos,
+    })
+}
+
+/// `None` if the token can't see the repo's traffic.
+async fn repo_traffic(
+    connection: &GitHub,
+    full_name: &str,
+) -> Result<Option<RepoTraffic>, Box<dyn Error>> {
+    let Some(views) = connection.traffic(full_name, "views").await? else {
+        debug!("No access to the traffic of {full_name}");
+        return Ok(None);
+    };
+    let Some(clones) = connection.traffic(full_name, "clones").await? else {
+        return Ok(None);
+    };
+
+    Ok(Some(RepoTraffic {
+        full_name: full_name.to_string(),
+        views: views.count,
+        unique_visitors: views.uniques,
+        clones: clones.count,
+        unique_cloners: clones.uniques,
+        days: traffic_days(&views, &clones),
+    }))
+}
+
+/// Views and clones per day, merged by date.
+fn traffic_days(views: &TrafficData, clones: &TrafficData) -> Vec<TrafficDay> {
+    let mut days: BTreeMap<&str, TrafficDay> = BTreeMap::new();
+    let empty_day = |date: &str| TrafficDay {
+        date: date.to_string(),
+        views: 0,
+        unique_visitors: 0,
+        clones: 0,
+        unique_cloners: 0,
+    };
+    for view in &views.days {
+        let date = view.timestamp.get(..10).unwrap_or(&view.timestamp);
+        let day = days.entry(date).or_insert_with(|| empty_day(date));
+        day.views = view.count;
+        day.unique_visitors = view.uniques;
+    }
+    for clone in &clones.days {
+        let date = clone.timestamp.get(..10).unwrap_or(&clone.timestamp);
+        let day = days.entry(date).or_insert_with(|| empty_day(date));
+        day.clones = clone.count;
+        day.unique_cloners = clone.uniques;
+    }
+    days.into_values().collect()
+}
@@ -0,0 +1,371 @@
+use std::{
+    error::Error,
+    future::Future,
+    io::{self, Stdout, Write},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use ratatui::{
+    backend::CrosstermBackend,
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Row, Table, TableState},
+    Frame, Terminal,
+};
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::{
+    colors::{language_color_or_fallback, parse_hex},
+    report::{summarize_languages, summarize_stars},
+    RepoInfo, Report,
+};
+
+/// How many log lines are kept for the progress screen.
+const LOG_LINES: usize = 200;
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+const BAR_WIDTH: usize = 20;
+
+/// Keeps log lines in memory so they can be shown in the dashboard instead of being written
+/// over it. Pass it to the tracing subscriber as the writer.
+#[derive(Clone, Default)]
+pub struct LogBuffer(Arc<Mutex<Vec<String>>>);
+
+impl LogBuffer {
+    fn tail(&self, count: usize) -> Vec<String> {
+        let lines = self.0.lock().unwrap();
+        lines[lines.len().saturating_sub(count)..].to_vec()
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogBuffer {
+    type Writer = LogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogWriter(self.clone())
+    }
+}
+
+pub struct LogWriter(LogBuffer);
+
+impl Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut lines = self.0 .0.lock().unwrap();
+        lines.extend(
+            String::from_utf8_lossy(buf)
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(str::to_string),
+        );
+        let excess = lines.len().saturating_sub(LOG_LINES);
+        lines.drain(..excess);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Stars,
+    Contribution,
+    Name,
+}
+impl SortKey {
+    fn next(self) -> Self {
+        match self {
+            Self::Stars => Self::Contribution,
+            Self::Contribution => Self::Name,
+            Self::Name => Self::Stars,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Stars => "stars",
+            Self::Contribution => "contribution",
+            Self::Name => "name",
+        }
+    }
+}
+
+struct App {
+    report: Report,
+    weighted: bool,
+    sort: SortKey,
+    table: TableState,
+}
+impl App {
+    fn new(report: Report) -> Self {
+        let mut app = Self {
+            weighted: report.weighted,
+            report,
+            sort: SortKey::Stars,
+            table: TableState::default().with_selected(Some(0)),
+        };
+        app.sort_repos();
+        app
+    }
+
+    fn sort_repos(&mut self) {
+        let repos = &mut self.report.repos;
+        match self.sort {
+            SortKey::Stars => repos.sort_by_key(|repo| std::cmp::Reverse(repo.stars)),
+            SortKey::Contribution => repos.sort_by(|a, b| {
+                b.ratio_of_commits_from_user
+                    .total_cmp(&a.ratio_of_commits_from_user)
+            }),
+            SortKey::Name => repos.sort_by(|a, b| a.full_name.cmp(&b.full_name)),
+        }
+    }
+
+    /// Recomputes the language totals with weighting switched, keeping everything else.
+    fn toggle_weighting(&mut self) {
+        self.weighted = !self.weighted;
+        let repos = std::mem::take(&mut self.report.repos);
+        let mut rebuilt = Report {
+            ranked_by: self.report.ranked_by,
+            ..Report::new(repos, self.weighted)
+        };
+        summarize_languages(&mut rebuilt);
+        summarize_stars(&mut rebuilt);
+        self.report.weighted = rebuilt.weighted;
+        self.report.languages = rebuilt.languages;
+        self.report.language_loc = rebuilt.language_loc;
+        self.report.total_stars = rebuilt.total_stars;
+        self.report.total_forks = rebuilt.total_forks;
+        self.report.total_watchers = rebuilt.total_watchers;
+        self.report.repos = rebuilt.repos;
+    }
+
+    fn select(&mut self, offset: isize) {
+        if self.report.repos.is_empty() {
+            return;
+        }
+        let last = self.report.repos.len() as isize - 1;
+        let selected = self.table.selected().unwrap_or(0) as isize;
+        self.table
+            .select(Some((selected + offset).clamp(0, last) as usize));
+    }
+
+    fn selected_repo(&self) -> Option<&RepoInfo> {
+        self.report.repos.get(self.table.selected()?)
+    }
+}
+
+/// Restores the terminal when dropped, including when collection fails.
+struct TerminalGuard;
+impl TerminalGuard {
+    fn enter() -> io::Result<(Self, Terminal<CrosstermBackend<Stdout>>)> {
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        Ok((Self, Terminal::new(CrosstermBackend::new(io::stdout()))?))
+    }
+}
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+/// Shows the log while `collect` runs, then lets the user browse the repos in the report.
+///
+/// Collection runs on the calling task, so `collect` doesn't need to be `Send`.
+pub async fn run<Fut>(collect: Fut, logs: LogBuffer) -> Result<(), Box<dyn Error>>
+where
+    Fut: Future<Output = Result<Report, Box<dyn Error>>>,
+{
+    let (_guard, mut terminal) = TerminalGuard::enter()?;
+
+    tokio::pin!(collect);
+    let mut tick = tokio::time::interval(Duration::from_millis(100));
+    let mut frame_count = 0;
+    let report = loop {
+        tokio::select! {
+            result = &mut collect => break result?,
+            _ = tick.tick() => {
+                frame_count += 1;
+                terminal.draw(|frame| draw_progress(frame, &logs, frame_count))?;
+                while event::poll(Duration::ZERO)? {
+                    if let Event::Key(key) = event::read()? {
+                        if is_quit(key) {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+        }
+    };
+
+    let mut app = App::new(report);
+    loop {
+        terminal.draw(|frame| draw_dashboard(frame, &mut app))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if is_quit(key) {
+            return Ok(());
+        }
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => app.select(-1),
+            KeyCode::Down | KeyCode::Char('j') => app.select(1),
+            KeyCode::PageUp => app.select(-10),
+            KeyCode::PageDown => app.select(10),
+            KeyCode::Char('s') => {
+                app.sort = app.sort.next();
+                app.sort_repos();
+            }
+            KeyCode::Char('w') => app.toggle_weighting(),
+            _ => {}
+        }
+    }
+}
+
+fn is_quit(key: KeyEvent) -> bool {
+    key.kind == KeyEventKind::Press
+        && (matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+            || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)))
+}
+
+fn draw_progress(frame: &mut Frame, logs: &LogBuffer, frame_count: usize) {
+    let [header, body] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(frame.area());
+    frame.render_widget(
+        Paragraph::new(format!(
+            "{} Collecting repos... (q to quit)",
+            SPINNER[frame_count % SPINNER.len()]
+        )),
+        header,
+    );
+    let lines: Vec<Line> = logs
+        .tail(body.height.saturating_sub(2) as usize)
+        .into_iter()
+        .map(Line::from)
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Log")),
+        body,
+    );
+}
+
+fn draw_dashboard(frame: &mut Frame, app: &mut App) {
+    let [header, body, help] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [repos, details] =
+        Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(body);
+    let [repo_languages, all_languages] =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(details);
+
+    frame.render_widget(
+        Paragraph::new(format!(
+            "{} repos · {:.0} stars · weighting {} · sorted by {}",
+            app.report.repos.len(),
+            app.report.total_stars,
+            if app.weighted { "on" } else { "off" },
+            app.sort.label()
+        )),
+        header,
+    );
+    frame.render_widget(
+        Paragraph::new("↑/↓ select · s sort · w toggle weighting · q quit")
+            .style(Style::default().add_modifier(Modifier::DIM)),
+        help,
+    );
+
+    let rows = app.report.repos.iter().map(|repo| {
+        Row::new(vec![
+            repo.full_name.clone(),
+            repo.stars.to_string(),
+            format!("{:.1}%", repo.ratio_of_commits_from_user * 100.0),
+            repo.primary_language().unwrap_or("-").to_string(),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Min(20),
+            Constraint::Length(7),
+            Constraint::Length(8),
+            Constraint::Length(14),
+        ],
+    )
+    .header(
+        Row::new(vec!["Repository", "Stars", "Commits", "Language"])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+    )
+    .block(Block::default().borders(Borders::ALL).title("Repos"))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(table, repos, &mut app.table);
+
+    let (title, percents) = match app.selected_repo() {
+        Some(repo) => {
+            let total = repo.language_loc_map.values().sum::<u32>().max(1) as f32;
+            let percents = repo
+                .language_loc_map
+                .iter()
+                .map(|(lang, loc)| (lang.clone(), *loc as f32 / total * 100.0))
+                .collect();
+            (repo.full_name.clone(), percents)
+        }
+        None => ("No repo selected".to_string(), Vec::new()),
+    };
+    draw_languages(frame, repo_languages, &title, percents);
+
+    let percents = app
+        .report
+        .languages
+        .iter()
+        .map(|(lang, percent)| (lang.clone(), *percent))
+        .collect();
+    draw_languages(frame, all_languages, "All repos", percents);
+}
+
+fn draw_languages(frame: &mut Frame, area: Rect, title: &str, mut percents: Vec<(String, f32)>) {
+    percents.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    let name_width = percents
+        .iter()
+        .map(|(lang, _)| lang.len())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = percents
+        .into_iter()
+        .map(|(lang, percent)| {
+            let color = parse_hex(language_color_or_fallback(&lang))
+                .map(|(r, g, b)| Color::Rgb(r, g, b))
+                .unwrap_or(Color::Gray);
+            let cells = (percent / 100.0 * BAR_WIDTH as f32).round() as usize;
+            Line::from(vec![
+                Span::raw(format!("{lang:<name_width$} ")),
+                Span::styled("█".repeat(cells), Style::default().fg(color)),
+                Span::raw(format!(
+                    "{} {percent:.1}%",
+                    " ".repeat(BAR_WIDTH.saturating_sub(cells))
+                )),
+            ])
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title.to_string()),
+        ),
+        area,
+    );
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    error::{parse_json, parse_url, MetricsError},
+    report::language_group,
+    retry::RetryPolicy,
+    Report,
+};
+
+/// WakaTime names that differ from what the LOC backends call the same language.
+const ALIASES: &[(&str, &str)] = &[
+    ("Bash", "Shell"),
+    ("Sh", "Shell"),
+    ("Zsh", "Shell"),
+    ("Vue.js", "Vue"),
+    ("JSX", "JavaScript"),
+    ("TSX", "TypeScript"),
+    ("Jupyter", "Jupyter Notebook"),
+    ("Protocol Buffer", "Protocol Buffers"),
+];
+
+/// Period the editor time is summed over.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WakaTimeRange {
+    Last7Days,
+    Last30Days,
+    Last6Months,
+    #[default]
+    LastYear,
+    AllTime,
+}
+impl WakaTimeRange {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Last7Days => "last_7_days",
+            Self::Last30Days => "last_30_days",
+            Self::Last6Months => "last_6_months",
+            Self::LastYear => "last_year",
+            Self::AllTime => "all_time",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct WakaTimeStats {
+    /// WakaTime's name for the period, e.g. `last_year`
+    pub range: String,
+    /// Hours in the editor per language, named like the report's languages
+    pub hours: BTreeMap<String, f32>,
+}
+impl WakaTimeStats {
+    /// Merges languages into their `--language-groups` group, like the repos' languages.
+    pub fn group_languages(&mut self, groups: &[(String, Vec<String>)]) {
+        let mut grouped = BTreeMap::new();
+        for (lang, hours) in std::mem::take(&mut self.hours) {
+            *grouped.entry(language_group(lang, groups)).or_default() += hours;
+        }
+        self.hours = grouped;
+    }
+
+    pub fn total_hours(&self) -> f32 {
+        self.hours.values().sum()
+    }
+
+    /// Share of the editor time per language, largest first.
+    pub fn percentages(&self) -> Vec<(&str, f32)> {
+        let total = self.total_hours();
+        let mut percentages: Vec<_> = self
+            .hours
+            .iter()
+            .map(|(lang, hours)| (lang.as_str(), hours / total * 100.0))
+            .collect();
+        percentages.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        percentages
+    }
+}
+
+/// One language's share of the code and of the editor time.
+#[derive(Debug, Clone)]
+pub struct CombinedLanguage<'a> {
+    pub language: &'a str,
+    pub code_percent: f32,
+    pub time_percent: f32,
+    pub hours: f32,
+}
+
+/// Every language of the report or its WakaTime data side by side, most time spent first, then
+/// most code. Empty without WakaTime data.
+pub fn combined_languages(report: &Report) -> Vec<CombinedLanguage<'_>> {
+    let Some(stats) = &report.wakatime else {
+        return Vec::new();
+    };
+    let time: BTreeMap<&str, f32> = stats.percentages().into_iter().collect();
+    let mut languages: Vec<&str> = report.languages.keys().map(String::as_str).collect();
+    languages.extend(stats.hours.keys().map(String::as_str));
+    languages.sort_unstable();
+    languages.dedup();
+
+    let mut combined: Vec<_> = languages
+        .into_iter()
+        .map(|language| CombinedLanguage {
+            language,
+            code_percent: report.languages.get(language).copied().unwrap_or(0.0),
+            time_percent: time.get(language).copied().unwrap_or(0.0),
+            hours: stats.hours.get(language).copied().unwrap_or(0.0),
+        })
+        .collect();
+    combined.sort_by(|a, b| {
+        b.time_percent
+            .total_cmp(&a.time_percent)
+            .then(b.code_percent.total_cmp(&a.code_percent))
+    });
+    combined
+}
+
+#[derive(Deserialize)]
+struct StatsResponse {
+    data: StatsData,
+}
+
+#[derive(Deserialize)]
+struct StatsData {
+    #[serde(default)]
+    languages: Vec<LanguageTime>,
+}
+
+#[derive(Deserialize)]
+struct LanguageTime {
+    name: String,
+    total_seconds: f64,
+}
+
+/// Fetches the editor time per language of the account `api_key` belongs to. `known_languages`
+/// are the languages of the report, whose spelling wins over WakaTime's when they only differ in
+/// case.
+pub async fn wakatime_stats(
+    retry: RetryPolicy,
+    api_key: &str,
+    range: WakaTimeRange,
+    known_languages: &[&str],
+) -> Result<WakaTimeStats, MetricsError> {
+    let url = parse_url(&format!(
+        "https://wakatime.com/api/v1/users/current/stats/{}",
+        range.as_str()
+    ))?;
+    let request = Client::new()
+        .get(url.clone())
+        .header("User-Agent", "GitHub user stats scraper (reqwest/hyper)")
+        .basic_auth(api_key, None::<&str>);
+    let response = retry.send(request).await?;
+    let status = response.status();
+    let json = response.text().await?;
+    if !status.is_success() {
+        return Err(MetricsError::from_response(&url, status, &json));
+    }
+    let response: StatsResponse = parse_json(&url, &json)?;
+    info!(
+        "Found WakaTime data for {} languages",
+        response.data.languages.len()
+    );
+
+    let mut hours: BTreeMap<String, f32> = BTreeMap::new();
+    for language in response.data.languages {
+        if language.total_seconds <= 0.0 {
+            continue;
+        }
+        let name = canonical_language(&language.name, known_languages);
+        *hours.entry(name).or_default() += (language.total_seconds / 3600.0) as f32;
+    }
+    Ok(WakaTimeStats {
+        range: range.as_str().to_string(),
+        hours,
+    })
+}
+
+fn canonical_language(name: &str, known_languages: &[&str]) -> String {
+    let name = ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+        .map_or(name, |(_, canonical)| canonical);
+    known_languages
+        .iter()
+        .find(|known| known.eq_ignore_ascii_case(name))
+        .unwrap_or(&name)
+        .to_string()
+}
@@ -0,0 +1,732 @@
+//! Runs the GitHub client against a mock server serving trimmed down real API payloads from
+//! `tests/fixtures`, where `{base}` stands for the mock server's address.
+
+use futures::{StreamExt, TryStreamExt};
+use github_user_scraper::{
+    activity::activity_stats,
+    build_report,
+    containers::collect_containers,
+    discussions::collect_discussions,
+    filter::{OrgFilter, RepoFilter},
+    github::{collect_repos, process_repos},
+    loc::LocBackend,
+    models::{ContributorData, RepoData},
+    packages::{collect_packages, RegistryUrls},
+    releases::collect_releases,
+    report::{summarize_languages, LanguageRanking},
+    sponsors::collect_sponsors,
+    traffic::collect_traffic,
+    GitHub, Report,
+};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{body_string_contains, method, path, query_param, query_param_is_missing},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn fixture(server: &MockServer, name: &str) -> Value {
+    let path = format!("{}/tests/fixtures/{name}.json", env!("CARGO_MANIFEST_DIR"));
+    let body = std::fs::read_to_string(&path).unwrap_or_else(|err| panic!("{path}: {err}"));
+    serde_json::from_str(&body.replace("{base}", &server.uri())).unwrap()
+}
+
+fn connection(server: &MockServer) -> GitHub {
+    GitHub::new("octocat", None)
+        .with_base_url(&server.uri())
+        .with_cache(None)
+        .with_loc_backend(LocBackend::Linguist)
+}
+
+async fn mock_json(server: &MockServer, at: &str, body: Value) {
+    Mock::given(method("GET"))
+        .and(path(at))
+        .respond_with(ResponseTemplate::new(200).set_body_json(body))
+        .mount(server)
+        .await;
+}
+
+/// The user with one repo of their own, a fork, and one repo in an organization.
+async fn mock_user(server: &MockServer) {
+    mock_json(server, "/users/octocat", fixture(server, "user")).await;
+    mock_json(
+        server,
+        "/users/octocat/repos",
+        fixture(server, "user_repos"),
+    )
+    .await;
+    mock_json(server, "/users/octocat/orgs", fixture(server, "user_orgs")).await;
+    mock_json(server, "/orgs/octo-lab/repos", fixture(server, "org_repos")).await;
+
+    let repos = [
+        (
+            "octocat/hello-world",
+            json!([
+                { "login": "octocat", "id": 583231, "type": "User", "contributions": 30 },
+                { "login": "hubot", "id": 1, "type": "User", "contributions": 10 }
+            ]),
+            // 100 and 10 lines at 40 bytes per line
+            json!({ "Rust": 4000, "Shell": 400 }),
+            3,
+        ),
+        (
+            "octo-lab/tools",
+            json!([{ "login": "octocat", "id": 583231, "type": "User", "contributions": 5 }]),
+            json!({ "Python": 1200, "Rust": 800 }),
+            1,
+        ),
+    ];
+    for (full_name, contributors, languages, watchers) in repos {
+        let repo = format!("/repos/{full_name}");
+        mock_json(server, &format!("{repo}/contributors"), contributors).await;
+        mock_json(server, &format!("{repo}/languages"), languages).await;
+        mock_json(
+            server,
+            &repo,
+            json!({ "full_name": full_name, "subscribers_count": watchers }),
+        )
+        .await;
+    }
+}
+
+#[tokio::test]
+async fn get_all_follows_pagination() {
+    let server = MockServer::start().await;
+    let url = format!("{}/repos/octocat/hello-world/contributors", server.uri());
+    Mock::given(method("GET"))
+        .and(path("/repos/octocat/hello-world/contributors"))
+        .and(query_param_is_missing("page"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header(
+                    "Link",
+                    format!(r#"<{url}?page=2>; rel="next", <{url}?page=2>; rel="last""#),
+                )
+                .set_body_json(json!([{ "login": "octocat", "contributions": 30 }])),
+        )
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octocat/hello-world/contributors"))
+        .and(query_param("page", "2"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!([{ "login": "hubot", "contributions": 10 }])),
+        )
+        .mount(&server)
+        .await;
+
+    let contributors: Vec<ContributorData> = connection(&server).get_all(url).await.unwrap();
+    let logins: Vec<&str> = contributors.iter().map(|c| c.login.as_str()).collect();
+    assert_eq!(logins, ["octocat", "hubot"]);
+}
+
+#[tokio::test]
+async fn collect_repos_includes_organization_repos() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+
+    let repos = collect_repos(&connection(&server)).await.unwrap();
+    let names: Vec<&str> = repos.iter().map(|repo| repo.full_name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "octocat/hello-world",
+            "octocat/spoon-knife",
+            "octo-lab/tools"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn installation_tokens_list_the_public_repos() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    // What the Actions `GITHUB_TOKEN` gets
+    Mock::given(method("GET"))
+        .and(path("/user"))
+        .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+            "message": "Resource not accessible by integration"
+        })))
+        .mount(&server)
+        .await;
+    let connection = GitHub::new("octocat", Some("token".to_string()))
+        .with_base_url(&server.uri())
+        .with_cache(None)
+        .with_loc_backend(LocBackend::Linguist);
+
+    let repos = collect_repos(&connection).await.unwrap();
+    assert_eq!(repos.len(), 3);
+}
+
+#[tokio::test]
+async fn repos_stream_stops_paginating_when_dropped() {
+    let server = MockServer::start().await;
+    mock_json(&server, "/users/octocat", fixture(&server, "user")).await;
+    mock_json(
+        &server,
+        "/users/octocat/repos",
+        fixture(&server, "user_repos"),
+    )
+    .await;
+    Mock::given(method("GET"))
+        .and(path("/users/octocat/orgs"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture(&server, "user_orgs")))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let connection = connection(&server);
+    let repos: Vec<RepoData> = connection.repos().take(1).try_collect().await.unwrap();
+    assert_eq!(repos[0].full_name, "octocat/hello-world");
+}
+
+#[tokio::test]
+async fn excluded_organizations_are_never_listed() {
+    let server = MockServer::start().await;
+    mock_json(&server, "/users/octocat", fixture(&server, "user")).await;
+    mock_json(
+        &server,
+        "/users/octocat/repos",
+        fixture(&server, "user_repos"),
+    )
+    .await;
+    mock_json(
+        &server,
+        "/users/octocat/orgs",
+        fixture(&server, "user_orgs"),
+    )
+    .await;
+    Mock::given(method("GET"))
+        .and(path("/orgs/octo-lab/repos"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture(&server, "org_repos")))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    let connection = connection(&server).with_org_filter(OrgFilter {
+        exclude: vec!["Octo-Lab".to_string()],
+        ..OrgFilter::default()
+    });
+    let repos = collect_repos(&connection).await.unwrap();
+    let names: Vec<&str> = repos.iter().map(|repo| repo.full_name.as_str()).collect();
+    assert_eq!(names, ["octocat/hello-world", "octocat/spoon-knife"]);
+}
+
+#[tokio::test]
+async fn repos_listed_by_user_and_organization_are_counted_once() {
+    let server = MockServer::start().await;
+    mock_json(&server, "/users/octocat", fixture(&server, "user")).await;
+    mock_json(
+        &server,
+        "/users/octocat/repos",
+        fixture(&server, "user_repos"),
+    )
+    .await;
+    mock_json(
+        &server,
+        "/users/octocat/orgs",
+        fixture(&server, "user_orgs"),
+    )
+    .await;
+    // The organization lists one of the user's repos again
+    let mut org_repos = fixture(&server, "org_repos");
+    org_repos
+        .as_array_mut()
+        .unwrap()
+        .push(fixture(&server, "user_repos")[0].clone());
+    mock_json(&server, "/orgs/octo-lab/repos", org_repos).await;
+
+    let repos = collect_repos(&connection(&server)).await.unwrap();
+    let names: Vec<&str> = repos.iter().map(|repo| repo.full_name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "octocat/hello-world",
+            "octocat/spoon-knife",
+            "octo-lab/tools"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn report_sums_languages_of_unfiltered_repos() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    let connection = connection(&server);
+
+    // The fork is left out by the default filter
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let report = build_report(process_repos(&connection, repos, 2).await.unwrap(), false);
+
+    assert_eq!(report.repos.len(), 2);
+    assert_eq!(report.language_loc["Rust"], 120);
+    assert_eq!(report.language_loc["Python"], 30);
+    assert_eq!(report.language_loc["Shell"], 10);
+    assert!((report.languages["Rust"] - 75.0).abs() < 0.01);
+    assert_eq!(report.total_stars, 15.0);
+    assert_eq!(report.total_watchers, 4.0);
+}
+
+#[tokio::test]
+async fn ranking_by_stars_splits_stars_by_lines_of_code() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    let connection = connection(&server);
+
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let mut report = Report {
+        ranked_by: LanguageRanking::Stars,
+        ..Report::new(process_repos(&connection, repos, 2).await.unwrap(), false)
+    };
+    summarize_languages(&mut report);
+
+    // tools has 5 of the 15 stars and is 60% Python
+    assert!((report.languages["Python"] - 20.0).abs() < 0.01);
+    assert_eq!(report.language_loc["Python"], 30);
+}
+
+#[tokio::test]
+async fn repos_whose_watchers_cant_be_read_count_none() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octocat/hello-world"))
+        .respond_with(ResponseTemplate::new(404))
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    mock_user(&server).await;
+    let connection = connection(&server);
+
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let report = build_report(process_repos(&connection, repos, 2).await.unwrap(), false);
+
+    assert_eq!(report.repos.len(), 2);
+    assert_eq!(report.total_watchers, 1.0);
+}
+
+#[tokio::test]
+async fn activity_finds_oldest_newest_and_recent_repos() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    let connection = connection(&server);
+
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let infos = process_repos(&connection, repos, 2).await.unwrap();
+    // Less than 90 days after hello-world was last pushed to, but not tools
+    let now = humantime::parse_rfc3339("2024-09-10T00:00:00Z").unwrap();
+    let activity = activity_stats(&infos, now);
+
+    let oldest = activity.oldest_repo.unwrap();
+    assert_eq!(oldest.full_name, "octocat/hello-world");
+    assert_eq!(oldest.created_date(), "2011-01-26");
+    assert_eq!(activity.newest_repo.unwrap().full_name, "octo-lab/tools");
+    assert_eq!(activity.recently_pushed, 1);
+}
+
+#[tokio::test]
+async fn weighted_report_scales_by_share_of_commits() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    let connection = connection(&server).with_weighted(true);
+
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let report = build_report(process_repos(&connection, repos, 2).await.unwrap(), true);
+
+    // octocat made 30 of 40 commits to hello-world and every commit to tools
+    assert_eq!(report.language_loc["Rust"], 95);
+    assert_eq!(report.total_stars, 12.5);
+}
+
+#[tokio::test]
+async fn repos_the_user_never_committed_to_are_dropped() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    let connection = GitHub::new("hubot", None)
+        .with_base_url(&server.uri())
+        .with_cache(None)
+        .with_loc_backend(LocBackend::Linguist);
+
+    // hubot only committed to hello-world, not to tools
+    let mut repos: Vec<RepoData> = serde_json::from_value(fixture(&server, "user_repos")).unwrap();
+    repos.extend(serde_json::from_value::<Vec<RepoData>>(fixture(&server, "org_repos")).unwrap());
+    let repos = RepoFilter::default().apply(repos);
+    let infos = process_repos(&connection, repos, 1).await.unwrap();
+    assert_eq!(infos.len(), 1);
+    assert_eq!(infos[0].full_name, "octocat/hello-world");
+    assert!((infos[0].ratio_of_commits_from_user - 0.25).abs() < f32::EPSILON);
+}
+
+#[tokio::test]
+async fn codetabs_counts_leave_out_the_total() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/v1/loc/"))
+        .and(query_param("github", "octocat/hello-world"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(fixture(&server, "codetabs")))
+        .mount(&server)
+        .await;
+    let connection = GitHub::new("octocat", None)
+        .with_cache(None)
+        .with_codetabs_url(&format!("{}/v1/loc", server.uri()));
+
+    let repos: Vec<RepoData> = serde_json::from_value(fixture(&server, "user_repos")).unwrap();
+    let (langs, _) = connection
+        .language_loc_map(&repos[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(langs.len(), 2);
+    assert_eq!(langs["Rust"], 1200);
+    assert_eq!(langs["Markdown"], 45);
+}
+
+#[tokio::test]
+async fn empty_repos_have_no_contributors() {
+    let server = MockServer::start().await;
+    // GitHub answers with 204 No Content rather than an empty list
+    Mock::given(method("GET"))
+        .and(path("/repos/octocat/empty/contributors"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+
+    let url = format!("{}/repos/octocat/empty/contributors", server.uri());
+    let contributors: Vec<ContributorData> = connection(&server).get_all(url).await.unwrap();
+    assert!(contributors.is_empty());
+}
+
+#[tokio::test]
+async fn collaborator_repos_are_always_weighted() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    let connection = GitHub::new("hubot", None)
+        .with_base_url(&server.uri())
+        .with_cache(None)
+        .with_loc_backend(LocBackend::Linguist);
+
+    // hello-world belongs to octocat, and hubot made 10 of its 40 commits
+    let repos: Vec<RepoData> = serde_json::from_value(fixture(&server, "user_repos")).unwrap();
+    let repos = RepoFilter::default().apply(repos);
+    let report = build_report(process_repos(&connection, repos, 1).await.unwrap(), false);
+
+    assert!(report.repos[0].collaborator);
+    assert_eq!(report.language_loc["Rust"], 25);
+    assert_eq!(report.total_stars, 2.5);
+}
+
+#[tokio::test]
+async fn releases_count_published_releases_and_downloads() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    mock_json(
+        &server,
+        "/repos/octocat/hello-world/releases",
+        json!([
+            { "tag_name": "v2.0.0-draft", "draft": true, "published_at": null, "assets": [] },
+            {
+                "tag_name": "v1.1.0",
+                "draft": false,
+                "published_at": "2024-05-01T10:00:00Z",
+                "assets": [{ "download_count": 40 }, { "download_count": 2 }]
+            },
+            {
+                "tag_name": "v1.0.0",
+                "draft": false,
+                "published_at": "2023-01-01T10:00:00Z",
+                "assets": [{ "download_count": 100 }]
+            }
+        ]),
+    )
+    .await;
+    mock_json(&server, "/repos/octo-lab/tools/releases", json!([])).await;
+    let connection = connection(&server);
+
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let infos = process_repos(&connection, repos, 2).await.unwrap();
+    let releases = collect_releases(&connection, &infos, 2).await.unwrap();
+
+    assert_eq!(releases.releases, 2);
+    assert_eq!(releases.downloads, 142);
+    assert_eq!(releases.repos.len(), 1);
+    assert_eq!(releases.repos[0].latest_tag.as_deref(), Some("v1.1.0"));
+}
+
+#[tokio::test]
+async fn packages_are_found_through_cargo_workspaces() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    let contents = "/repos/octocat/hello-world/contents";
+    let raw = |body: &str| ResponseTemplate::new(200).set_body_string(body);
+    Mock::given(method("GET"))
+        .and(path(format!("{contents}/Cargo.toml")))
+        .respond_with(raw(
+            "[package]\nname = \"hello-world\"\n\n[workspace]\nmembers = [\"crates/*\", \"xtask\"]\n",
+        ))
+        .mount(&server)
+        .await;
+    mock_json(
+        &server,
+        &format!("{contents}/crates"),
+        json!([
+            { "path": "crates/hello-cli", "type": "dir" },
+            { "path": "crates/README.md", "type": "file" }
+        ]),
+    )
+    .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{contents}/crates/hello-cli/Cargo.toml")))
+        .respond_with(raw("[package]\nname = \"hello-cli\"\n"))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("{contents}/xtask/Cargo.toml")))
+        .respond_with(raw("[package]\nname = \"xtask\"\npublish = false\n"))
+        .mount(&server)
+        .await;
+    mock_json(
+        &server,
+        "/crates-io/crates/hello-world",
+        json!({ "crate": { "downloads": 5000, "recent_downloads": 300 } }),
+    )
+    .await;
+    mock_json(
+        &server,
+        "/crates-io/crates/hello-cli",
+        json!({ "crate": { "downloads": 700, "recent_downloads": 20 } }),
+    )
+    .await;
+    let connection = connection(&server);
+
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let infos = process_repos(&connection, repos, 2).await.unwrap();
+    let registries = RegistryUrls {
+        crates_io: format!("{}/crates-io", server.uri()),
+        ..RegistryUrls::default()
+    };
+    let packages = collect_packages(&connection, &infos, 2, &registries)
+        .await
+        .unwrap();
+
+    let names: Vec<&str> = packages.packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["hello-world", "hello-cli"]);
+    assert_eq!(packages.downloads, 5700);
+    assert_eq!(
+        packages.repo_downloads("octocat/hello-world"),
+        Some((5700, 320))
+    );
+}
+
+#[tokio::test]
+async fn pypi_packages_are_named_by_pyproject() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    Mock::given(method("GET"))
+        .and(path("/repos/octo-lab/tools/contents/pyproject.toml"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string("[project]\nname = \"Octo_Tools\"\n"),
+        )
+        .mount(&server)
+        .await;
+    // pypistats only knows normalized names
+    mock_json(
+        &server,
+        "/pypistats/packages/octo-tools/overall",
+        json!({
+            "data": [
+                { "category": "without_mirrors", "date": "2000-01-01", "downloads": 50 },
+                { "category": "without_mirrors", "date": "2999-01-01", "downloads": 7 }
+            ]
+        }),
+    )
+    .await;
+    let connection = connection(&server);
+
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let infos = process_repos(&connection, repos, 2).await.unwrap();
+    let registries = RegistryUrls {
+        pypistats: format!("{}/pypistats", server.uri()),
+        ..RegistryUrls::default()
+    };
+    let packages = collect_packages(&connection, &infos, 2, &registries)
+        .await
+        .unwrap();
+
+    assert_eq!(packages.packages.len(), 1);
+    assert_eq!(packages.packages[0].registry, "PyPI");
+    assert_eq!(packages.packages[0].name, "octo-tools");
+    assert_eq!(packages.downloads, 57);
+    assert_eq!(packages.recent_downloads, 7);
+}
+
+#[tokio::test]
+async fn mapped_container_images_report_docker_hub_pulls() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    // Official images live under `library/`
+    mock_json(
+        &server,
+        "/docker/repositories/library/octo-tools/",
+        json!({ "name": "octo-tools", "pull_count": 1234 }),
+    )
+    .await;
+    let connection = connection(&server);
+
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let infos = process_repos(&connection, repos, 2).await.unwrap();
+    let images = vec![(
+        "Octo-Lab/Tools".to_string(),
+        vec![
+            "octo-tools:latest".to_string(),
+            "quay.io/octo/tools".to_string(),
+        ],
+    )];
+    let docker_hub = format!("{}/docker", server.uri());
+    let containers = collect_containers(&connection, &infos, &images, 2, &docker_hub)
+        .await
+        .unwrap();
+
+    assert_eq!(containers.images.len(), 1);
+    assert_eq!(containers.images[0].image, "docker.io/library/octo-tools");
+    assert_eq!(containers.images[0].full_name, "octo-lab/tools");
+    assert_eq!(containers.pulls, 1234);
+}
+
+#[tokio::test]
+async fn sponsors_add_up_monthly_tiers() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "data": { "user": { "sponsorshipsAsMaintainer": {
+                "totalCount": 4,
+                "pageInfo": { "hasNextPage": false, "endCursor": null },
+                "nodes": [
+                    { "isOneTimePayment": false, "tier": { "name": "$5 a month", "monthlyPriceInCents": 500 } },
+                    { "isOneTimePayment": false, "tier": { "name": "$5 a month", "monthlyPriceInCents": 500 } },
+                    { "isOneTimePayment": false, "tier": { "name": "$25 a month", "monthlyPriceInCents": 2500 } },
+                    { "isOneTimePayment": true, "tier": { "name": "$100 one time", "monthlyPriceInCents": 10000 } }
+                ]
+            } } }
+        })))
+        .mount(&server)
+        .await;
+    let connection = GitHub::new("octocat", Some("token".to_string()))
+        .with_base_url(&server.uri())
+        .with_cache(None);
+
+    let sponsors = collect_sponsors(&connection).await.unwrap();
+
+    assert_eq!(sponsors.sponsors, 4);
+    assert_eq!(sponsors.monthly_cents, 3500);
+    assert_eq!(sponsors.monthly_dollars(), "$35.00");
+    let tiers: Vec<(&str, u32)> = sponsors
+        .tiers
+        .iter()
+        .map(|tier| (tier.name.as_str(), tier.sponsors))
+        .collect();
+    assert_eq!(tiers, [("$25 a month", 1), ("$5 a month", 2)]);
+}
+
+#[tokio::test]
+async fn discussions_only_count_analyzed_repos() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    let page = |nodes: Value| {
+        json!({
+            "data": { "user": { "items": {
+                "pageInfo": { "hasNextPage": false, "endCursor": null },
+                "nodes": nodes
+            } } }
+        })
+    };
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .and(body_string_contains("repositoryDiscussions("))
+        .respond_with(ResponseTemplate::new(200).set_body_json(page(json!([
+            { "repository": { "nameWithOwner": "Octo-Lab/Tools" } },
+            { "repository": { "nameWithOwner": "someone/else" } }
+        ]))))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/graphql"))
+        .and(body_string_contains("repositoryDiscussionComments("))
+        .respond_with(ResponseTemplate::new(200).set_body_json(page(json!([
+            { "discussion": { "repository": { "nameWithOwner": "octo-lab/tools" } } },
+            { "discussion": { "repository": { "nameWithOwner": "octo-lab/tools" } } }
+        ]))))
+        .mount(&server)
+        .await;
+    let connection = connection(&server);
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let infos = process_repos(&connection, repos, 2).await.unwrap();
+
+    // The GraphQL API needs a token
+    let connection = GitHub::new("octocat", Some("token".to_string()))
+        .with_base_url(&server.uri())
+        .with_cache(None);
+    let discussions = collect_discussions(&connection, &infos).await.unwrap();
+
+    assert_eq!(discussions.created, 1);
+    assert_eq!(discussions.answered, 2);
+    assert_eq!(discussions.repos.len(), 1);
+    assert_eq!(discussions.repos[0].full_name, "octo-lab/tools");
+}
+
+#[tokio::test]
+async fn traffic_merges_views_and_clones_by_day() {
+    let server = MockServer::start().await;
+    mock_user(&server).await;
+    mock_json(
+        &server,
+        "/repos/octo-lab/tools/traffic/views",
+        json!({
+            "count": 30, "uniques": 4,
+            "views": [
+                { "timestamp": "2024-05-01T00:00:00Z", "count": 10, "uniques": 3 },
+                { "timestamp": "2024-05-02T00:00:00Z", "count": 20, "uniques": 2 }
+            ]
+        }),
+    )
+    .await;
+    mock_json(
+        &server,
+        "/repos/octo-lab/tools/traffic/clones",
+        json!({
+            "count": 5, "uniques": 1,
+            "clones": [{ "timestamp": "2024-05-03T00:00:00Z", "count": 5, "uniques": 1 }]
+        }),
+    )
+    .await;
+    // Traffic of repos the token can't push to is forbidden
+    Mock::given(method("GET"))
+        .and(path("/repos/octocat/hello-world/traffic/views"))
+        .respond_with(
+            ResponseTemplate::new(403)
+                .set_body_json(json!({ "message": "Must have push access to repository" })),
+        )
+        .mount(&server)
+        .await;
+    let connection = connection(&server);
+
+    let repos = RepoFilter::default().apply(collect_repos(&connection).await.unwrap());
+    let infos = process_repos(&connection, repos, 2).await.unwrap();
+    let traffic = collect_traffic(&connection, &infos, 2).await.unwrap();
+
+    assert_eq!(traffic.views, 30);
+    assert_eq!(traffic.clones, 5);
+    assert_eq!(traffic.repos.len(), 1);
+    let days: Vec<(&str, u64, u64)> = traffic.repos[0]
+        .days
+        .iter()
+        .map(|day| (day.date.as_str(), day.views, day.clones))
+        .collect();
+    assert_eq!(
+        days,
+        [
+            ("2024-05-01", 10, 0),
+            ("2024-05-02", 20, 0),
+            ("2024-05-03", 0, 5)
+        ]
+    );
+}